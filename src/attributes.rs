@@ -0,0 +1,130 @@
+//! Attributes known to the compiler.
+//!
+//! The parser keeps every `#name args...` it finds, the registry tells which of them mean something
+//! and where they may be given. Unknown attributes are only warned about,
+//! so code using attributes of newer tools still compiles
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use bitflags::bitflags;
+use crate::parser::*;
+use crate::visit::{walk_expr, walk_stmt, Visit};
+
+bitflags! {
+    /// Kinds of syntax an attribute can be given to
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct AttributeTargets: u16 {
+        const FUNCTION = 0b0000_0000_0001;
+        const VALUE = 0b0000_0000_0010;
+        const TYPE = 0b0000_0000_0100;
+        const TRAIT = 0b0000_0000_1000;
+        const IMPL = 0b0000_0001_0000;
+        const SIGNATURE = 0b0000_0010_0000;
+        const MOD = 0b0000_0100_0000;
+        const USE = 0b0000_1000_0000;
+        const EXPR = 0b0001_0000_0000;
+        const DECLARATION = Self::FUNCTION.bits() | Self::VALUE.bits() | Self::TYPE.bits() | Self::TRAIT.bits()
+            | Self::IMPL.bits() | Self::SIGNATURE.bits() | Self::MOD.bits() | Self::USE.bits();
+    }
+}
+
+impl AttributeTargets {
+    /// Target of the attributes of `stmt`
+    pub fn of(stmt: &Stmt) -> Self {
+        match stmt {
+            Stmt::Let(_) => AttributeTargets::VALUE,
+            Stmt::Function(_) => AttributeTargets::FUNCTION,
+            Stmt::Mod(_) => AttributeTargets::MOD,
+            Stmt::Use(_) => AttributeTargets::USE,
+            Stmt::Type(_) => AttributeTargets::TYPE,
+            Stmt::Trait(_) => AttributeTargets::TRAIT,
+            Stmt::Impl(_) => AttributeTargets::IMPL,
+            Stmt::Signature(_) => AttributeTargets::SIGNATURE,
+            Stmt::Expr(_) => AttributeTargets::EXPR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeSpec {
+    /// Allowed number of arguments, all of them strings
+    pub args: RangeInclusive<usize>,
+    pub targets: AttributeTargets,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AttributeRegistry {
+    specs: HashMap<String, AttributeSpec>,
+}
+
+impl AttributeRegistry {
+    /// Attributes the compiler itself understands:
+    /// `#inline`, `#test`, `#deprecated ["message"]` and `#extern "abi"`
+    pub fn builtin() -> Self {
+        let mut registry = AttributeRegistry::default();
+        registry.register("inline", 0..=0, AttributeTargets::FUNCTION);
+        registry.register("test", 0..=0, AttributeTargets::FUNCTION | AttributeTargets::VALUE);
+        registry.register("deprecated", 0..=1, AttributeTargets::DECLARATION);
+        registry.register("extern", 1..=1, AttributeTargets::FUNCTION | AttributeTargets::SIGNATURE);
+        registry
+    }
+
+    /// Adds or replaces attribute `name`
+    pub fn register(&mut self, name: &str, args: RangeInclusive<usize>, targets: AttributeTargets) {
+        self.specs.insert(name.to_string(), AttributeSpec { args, targets });
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AttributeSpec> {
+        self.specs.get(name)
+    }
+
+    /// Checks the attributes of `attrs` given to `target`
+    pub fn check_attrs(&self, attrs: &[Attribute], target: AttributeTargets, diagnostics: &mut Vec<Diagnostic>) {
+        for attr in attrs {
+            let Some(spec) = self.get(&attr.name.name) else {
+                let kind = ParseErrorKind::UnknownAttribute { name: attr.name.name.clone() };
+                diagnostics.push(Diagnostic { severity: Severity::Warning, kind, span: attr.name.span });
+                continue;
+            };
+            let strings = attr.args.iter().all(|arg| matches!(arg.kind, ExprKind::Literal(Literal::String(_))));
+            if !strings || !spec.args.contains(&attr.args.len()) {
+                let kind = ParseErrorKind::AttributeArgs { min: *spec.args.start(), max: *spec.args.end() };
+                diagnostics.push(Diagnostic { severity: Severity::Error, kind, span: attr.span });
+            }
+            if !spec.targets.intersects(target) {
+                let kind = ParseErrorKind::MisplacedAttribute;
+                diagnostics.push(Diagnostic { severity: Severity::Error, kind, span: attr.span });
+            }
+        }
+    }
+
+    /// Checks every attribute in `ast`, diagnostics come in source order
+    pub fn check(&self, ast: &Ast) -> Vec<Diagnostic> {
+        let mut checker = Checker { registry: self, diagnostics: vec![] };
+        checker.visit_ast(ast);
+        let mut diagnostics = checker.diagnostics;
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.span.row, diagnostic.span.num));
+        diagnostics
+    }
+}
+
+struct Checker<'r> {
+    registry: &'r AttributeRegistry,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Visit for Checker<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if !matches!(stmt, Stmt::Expr(_)) {
+            self.registry.check_attrs(stmt.attrs(), AttributeTargets::of(stmt), &mut self.diagnostics);
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Annotated { attrs, .. } = &expr.kind {
+            self.registry.check_attrs(attrs, AttributeTargets::EXPR, &mut self.diagnostics);
+        }
+        walk_expr(self, expr);
+    }
+}
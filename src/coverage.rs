@@ -0,0 +1,355 @@
+//! Coverage of the clauses of functions.
+//!
+//! The parameters of a clause stand for the arguments it matches, guards comparing an integer or character
+//! binder with constants like `('n > 0)` narrowing them. A clause the ones before it match all arguments of
+//! is never reached, and an example of the arguments no clause matches is reported. So is a call, a partial
+//! application defined with `:=` included, whose arguments are constants matching no clause
+
+mod space;
+
+use std::collections::HashMap;
+use crate::ImStr;
+use crate::parser::*;
+use crate::resolve::{Def, DefKind, DefScope, Resolution};
+use crate::visit::{walk_expr, walk_function, walk_let, Visit};
+use self::space::{format_args, overlaps, IntSet, Pat, Scalar, Variants};
+
+/// Depth of `:=` definitions followed to find a constant or a partially applied function
+const DEFINITION_LIMIT: usize = 32;
+
+/// Checks the clauses of the functions of `ast` and the calls of them. Constructors of the types of `ast`
+/// and `prelude` make the sum types clauses can cover all values of
+pub fn check(ast: &Ast, prelude: &Ast, resolution: &Resolution) -> Vec<Diagnostic> {
+    let mut variants = Variants::default();
+    for stmt in prelude.stmts.iter().chain(&ast.stmts) {
+        if let Stmt::Type(TypeDecl { body: TypeBody::Variants(constructors), .. }) = stmt {
+            variants.insert(constructors.iter().map(|variant| (variant.name.name.clone(), variant.fields.len())).collect());
+        }
+    }
+    let globals = resolution.defs.iter()
+        .filter(|def| def.scope == DefScope::Module && matches!(def.kind, DefKind::Function | DefKind::Value))
+        .map(|def| (def.name.clone(), def))
+        .collect();
+    let mut checker = Checker {
+        pass: Pass::Definitions,
+        variants,
+        resolution,
+        globals,
+        definitions: HashMap::new(),
+        functions: HashMap::new(),
+        diagnostics: vec![],
+    };
+    // guards can compare with constants and calls can come before the functions they call
+    for pass in [Pass::Definitions, Pass::Clauses, Pass::Calls] {
+        checker.pass = pass;
+        checker.visit_ast(ast);
+    }
+
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span.row, diagnostic.span.num));
+    diagnostics
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pass {
+    /// Gathers the `:=` definitions
+    Definitions,
+    /// Checks the clauses of each function
+    Clauses,
+    /// Checks the calls of functions defined by clauses
+    Calls,
+}
+
+/// Clauses of a function as the arguments each of them may match
+struct Clauses {
+    name: ImStr,
+    arity: usize,
+    rows: Vec<Vec<Pat>>,
+}
+
+struct Checker<'r> {
+    pass: Pass,
+    variants: Variants,
+    resolution: &'r Resolution,
+    /// Module functions and `:=` definitions by name, for the functions of dot calls
+    globals: HashMap<ImStr, &'r Def>,
+    /// Values of `:=` definitions which can be constants or partial applications, by the span of the name
+    definitions: HashMap<Span, Expr>,
+    /// Functions defined by clauses, by the span of the name
+    functions: HashMap<Span, Clauses>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, severity: Severity, kind: ParseErrorKind, span: Span) {
+        self.diagnostics.push(Diagnostic { severity, kind, span });
+    }
+
+    /// Reports the clauses never reached and the arguments no clause matches, the clauses with a guard
+    /// which isn't understood only count as matching for calls
+    fn clauses(&mut self, function: &FunctionDef) {
+        let arity = match function.params.is_empty() {
+            true => function.clauses[0].params.len(),
+            false => function.params.len(),
+        };
+        let (mut exact_rows, mut rows) = (vec![], vec![]);
+        for clause in function.clauses.iter().filter(|clause| clause.params.len() == arity) {
+            let (row, exact): (Vec<_>, Vec<_>) = clause.params.iter().map(|param| self.pattern(param)).unzip();
+            if self.variants.uncovered(&exact_rows, &row).is_none() {
+                self.report(Severity::Warning, ParseErrorKind::UnreachableClause, clause.span);
+            } else if exact.into_iter().all(|exact| exact) {
+                exact_rows.push(row.clone());
+            }
+            rows.push(row);
+        }
+        if let Some(missing) = self.variants.uncovered(&exact_rows, &vec![Pat::Any; arity]) {
+            let missing = format_args(&missing).into();
+            let kind = ParseErrorKind::NonExhaustiveClauses { function: function.name.name.clone(), missing };
+            self.report(Severity::Warning, kind, function.name.span);
+        }
+        self.functions.insert(function.name.span, Clauses { name: function.name.name.clone(), arity, rows });
+    }
+
+    /// Values `pattern` matches and whether exactly, guards which aren't understood are left out
+    fn pattern(&self, pattern: &Pattern) -> (Pat, bool) {
+        match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::Optional { .. } => (Pat::Any, true),
+            PatternKind::Bind { name, pattern, guard } => {
+                let (inner, exact) = pattern.as_deref().map_or((Pat::Any, true), |pattern| self.pattern(pattern));
+                let Some(guard) = guard else { return (inner, exact) };
+                match (self.guard(guard, &name.name), inner) {
+                    (Some((set, scalar)), Pat::Any) => (Pat::Set(set, scalar), exact),
+                    (Some((set, scalar)), Pat::Set(inner, inner_scalar)) if scalar == inner_scalar => {
+                        (Pat::Set(set.intersection(&inner), scalar), exact)
+                    }
+                    (_, inner) => (inner, false),
+                }
+            }
+            PatternKind::Literal { value, negative } => match literal(value, *negative) {
+                Some(pat) => (pat, true),
+                None => (Pat::Any, false),
+            },
+            PatternKind::Constructor { name, args } => {
+                let (args, exact): (Vec<_>, Vec<_>) = args.iter().map(|arg| self.pattern(arg)).unzip();
+                (Pat::Constructor(name.name.clone(), args), exact.into_iter().all(|exact| exact))
+            }
+            PatternKind::Unit => (Pat::Tuple(vec![]), true),
+            PatternKind::Tuple(elements) => {
+                let (elements, exact): (Vec<_>, Vec<_>) = elements.iter().map(|element| self.pattern(element)).unzip();
+                (Pat::Tuple(elements), exact.into_iter().all(|exact| exact))
+            }
+            PatternKind::Range { start, end, inclusive } => {
+                let [start, end] = [start, end].map(|bound| bound.as_deref().map(|bound| match &bound.kind {
+                    PatternKind::Literal { value, negative } => scalar(value, *negative),
+                    _ => None,
+                }));
+                // float ranges
+                if matches!(start, Some(None)) || matches!(end, Some(None)) {
+                    return (Pat::Any, false);
+                }
+                let (start, end) = (start.flatten(), end.flatten());
+                let Some((_, scalar)) = start.or(end) else { return (Pat::Any, true) };
+                let last = match end {
+                    Some((end, _)) if !inclusive => end.saturating_sub(1),
+                    Some((end, _)) => end,
+                    None => i128::MAX,
+                };
+                let set = IntSet::interval(start.map_or(i128::MIN, |(start, _)| start), last);
+                (Pat::Set(set.intersection(&IntSet::full(scalar)), scalar), true)
+            }
+            // lists are matched by their length, which isn't tracked
+            PatternKind::List(_) | PatternKind::Error => (Pat::Any, false),
+        }
+    }
+
+    /// Values of binder `name` passing `guard`, `None` unless it compares the binder with constants
+    fn guard(&self, guard: &Expr, name: &str) -> Option<(IntSet, Scalar)> {
+        match &guard.kind {
+            ExprKind::Binary { op, lhs, rhs } if matches!(op.name.as_str(), "and" | "&&" | "or" | "||") => {
+                let ((lhs, scalar), (rhs, rhs_scalar)) = (self.guard(lhs, name)?, self.guard(rhs, name)?);
+                if scalar != rhs_scalar {
+                    return None;
+                }
+                match op.name.as_str() {
+                    "and" | "&&" => Some((lhs.intersection(&rhs), scalar)),
+                    _ => Some((lhs.union(&rhs), scalar)),
+                }
+            }
+            ExprKind::Unary { op, operand } if matches!(op.name.as_str(), "not" | "!") => {
+                let (set, scalar) = self.guard(operand, name)?;
+                Some((set.complement(scalar), scalar))
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let binder = |expr: &Expr| matches!(&expr.kind, ExprKind::Argument(arg) | ExprKind::Name(arg) if arg == name);
+                let (op, constant) = match () {
+                    _ if binder(lhs) => (op.name.as_str(), rhs),
+                    _ if binder(rhs) => match op.name.as_str() {
+                        "<" => (">", lhs),
+                        "<=" => (">=", lhs),
+                        ">" => ("<", lhs),
+                        ">=" => ("<=", lhs),
+                        op => (op, lhs),
+                    },
+                    _ => return None,
+                };
+                let Pat::Set(constant, scalar) = self.value(constant, 0) else { return None };
+                let value = constant.as_single()?;
+                let set = match op {
+                    "==" => IntSet::single(value),
+                    "!=" => IntSet::single(value).complement(scalar),
+                    "<" => IntSet::interval(i128::MIN, value.saturating_sub(1)),
+                    "<=" => IntSet::interval(i128::MIN, value),
+                    ">" => IntSet::interval(value.saturating_add(1), i128::MAX),
+                    ">=" => IntSet::interval(value, i128::MAX),
+                    _ => return None,
+                };
+                Some((set.intersection(&IntSet::full(scalar)), scalar))
+            }
+            _ => None,
+        }
+    }
+
+    /// Value of `expr` if it's a constant, literals and constructors of constants, `Pat::Any` otherwise
+    fn value(&self, expr: &Expr, depth: usize) -> Pat {
+        match &expr.kind {
+            ExprKind::Literal(value) => literal(value, false).unwrap_or(Pat::Any),
+            ExprKind::Unary { op, operand } if op.name == "-" => match &operand.kind {
+                ExprKind::Literal(value @ Literal::Number(_)) => literal(value, true).unwrap_or(Pat::Any),
+                _ => Pat::Any,
+            },
+            ExprKind::Name(name) => match self.resolution.def_at(expr.span) {
+                Some(def) if def.kind == DefKind::Constructor => match self.variants.arity(name) {
+                    Some(0) => Pat::Constructor(name.clone(), vec![]),
+                    _ => Pat::Any,
+                },
+                Some(def) => self.definition(def, depth).map_or(Pat::Any, |value| self.value(value, depth + 1)),
+                None => Pat::Any,
+            },
+            ExprKind::Apply { func, args } => match &func.kind {
+                ExprKind::Name(name) if self.resolution.def_at(func.span).is_some_and(|def| def.kind == DefKind::Constructor)
+                    && self.variants.arity(name) == Some(args.len()) =>
+                {
+                    Pat::Constructor(name.clone(), args.iter().map(|arg| self.value(arg, depth)).collect())
+                }
+                _ => Pat::Any,
+            },
+            ExprKind::Unit => Pat::Tuple(vec![]),
+            ExprKind::Tuple(fields) if fields.iter().all(|field| field.name.is_none()) => {
+                Pat::Tuple(fields.iter().map(|field| self.value(&field.value, depth)).collect())
+            }
+            ExprKind::Annotated { expr, .. } => self.value(expr, depth),
+            _ => Pat::Any,
+        }
+    }
+
+    /// Value of the `:=` definition `def`, unless it's in the prelude or too many definitions deep
+    fn definition(&self, def: &Def, depth: usize) -> Option<&Expr> {
+        match def.scope != DefScope::Prelude && depth < DEFINITION_LIMIT {
+            true => self.definitions.get(&def.span),
+            false => None,
+        }
+    }
+
+    /// Function defined by clauses that `func` is, the span of its name and the arguments a partial
+    /// application gives it first
+    fn callee(&self, func: &Expr, depth: usize) -> Option<(Span, Vec<Pat>)> {
+        match &func.kind {
+            ExprKind::Name(_) => self.callee_def(self.resolution.def_at(func.span)?, depth),
+            ExprKind::Apply { func, args } => {
+                let (function, mut fixed) = self.callee(func, depth)?;
+                fixed.extend(args.iter().map(|arg| self.value(arg, depth)));
+                Some((function, fixed))
+            }
+            ExprKind::Annotated { expr, .. } => self.callee(expr, depth),
+            _ => None,
+        }
+    }
+
+    fn callee_def(&self, def: &Def, depth: usize) -> Option<(Span, Vec<Pat>)> {
+        match def.kind {
+            DefKind::Function if def.scope != DefScope::Prelude => {
+                self.functions.contains_key(&def.span).then(|| (def.span, vec![]))
+            }
+            _ => self.callee(self.definition(def, depth)?, depth + 1),
+        }
+    }
+
+    /// Reports a call of the function named at `function` whose constant arguments no clause matches
+    fn call(&mut self, function: Span, mut values: Vec<Pat>, span: Span) {
+        let clauses = &self.functions[&function];
+        values.resize(clauses.arity, Pat::Any);
+        let matched = |row: &Vec<Pat>| row.iter().zip(&values).all(|(pat, value)| overlaps(pat, value));
+        if values.iter().all(|value| *value == Pat::Any) || clauses.rows.iter().any(matched) {
+            return;
+        }
+        let kind = ParseErrorKind::UncoveredCall { function: clauses.name.clone(), args: format_args(&values).into() };
+        self.report(Severity::Error, kind, span);
+    }
+}
+
+impl Visit for Checker<'_> {
+    fn visit_let(&mut self, let_: &Let) {
+        let candidate = matches!(
+            let_.value.kind,
+            ExprKind::Literal(_) | ExprKind::Unary { .. } | ExprKind::Name(_) | ExprKind::Apply { .. } | ExprKind::Unit
+                | ExprKind::Tuple(_) | ExprKind::Annotated { .. }
+        );
+        if self.pass == Pass::Definitions && candidate && !let_.mutable {
+            self.definitions.insert(let_.name.span, let_.value.clone());
+        }
+        walk_let(self, let_);
+    }
+
+    fn visit_function(&mut self, function: &FunctionDef) {
+        if self.pass == Pass::Clauses && !function.clauses.is_empty() {
+            self.clauses(function);
+        }
+        walk_function(self, function);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if self.pass == Pass::Calls {
+            match &expr.kind {
+                ExprKind::Apply { .. } => {
+                    if let Some((function, values)) = self.callee(expr, 0) {
+                        self.call(function, values, expr.span);
+                    }
+                }
+                // the receiver of a dot call is the first argument
+                ExprKind::Method { receiver: Some(receiver), method, args } => {
+                    if let ExprKind::Name(name) = &method.kind
+                        && let Some(&def) = self.globals.get(name)
+                        && let Some((function, mut values)) = self.callee_def(def, 0)
+                    {
+                        values.push(self.value(receiver, 0));
+                        values.extend(args.iter().map(|arg| self.value(arg, 0)));
+                        self.call(function, values, expr.span);
+                    }
+                }
+                _ => {}
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Values of integer or character literal `value`, negated if `negative`
+fn scalar(value: &Literal, negative: bool) -> Option<(i128, Scalar)> {
+    match value {
+        Literal::Number(number) => {
+            let value = i128::try_from(number.int_value()?).ok()?;
+            Some((if negative { -value } else { value }, Scalar::Int))
+        }
+        Literal::Char(char) => Some((*char as i128, Scalar::Char)),
+        Literal::String(_) => None,
+    }
+}
+
+/// Value of a literal, `None` for integers too big to compare
+fn literal(value: &Literal, negative: bool) -> Option<Pat> {
+    match value {
+        Literal::Number(number) if number.is_float() => Some(Pat::Lit(value.clone(), negative)),
+        Literal::String(_) => Some(Pat::Lit(value.clone(), false)),
+        _ => scalar(value, negative).map(|(value, scalar)| Pat::Set(IntSet::single(value), scalar)),
+    }
+}
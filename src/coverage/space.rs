@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use crate::ImStr;
+use crate::lexer::StringValue;
+use crate::parser::{Literal, StringLiteral};
+
+/// Kind of the values of an [`IntSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scalar {
+    Int,
+    Char,
+}
+
+impl Scalar {
+    /// Smallest and largest value, integers are unbounded and the extremes of `i128` stand for infinity
+    fn bounds(self) -> (i128, i128) {
+        match self {
+            Scalar::Int => (i128::MIN, i128::MAX),
+            Scalar::Char => (0, char::MAX as i128),
+        }
+    }
+}
+
+/// Integers or characters as sorted, disjoint and non-adjacent inclusive intervals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntSet(Vec<(i128, i128)>);
+
+impl IntSet {
+    pub fn interval(start: i128, end: i128) -> Self {
+        IntSet(if start <= end { vec![(start, end)] } else { vec![] })
+    }
+
+    pub fn single(value: i128) -> Self {
+        Self::interval(value, value)
+    }
+
+    pub fn full(scalar: Scalar) -> Self {
+        let (min, max) = scalar.bounds();
+        Self::interval(min, max)
+    }
+
+    pub fn contains(&self, value: i128) -> bool {
+        self.0.iter().any(|&(start, end)| start <= value && value <= end)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut intervals: Vec<_> = self.0.iter().chain(&other.0).copied().collect();
+        intervals.sort();
+        let mut merged: Vec<(i128, i128)> = vec![];
+        for (start, end) in intervals {
+            match merged.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        IntSet(merged)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = vec![];
+        for &(start, end) in &self.0 {
+            for &(other_start, other_end) in &other.0 {
+                if start.max(other_start) <= end.min(other_end) {
+                    intervals.push((start.max(other_start), end.min(other_end)));
+                }
+            }
+        }
+        intervals.sort();
+        IntSet(intervals)
+    }
+
+    /// Values of the kind of `scalar` not in the set
+    pub fn complement(&self, scalar: Scalar) -> Self {
+        let (min, max) = scalar.bounds();
+        let mut intervals = vec![];
+        let mut next = Some(min);
+        for &(start, end) in &self.0 {
+            if let Some(next) = next && next < start {
+                intervals.push((next, start - 1));
+            }
+            next = end.checked_add(1);
+        }
+        if let Some(next) = next && next <= max {
+            intervals.push((next, max));
+        }
+        IntSet(intervals)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The value of a set of one
+    pub fn as_single(&self) -> Option<i128> {
+        match self.0[..] {
+            [(start, end)] if start == end => Some(start),
+            _ => None,
+        }
+    }
+}
+
+/// Values a pattern matches, or an example of values no clause matches
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pat {
+    Any,
+    Set(IntSet, Scalar),
+    /// String or float literal and whether it's negated
+    Lit(Literal, bool),
+    Constructor(ImStr, Vec<Pat>),
+    /// Tuple, `()` included
+    Tuple(Vec<Pat>),
+}
+
+/// Whether some value matches both `a` and `b`. Kinds of values which can't meet are left to the type checker
+pub fn overlaps(a: &Pat, b: &Pat) -> bool {
+    match (a, b) {
+        (Pat::Any, _) | (_, Pat::Any) => true,
+        (Pat::Set(a, a_scalar), Pat::Set(b, b_scalar)) => a_scalar != b_scalar || !a.intersection(b).is_empty(),
+        (Pat::Lit(a, a_negative), Pat::Lit(b, b_negative)) => (a, a_negative) == (b, b_negative),
+        (Pat::Constructor(a, a_args), Pat::Constructor(b, b_args)) => {
+            a == b && a_args.iter().zip(b_args).all(|(a, b)| overlaps(a, b))
+        }
+        (Pat::Tuple(a), Pat::Tuple(b)) => a.len() != b.len() || a.iter().zip(b).all(|(a, b)| overlaps(a, b)),
+        _ => true,
+    }
+}
+
+/// Outermost shape of values the first patterns of the rows are split by
+#[derive(Debug, Clone)]
+enum Head {
+    /// Interval no pattern of the column partly covers
+    Interval(i128, i128, Scalar),
+    Lit(Literal, bool),
+    /// Constructor and its number of fields
+    Constructor(ImStr, usize),
+    Tuple(usize),
+}
+
+impl Head {
+    fn arity(&self) -> usize {
+        match self {
+            Head::Interval(..) | Head::Lit(..) => 0,
+            Head::Constructor(_, arity) | Head::Tuple(arity) => *arity,
+        }
+    }
+}
+
+/// Constructors of the sum types, by the name of each of them
+#[derive(Debug, Default)]
+pub struct Variants(HashMap<ImStr, Vec<(ImStr, usize)>>);
+
+impl Variants {
+    /// Adds a sum type by its constructors and their numbers of fields
+    pub fn insert(&mut self, constructors: Vec<(ImStr, usize)>) {
+        for (name, _) in &constructors {
+            self.0.insert(name.clone(), constructors.clone());
+        }
+    }
+
+    pub fn arity(&self, constructor: &str) -> Option<usize> {
+        let siblings = self.0.get(constructor)?;
+        siblings.iter().find(|(name, _)| name == constructor).map(|&(_, arity)| arity)
+    }
+
+    /// Example of values `values` match that no row does, `None` if the rows match all of them.
+    /// Rows and `values` have the same length, a row matches the values its patterns match in order
+    pub fn uncovered(&self, rows: &[Vec<Pat>], values: &[Pat]) -> Option<Vec<Pat>> {
+        let Some((first, rest)) = values.split_first() else { return rows.is_empty().then(Vec::new) };
+        let column: Vec<&Pat> = rows.iter().map(|row| &row[0]).collect();
+        match self.heads(first, &column) {
+            Some(heads) => heads.into_iter().find_map(|head| {
+                let rows: Vec<_> = rows.iter().filter_map(|row| specialize(row, &head)).collect();
+                let witness = self.uncovered(&rows, &specialize(values, &head)?)?;
+                Some(rebuild(head, witness))
+            }),
+            // the rows with a wildcard first are the only ones which can match values the others don't
+            None => {
+                let rows: Vec<_> = rows.iter().filter(|row| row[0] == Pat::Any).map(|row| row[1..].to_vec()).collect();
+                let mut witness = self.uncovered(&rows, rest)?;
+                witness.insert(0, self.missing(&column));
+                Some(witness)
+            }
+        }
+    }
+
+    /// Heads to split the values matching `first` by, `None` when `first` is a wildcard and the heads in
+    /// `column` don't cover all values
+    fn heads(&self, first: &Pat, column: &[&Pat]) -> Option<Vec<Head>> {
+        let heads = match first {
+            Pat::Set(set, scalar) => {
+                let sets = column.iter().copied().chain([first]);
+                intervals(sets, *scalar).into_iter().filter(|&(start, _)| set.contains(start))
+                    .map(|(start, end)| Head::Interval(start, end, *scalar))
+                    .collect()
+            }
+            Pat::Lit(literal, negative) => vec![Head::Lit(literal.clone(), *negative)],
+            Pat::Constructor(name, args) => vec![Head::Constructor(name.clone(), self.arity(name).unwrap_or(args.len()))],
+            Pat::Tuple(elements) => vec![Head::Tuple(elements.len())],
+            Pat::Any => match column.iter().find(|pat| ***pat != Pat::Any)? {
+                Pat::Set(_, scalar) => {
+                    intervals(column.iter().copied(), *scalar).into_iter()
+                        .map(|(start, end)| Head::Interval(start, end, *scalar))
+                        .collect()
+                }
+                Pat::Tuple(elements) => vec![Head::Tuple(elements.len())],
+                Pat::Constructor(name, _) => {
+                    let siblings = self.0.get(name)?;
+                    if !siblings.iter().all(|(sibling, _)| has_constructor(column, sibling)) {
+                        return None;
+                    }
+                    siblings.iter().map(|(name, arity)| Head::Constructor(name.clone(), *arity)).collect()
+                }
+                Pat::Lit(..) | Pat::Any => return None,
+            },
+        };
+        Some(heads)
+    }
+
+    /// Example of a value the heads of `column` don't cover
+    fn missing(&self, column: &[&Pat]) -> Pat {
+        let siblings = column.iter().find_map(|pat| match pat {
+            Pat::Constructor(name, _) => self.0.get(name),
+            _ => None,
+        });
+        match siblings.and_then(|siblings| siblings.iter().find(|(name, _)| !has_constructor(column, name))) {
+            Some((name, arity)) => Pat::Constructor(name.clone(), vec![Pat::Any; *arity]),
+            None => Pat::Any,
+        }
+    }
+}
+
+fn has_constructor(column: &[&Pat], constructor: &str) -> bool {
+    column.iter().any(|pat| matches!(pat, Pat::Constructor(name, _) if name == constructor))
+}
+
+/// Splits all values of `scalar` into intervals each set of `sets` covers whole or not at all
+fn intervals<'p>(sets: impl Iterator<Item = &'p Pat>, scalar: Scalar) -> Vec<(i128, i128)> {
+    let (min, max) = scalar.bounds();
+    let mut starts = vec![min];
+    for pat in sets {
+        if let Pat::Set(set, set_scalar) = pat && *set_scalar == scalar {
+            for &(start, end) in &set.0 {
+                starts.push(start.clamp(min, max));
+                starts.extend(end.checked_add(1).filter(|&next| next <= max));
+            }
+        }
+    }
+    starts.sort();
+    starts.dedup();
+    starts.iter().enumerate().map(|(i, &start)| (start, starts.get(i + 1).map_or(max, |next| next - 1))).collect()
+}
+
+/// Row without its first pattern, whose fields for `head` take its place, `None` if it doesn't match `head`
+fn specialize(row: &[Pat], head: &Head) -> Option<Vec<Pat>> {
+    let (first, rest) = row.split_first()?;
+    let mut fields = match (first, head) {
+        (Pat::Any, head) => vec![Pat::Any; head.arity()],
+        // intervals are either in a set or out of it
+        (Pat::Set(set, scalar), Head::Interval(start, _, head_scalar)) if scalar == head_scalar && set.contains(*start) => {
+            vec![]
+        }
+        (Pat::Lit(literal, negative), Head::Lit(head_literal, head_negative))
+            if (literal, negative) == (head_literal, head_negative) => vec![],
+        (Pat::Constructor(name, args), Head::Constructor(head_name, arity)) if name == head_name => {
+            args.iter().cloned().chain(std::iter::repeat(Pat::Any)).take(*arity).collect()
+        }
+        (Pat::Tuple(elements), Head::Tuple(arity)) if elements.len() == *arity => elements.clone(),
+        _ => return None,
+    };
+    fields.extend_from_slice(rest);
+    Some(fields)
+}
+
+/// Puts the fields of `head` at the start of `witness` back into a value of it
+fn rebuild(head: Head, mut witness: Vec<Pat>) -> Vec<Pat> {
+    let fields: Vec<Pat> = witness.drain(..head.arity()).collect();
+    let first = match head {
+        Head::Interval(start, end, scalar) => Pat::Set(IntSet::interval(start, end), scalar),
+        Head::Lit(literal, negative) => Pat::Lit(literal, negative),
+        Head::Constructor(name, _) => Pat::Constructor(name, fields),
+        Head::Tuple(_) => Pat::Tuple(fields),
+    };
+    witness.insert(0, first);
+    witness
+}
+
+/// Patterns as arguments separated by spaces
+pub fn format_args(pats: &[Pat]) -> String {
+    let arg = |pat: &Pat| {
+        let arg = pat.to_string();
+        let parens = match pat {
+            Pat::Constructor(_, args) => !args.is_empty(),
+            Pat::Set(..) | Pat::Lit(..) => arg.starts_with('-') || arg.contains(" | "),
+            Pat::Any | Pat::Tuple(_) => false,
+        };
+        if parens { format!("({arg})") } else { arg }
+    };
+    pats.iter().map(arg).collect::<Vec<_>>().join(" ")
+}
+
+fn format_value(f: &mut Formatter<'_>, value: i128, scalar: Scalar) -> std::fmt::Result {
+    match scalar {
+        Scalar::Int => write!(f, "{value}"),
+        Scalar::Char => match u32::try_from(value).ok().and_then(char::from_u32) {
+            Some(char) => write!(f, "'{}'", char.escape_default()),
+            None => write!(f, "'\\u{{{value:x}}}'"),
+        },
+    }
+}
+
+impl Display for Pat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pat::Any => write!(f, "_"),
+            Pat::Set(set, scalar) => {
+                let (min, max) = scalar.bounds();
+                for (i, &(start, end)) in set.0.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " | ")?;
+                    }
+                    match (start == min, end == max) {
+                        (true, true) => write!(f, "_")?,
+                        _ if start == end => format_value(f, start, *scalar)?,
+                        (true, false) => {
+                            write!(f, "..=")?;
+                            format_value(f, end, *scalar)?;
+                        }
+                        (false, true) => {
+                            format_value(f, start, *scalar)?;
+                            write!(f, "..")?;
+                        }
+                        (false, false) => {
+                            format_value(f, start, *scalar)?;
+                            write!(f, "..=")?;
+                            format_value(f, end, *scalar)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Pat::Lit(literal, negative) => {
+                if *negative {
+                    write!(f, "-")?;
+                }
+                match literal {
+                    Literal::Number(number) => write!(f, "{}", number.slice),
+                    Literal::String(StringLiteral { prefix, value, suffix }) => match value {
+                        StringValue::SingleLine(content) => write!(f, "{prefix}\"{content}\"{suffix}"),
+                        StringValue::Empty => write!(f, "{prefix}\"\"{suffix}"),
+                        StringValue::MultiLine { .. } => write!(f, "{prefix}\"...\"{suffix}"),
+                    },
+                    Literal::Char(char) => write!(f, "'{}'", char.escape_default()),
+                }
+            }
+            Pat::Constructor(name, args) if args.is_empty() => write!(f, "{name}"),
+            Pat::Constructor(name, args) => write!(f, "{name} {}", format_args(args)),
+            Pat::Tuple(elements) => {
+                let elements: Vec<_> = elements.iter().map(|element| element.to_string()).collect();
+                match elements.len() {
+                    1 => write!(f, "({},)", elements[0]),
+                    _ => write!(f, "({})", elements.join(", ")),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Lowering of generators to state machines.
+//!
+//! A generator body is cut at its yields into states, each holding the statements run between two yields.
+//! Resuming a generator runs the steps of its current state and follows the exits up to the next `Yield`
+//! or `Done`, so a backend only has to keep the current state, the scopes and the iterators of a generator
+//! in a frame to run it as a lazy iterator, without native coroutines
+
+use crate::parser::*;
+
+/// Index of a state in [`StateMachine::states`]
+pub type StateId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateMachine {
+    /// Bound in the outermost scope when the generator is made
+    pub params: Vec<Pattern>,
+    /// The first resume starts at the first state
+    pub states: Vec<State>,
+    /// Number of iterator slots of the `for` loops the generator can stop in
+    pub iterators: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct State {
+    pub steps: Vec<Step>,
+    pub exit: Exit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Statement without yields, run like in an ordinary function
+    Stmt(Box<Stmt>),
+    /// Opens a scope for the bindings of a block
+    Enter,
+    /// Closes the innermost scope
+    Leave,
+    /// Stores an iterator over the value of `iter` to iterator slot `slot`
+    Iter { slot: usize, iter: Expr },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exit {
+    /// Hands `value` over to the consumer and stops,
+    /// the next resume continues at `resume` binding the value it is given like `bind`, whose value is the yield
+    Yield { value: Expr, bind: Option<Box<Let>>, resume: StateId },
+    Goto(StateId),
+    Branch { cond: Expr, then: StateId, otherwise: StateId },
+    /// Binds the next element of iterator `slot` to `pattern` in a new scope and goes to `body`,
+    /// goes to `done` once the iterator is exhausted
+    Next { slot: usize, pattern: Pattern, body: StateId, done: StateId },
+    /// Binds the pattern of the first matching arm in a new scope and goes to its state
+    Match { scrutinee: Expr, arms: Vec<MatchArm> },
+    /// The generator is exhausted
+    Done,
+}
+
+/// `pattern [if guard]` going to `target`, the scope of the pattern is left again if the guard fails
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub target: StateId,
+}
+
+impl StateMachine {
+    /// Lowers the generator with `params` and `body`.
+    /// Yields the parser reports as misplaced are left in the statements holding them
+    pub fn new(params: &[Pattern], body: &Expr) -> Self {
+        let mut lowering = Lowering { states: vec![], iterators: 0 };
+        let start = lowering.state();
+        lowering.statement(start, body);
+        StateMachine { params: params.to_vec(), states: lowering.states, iterators: lowering.iterators }
+    }
+
+    /// Machines of a generator function, one for its body or one per clause:
+    /// the clause is picked when the function is called, before the generator starts
+    pub fn of_function(function: &FunctionDef) -> Vec<Self> {
+        match &function.body {
+            Some(body) => vec![Self::new(&function.params, body)],
+            None => function.clauses.iter().map(|clause| Self::new(&clause.params, &clause.body)).collect(),
+        }
+    }
+}
+
+struct Lowering {
+    states: Vec<State>,
+    iterators: usize,
+}
+
+impl Lowering {
+    /// Adds a state which ends the generator until its exit is set
+    fn state(&mut self) -> StateId {
+        self.states.push(State { steps: vec![], exit: Exit::Done });
+        self.states.len() - 1
+    }
+
+    fn step(&mut self, state: StateId, step: Step) {
+        self.states[state].steps.push(step);
+    }
+
+    fn exit(&mut self, state: StateId, exit: Exit) {
+        self.states[state].exit = exit;
+    }
+
+    /// Lowers `expr` whose value is dropped, starting in `state`. Returns the state control continues in
+    fn statement(&mut self, state: StateId, expr: &Expr) -> StateId {
+        if yields(&[], expr).stops.is_empty() {
+            self.step(state, Step::Stmt(Box::new(Stmt::Expr(expr.clone()))));
+            return state;
+        }
+        match &expr.kind {
+            ExprKind::Yield(value) => {
+                let resume = self.state();
+                self.exit(state, Exit::Yield { value: (**value).clone(), bind: None, resume });
+                resume
+            }
+            ExprKind::Block(stmts) => {
+                self.step(state, Step::Enter);
+                let end = stmts.iter().fold(state, |state, stmt| self.stmt(state, stmt));
+                self.step(end, Step::Leave);
+                end
+            }
+            ExprKind::For { iter, pattern, body } => {
+                let slot = self.iterators;
+                self.iterators += 1;
+                self.step(state, Step::Iter { slot, iter: (**iter).clone() });
+                let (head, body_state, done) = (self.state(), self.state(), self.state());
+                self.exit(state, Exit::Goto(head));
+                self.exit(head, Exit::Next { slot, pattern: (**pattern).clone(), body: body_state, done });
+                let end = self.statement(body_state, body);
+                self.step(end, Step::Leave);
+                self.exit(end, Exit::Goto(head));
+                done
+            }
+            ExprKind::While { cond, body, otherwise } => {
+                let (head, then, done) = (self.state(), self.state(), self.state());
+                self.exit(state, Exit::Goto(head));
+                self.exit(head, Exit::Branch { cond: (**cond).clone(), then, otherwise: done });
+                let end = match body {
+                    Some(body) => self.statement(then, body),
+                    None => then,
+                };
+                self.exit(end, Exit::Goto(head));
+                match otherwise {
+                    Some(otherwise) => self.statement(done, otherwise),
+                    None => done,
+                }
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                let mut ends = vec![];
+                let arms = arms.iter()
+                    .map(|arm| {
+                        let target = self.state();
+                        let end = self.statement(target, &arm.body);
+                        self.step(end, Step::Leave);
+                        ends.push(end);
+                        MatchArm { pattern: arm.pattern.clone(), guard: arm.guard.clone(), target }
+                    })
+                    .collect();
+                self.exit(state, Exit::Match { scrutinee: (**scrutinee).clone(), arms });
+                let done = self.state();
+                ends.into_iter().for_each(|end| self.exit(end, Exit::Goto(done)));
+                done
+            }
+            ExprKind::Annotated { expr, .. } => self.statement(state, expr),
+            _ => {
+                self.step(state, Step::Stmt(Box::new(Stmt::Expr(expr.clone()))));
+                state
+            }
+        }
+    }
+
+    fn stmt(&mut self, state: StateId, stmt: &Stmt) -> StateId {
+        match stmt {
+            Stmt::Let(bind @ Let { value: Expr { kind: ExprKind::Yield(value), .. }, .. }) => {
+                let resume = self.state();
+                self.exit(state, Exit::Yield { value: (**value).clone(), bind: Some(Box::new(bind.clone())), resume });
+                resume
+            }
+            Stmt::Expr(expr) => self.statement(state, expr),
+            stmt => {
+                self.step(state, Step::Stmt(Box::new(stmt.clone())));
+                state
+            }
+        }
+    }
+}
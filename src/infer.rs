@@ -0,0 +1,998 @@
+//! Type inference.
+//!
+//! Types are inferred Hindley–Milner style, annotations are only needed for casts. Module definitions
+//! are inferred a group of mutually recursive ones at a time, each after the ones it uses, and are
+//! generic over the type variables left in them. So are the `:=` definitions of blocks, `mut` ones aside.
+//! Integer and float literals without suffix take any integer or float type, `int` or `f64` if nothing
+//! else decides it, and must fit in it, in each type the uses of a generic definition give it.
+//!
+//! Names are looked up through their resolution. Dot calls are calls of the module or prelude function
+//! or of the trait method named by the method which takes the receiver first, or fields of a receiver of
+//! a `class` type. `.map` on a tuple calls its function on each element, which may each have their own
+//! type, the function is generic and used once for each. `value |> .section` is `value.section`.
+//!
+//! Uses of trait methods must meet the bound of their trait, the types they're used at picking the impl
+//! called. A definition using them on its own type variables is bounded in turn, each use of it picking
+//! impls for its bounds, so it's instantiated for them. Methods of `dyn` values are called through the
+//! vtable made when the value was cast
+
+mod traits;
+mod types;
+
+pub use self::traits::{Dispatch, Impl, VTable};
+pub use self::types::{Bound, LiteralCheck, Scheme, Type, TypeVar, VarKind, FLOAT_TYPES, INTEGER_TYPES};
+
+use std::collections::{HashMap, HashSet};
+use crate::ImStr;
+use crate::numeric::{arithmetic, arithmetic_operator, is_bounded_operator, ArithmeticError, ArithmeticOp, Overflow};
+use crate::numeric::{Number, NumberType};
+use crate::parser::*;
+use crate::resolve::{resolve, DefId, DefKind, DefScope, Resolution};
+use crate::visit::{walk_expr, walk_type, Visit};
+use self::traits::{Obligation, Trait};
+use self::types::Unifier;
+
+/// Depth of aliases expanded in a type
+const ALIAS_LIMIT: usize = 32;
+
+#[derive(Debug, Default)]
+pub struct Typing {
+    /// Types of the definitions of the module by [`DefId`], generic ones generalized
+    pub schemes: HashMap<DefId, Scheme>,
+    /// Type of each expression and pattern by its span
+    pub types: HashMap<Span, Type>,
+    /// Impls of the prelude and of the module, which [`Dispatch::Static`] refers to by index
+    pub impls: Vec<Impl>,
+    /// How each bound of the definitions used at a span is met, a trait method's one being its trait
+    pub dispatch: HashMap<Span, Vec<Dispatch>>,
+    /// Vtables of the values cast to a `dyn` trait
+    pub vtables: Vec<VTable>,
+    /// Errors in source order
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Infers the types of module `ast` whose names are resolved by `resolution`. The prelude is inferred on
+/// its own first
+pub fn infer(ast: &Ast, prelude: &Ast, resolution: &Resolution) -> Typing {
+    let mut env = Prelude::default();
+    if !prelude.stmts.is_empty() {
+        let empty = Ast::default();
+        let prelude_resolution = resolve(prelude, &empty);
+        let inferer = Inferer::run(prelude, &empty, &prelude_resolution, Prelude::default());
+        for (id, def) in prelude_resolution.defs.iter().enumerate() {
+            if def.scope == DefScope::Module && let Some(scheme) = inferer.schemes.get(&id) {
+                env.schemes.insert(def.name.clone(), (scheme.clone(), def.kind));
+            }
+        }
+        env.traits = inferer.traits;
+        env.impls = inferer.impls;
+    }
+    let inferer = Inferer::run(ast, prelude, resolution, env);
+
+    let Inferer { unifier, schemes, types, impls, dispatch, vtables, mut diagnostics, .. } = inferer;
+    let schemes = schemes.into_iter()
+        .map(|(id, scheme)| {
+            let bounds = scheme.bounds.iter().map(|bound| unifier.describe_bound(bound)).collect();
+            (id, Scheme { ty: unifier.describe(&scheme.ty), bounds, ..scheme })
+        })
+        .collect();
+    let types = types.into_iter().map(|(span, ty)| (span, unifier.describe(&ty))).collect();
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span.row, diagnostic.span.num));
+    Typing { schemes, types, impls, dispatch, vtables, diagnostics }
+}
+
+/// Definitions of the prelude, which every module sees
+#[derive(Default)]
+struct Prelude {
+    /// Schemes and kinds of the definitions by name
+    schemes: HashMap<ImStr, (Scheme, DefKind)>,
+    traits: Vec<Trait>,
+    impls: Vec<Impl>,
+}
+
+struct Inferer<'r> {
+    unifier: Unifier,
+    resolution: &'r Resolution,
+    /// Schemes of the prelude definitions by name
+    prelude: HashMap<ImStr, (Scheme, DefKind)>,
+    /// Definitions by the span of their name, prelude ones left out since their spans are in another file
+    defs: HashMap<Span, DefId>,
+    /// Module definitions by name, for dot calls
+    globals: HashMap<ImStr, DefId>,
+    schemes: HashMap<DefId, Scheme>,
+    /// Fields of the `class` types by type and field name, generic over the type parameters
+    records: HashMap<ImStr, HashMap<ImStr, Scheme>>,
+    /// Parameters and aliased type of the aliases
+    aliases: HashMap<ImStr, (Vec<ImStr>, TypeExpr)>,
+    alias_depth: usize,
+    /// Traits of the prelude and then of the module
+    traits: Vec<Trait>,
+    impls: Vec<Impl>,
+    /// Bounds of the uses of definitions not met yet
+    obligations: Vec<Obligation>,
+    dispatch: HashMap<Span, Vec<Dispatch>>,
+    vtables: Vec<VTable>,
+    types: HashMap<Span, Type>,
+    /// Number literals, those of generic definitions again at each use
+    literals: Vec<LiteralCheck>,
+    /// Values of the number literals and of the arithmetic on them folded so far
+    constants: HashMap<Span, Number>,
+    /// Arithmetic on number literals, innermost first, folded once the types are known
+    constant_ops: Vec<ConstantOp>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// `lhs op rhs` at `span` whose operands are number literals or arithmetic on them
+struct ConstantOp {
+    op: ArithmeticOp,
+    overflow: Overflow,
+    operands: [Span; 2],
+    span: Span,
+    ty: Type,
+}
+
+impl<'r> Inferer<'r> {
+    /// Infers module `ast` seeing the definitions of `prelude`, whose types are declared in `prelude_ast`
+    fn run(ast: &Ast, prelude_ast: &Ast, resolution: &'r Resolution, prelude: Prelude) -> Self {
+        let defs = resolution.defs.iter().enumerate()
+            .filter(|(_, def)| def.scope != DefScope::Prelude)
+            .map(|(id, def)| (def.span, id))
+            .collect();
+        let globals = resolution.defs.iter().enumerate()
+            .filter(|(_, def)| def.scope == DefScope::Module && !matches!(def.kind, DefKind::Module | DefKind::Import))
+            .map(|(id, def)| (def.name.clone(), id))
+            .collect();
+        let mut inferer = Inferer {
+            unifier: Unifier::default(),
+            resolution,
+            prelude: prelude.schemes,
+            defs,
+            globals,
+            schemes: HashMap::new(),
+            records: HashMap::new(),
+            aliases: HashMap::new(),
+            alias_depth: 0,
+            traits: prelude.traits,
+            impls: prelude.impls,
+            obligations: vec![],
+            dispatch: HashMap::new(),
+            vtables: vec![],
+            types: HashMap::new(),
+            literals: vec![],
+            constants: HashMap::new(),
+            constant_ops: vec![],
+            diagnostics: vec![],
+        };
+        inferer.module(prelude_ast, ast);
+        // literals left without a type are `int` or `f64`, which may pick the impls of their bounds
+        inferer.solve();
+        inferer.unifier.default_literals();
+        inferer.solve();
+        inferer.check_literals();
+        inferer.fold_constants();
+        inferer
+    }
+}
+
+impl Inferer<'_> {
+    fn fresh(&mut self) -> Type {
+        self.unifier.fresh(VarKind::Any)
+    }
+
+    /// Unifies the type `found` at `span` with the one `expected` there, because of the code at `origin`
+    fn unify(&mut self, expected: &Type, origin: Option<Span>, found: &Type, span: Span) {
+        self.unifier.infinite = None;
+        if !self.unifier.unify(expected, found) {
+            let kind = match self.unifier.infinite.take() {
+                Some((var, ty)) => {
+                    let var = self.unifier.describe(&var).to_string().into();
+                    let ty = self.unifier.describe(&ty).to_string().into();
+                    ParseErrorKind::InfiniteType { var, ty }
+                }
+                None => {
+                    let expected = self.unifier.describe(expected).to_string().into();
+                    let found = self.unifier.describe(found).to_string().into();
+                    ParseErrorKind::TypeMismatch { expected, found, origin }
+                }
+            };
+            self.diagnostics.push(Diagnostic { severity: Severity::Error, kind, span });
+        }
+    }
+
+    fn condition(&mut self, expr: &Expr) {
+        let ty = self.expr(expr);
+        self.unify(&Type::named("bool"), None, &ty, expr.span);
+    }
+
+    /// Infers generic definitions `infer` gives the types of, a level deeper than the current one
+    fn generic<T>(&mut self, infer: impl FnOnce(&mut Self) -> T) -> T {
+        self.unifier.level += 1;
+        let result = infer(self);
+        self.unifier.level -= 1;
+        result
+    }
+
+    fn define(&mut self, name: &Ident, scheme: Scheme) {
+        if let Some(&id) = self.defs.get(&name.span) {
+            self.schemes.insert(id, scheme);
+        }
+    }
+
+    fn module(&mut self, prelude: &Ast, ast: &Ast) {
+        for stmt in &prelude.stmts {
+            if let Stmt::Type(decl) = stmt {
+                self.type_decl(decl, false);
+            }
+        }
+        for stmt in &ast.stmts {
+            if let Stmt::Type(decl) = stmt {
+                self.type_decl(decl, true);
+            }
+        }
+        for stmt in &ast.stmts {
+            if let Stmt::Type(TypeDecl { name, body: TypeBody::Alias(_), .. }) = stmt && self.cyclic_alias(&name.name) {
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    kind: ParseErrorKind::CyclicAlias { name: name.name.clone() },
+                    span: name.span,
+                });
+            }
+        }
+        for stmt in &ast.stmts {
+            if let Stmt::Trait(decl) = stmt {
+                self.trait_decl(decl);
+            }
+        }
+        for stmt in &ast.stmts {
+            if let Stmt::Impl(decl) = stmt {
+                self.impl_header(decl);
+            }
+        }
+        self.definitions(&ast.stmts);
+        for stmt in &ast.stmts {
+            match stmt {
+                Stmt::Impl(decl) => self.impl_decl(decl),
+                Stmt::Expr(expr) => {
+                    self.expr(expr);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Declares the constructors of a sum type if `constructors`, the fields of a `class` or an alias
+    fn type_decl(&mut self, decl: &TypeDecl, constructors: bool) {
+        let params: Vec<ImStr> = decl.params.iter().map(|param| param.name.clone()).collect();
+        let mut vars: HashMap<ImStr, Type> = params.iter().enumerate().map(|(i, param)| (param.clone(), Type::Gen(i))).collect();
+        let generic = |ty| Scheme { vars: vec![VarKind::Any; params.len()], ty, bounds: vec![], literals: vec![] };
+        match &decl.body {
+            TypeBody::Variants(variants) if constructors => {
+                let result = Type::Con(decl.name.name.clone(), (0..params.len()).map(Type::Gen).collect());
+                for variant in variants {
+                    let fields: Vec<_> = variant.fields.iter().map(|field| self.type_expr(field, &mut vars)).collect();
+                    let scheme = generic(Type::function(fields.into_iter(), result.clone()));
+                    self.define(&variant.name, scheme);
+                }
+            }
+            TypeBody::Variants(_) => {}
+            TypeBody::Record(fields) => {
+                let fields = fields.iter().map(|field| (field.name.name.clone(), generic(self.type_expr(&field.ty, &mut vars)))).collect();
+                self.records.insert(decl.name.name.clone(), fields);
+            }
+            TypeBody::Alias(ty) => {
+                self.aliases.insert(decl.name.name.clone(), (params.clone(), ty.clone()));
+            }
+        }
+    }
+
+    /// Type written as `ty`, whose type parameters are the types in `vars` or new variables added to it
+    fn type_expr(&mut self, ty: &TypeExpr, vars: &mut HashMap<ImStr, Type>) -> Type {
+        match &ty.kind {
+            TypeExprKind::Name(name) => self.named_type(name, vec![]),
+            TypeExprKind::Var(name) => match vars.get(name) {
+                Some(var) => var.clone(),
+                None => {
+                    let var = self.fresh();
+                    vars.insert(name.clone(), var.clone());
+                    var
+                }
+            },
+            TypeExprKind::Apply { name, args } => {
+                let args = args.iter().map(|arg| self.type_expr(arg, vars)).collect();
+                self.named_type(&name.name, args)
+            }
+            TypeExprKind::Unit => Type::unit(),
+            TypeExprKind::Tuple(fields) if fields.iter().all(|field| field.name.is_none()) => {
+                Type::Tuple(fields.iter().map(|field| self.type_expr(&field.ty, vars)).collect())
+            }
+            TypeExprKind::Function { param, result } => {
+                Type::Fn(Box::new(self.type_expr(param, vars)), Box::new(self.type_expr(result, vars)))
+            }
+            TypeExprKind::Dyn(inner) => Type::Con("dyn".into(), vec![self.type_expr(inner, vars)]),
+            // tuples with named fields are matched by their fields, which isn't tracked
+            TypeExprKind::Tuple(_) | TypeExprKind::Hole | TypeExprKind::Error => self.fresh(),
+        }
+    }
+
+    /// Whether expanding alias `name` reaches it again, the expansion then stops at [`ALIAS_LIMIT`]
+    fn cyclic_alias(&self, name: &ImStr) -> bool {
+        let mut seen = HashSet::new();
+        let mut aliases = vec![name.clone()];
+        while let Some(alias) = aliases.pop() {
+            let Some((_, ty)) = self.aliases.get(&alias) else { continue };
+            let mut names = TypeNames::default();
+            names.visit_type(ty);
+            if names.0.contains(name) {
+                return true;
+            }
+            aliases.extend(names.0.into_iter().filter(|name| seen.insert(name.clone())));
+        }
+        false
+    }
+
+    /// Type `name` applied to `args`, with aliases expanded
+    fn named_type(&mut self, name: &ImStr, args: Vec<Type>) -> Type {
+        let Some((params, aliased)) = self.aliases.get(name).filter(|(params, _)| params.len() == args.len()).cloned() else {
+            return Type::Con(name.clone(), args);
+        };
+        if self.alias_depth == ALIAS_LIMIT {
+            return self.fresh();
+        }
+        self.alias_depth += 1;
+        let ty = self.type_expr(&aliased, &mut params.into_iter().zip(args).collect());
+        self.alias_depth -= 1;
+        ty
+    }
+
+    /// Types of the parameters of a function, a binder named like one of `vars` has its type
+    fn params(&mut self, params: &[Pattern], vars: &HashMap<ImStr, Type>) -> Vec<Type> {
+        params.iter().map(|param| match &param.kind {
+            PatternKind::Bind { name, pattern: None, guard: None } if vars.contains_key(&name.name) => {
+                let ty = vars[&name.name].clone();
+                self.define(name, Scheme::mono(ty.clone()));
+                self.types.insert(param.span, ty.clone());
+                ty
+            }
+            _ => self.pattern(param),
+        }).collect()
+    }
+
+    /// Infers the module level `:=` definitions and functions, each group of mutually recursive ones
+    /// after the groups it uses, and generalizes them
+    fn definitions(&mut self, stmts: &[Stmt]) {
+        let items: Vec<&Stmt> = stmts.iter().filter(|stmt| matches!(stmt, Stmt::Let(_) | Stmt::Function(_))).collect();
+        let name = |item: &Stmt| match item {
+            Stmt::Let(let_) => let_.name.span,
+            Stmt::Function(function) => function.name.span,
+            _ => unreachable!(),
+        };
+        let nodes: HashMap<DefId, usize> = items.iter().enumerate()
+            .filter_map(|(node, item)| Some((*self.defs.get(&name(item))?, node)))
+            .collect();
+        let edges: Vec<Vec<usize>> = items.iter().map(|item| {
+            let mut uses = Uses::default();
+            uses.visit_stmt(item);
+            let resolved = uses.names.iter().filter_map(|span| self.resolution.resolved.get(span));
+            let methods = uses.methods.iter().filter_map(|name| self.globals.get(name));
+            resolved.chain(methods).filter_map(|id| nodes.get(id).copied()).collect()
+        }).collect();
+
+        for component in components(&edges) {
+            let types: Vec<Type> = self.generic(|inferer| {
+                let placeholders: Vec<Type> = component.iter().map(|&node| {
+                    let placeholder = inferer.fresh();
+                    if let Some(&id) = inferer.defs.get(&name(items[node])) {
+                        inferer.schemes.insert(id, Scheme::mono(placeholder.clone()));
+                    }
+                    placeholder
+                }).collect();
+                for (&node, placeholder) in component.iter().zip(&placeholders) {
+                    let (ty, span) = match items[node] {
+                        Stmt::Let(let_) => (inferer.let_value(let_), let_.value.span),
+                        Stmt::Function(function) => (inferer.function(function, &mut HashMap::new()), function.name.span),
+                        _ => unreachable!(),
+                    };
+                    inferer.unify(&ty, None, placeholder, span);
+                }
+                placeholders
+            });
+            let mutable = |node: usize| matches!(items[node], Stmt::Let(Let { mutable: true, .. }));
+            for (&node, ty) in component.iter().zip(&types) {
+                if mutable(node) {
+                    self.unifier.fix(ty);
+                }
+            }
+            let schemes = self.generalize(&[], &types, vec![]);
+            for ((&node, ty), scheme) in component.iter().zip(types).zip(schemes) {
+                let scheme = if mutable(node) { Scheme::mono(ty) } else { scheme };
+                if let Some(&id) = self.defs.get(&name(items[node])) {
+                    self.schemes.insert(id, scheme);
+                }
+            }
+        }
+    }
+
+    /// Type of the value of a `:=` definition, checked against its annotation
+    fn let_value(&mut self, let_: &Let) -> Type {
+        let value = self.expr(&let_.value);
+        if let Some(ty) = &let_.ty {
+            let annotated = self.type_expr(ty, &mut HashMap::new());
+            self.unify(&annotated, Some(ty.span), &value, let_.value.span);
+            return annotated;
+        }
+        value
+    }
+
+    /// Type of a function with the parameters its signature and clauses match and the result of its body
+    /// and clauses. Generators give the iterator made by a call, which isn't inferred
+    fn function(&mut self, function: &FunctionDef, vars: &mut HashMap<ImStr, Type>) -> Type {
+        let params: Vec<(Type, Span)> = self.params(&function.params, vars).into_iter()
+            .zip(function.params.iter().map(|param| param.span))
+            .collect();
+        let (result, mut origin) = match &function.ty {
+            Some(ty) => (self.type_expr(ty, vars), Some(ty.span)),
+            None => (self.fresh(), None),
+        };
+        let mut body = |inferer: &mut Self, body: &Expr| {
+            let ty = inferer.expr(body);
+            if !function.generator {
+                inferer.unify(&result, origin, &ty, body.span);
+                origin = origin.or(Some(body.span));
+            }
+        };
+        if let Some(expr) = &function.body {
+            body(self, expr);
+        }
+        for clause in &function.clauses {
+            for ((param, span), pattern) in params.iter().zip(&clause.params) {
+                let ty = self.pattern(pattern);
+                self.unify(param, Some(*span), &ty, pattern.span);
+            }
+            body(self, &clause.body);
+        }
+        Type::function(params.into_iter().map(|(param, _)| param), result)
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Type {
+        let ty = match &expr.kind {
+            ExprKind::Name(_) | ExprKind::Argument(_) => self.name(expr.span),
+            ExprKind::Hole | ExprKind::Error | ExprKind::Missing => self.fresh(),
+            ExprKind::Literal(literal) => self.literal(literal, false, expr.span),
+            ExprKind::Unit => Type::unit(),
+            ExprKind::Tuple(fields) => {
+                let elements: Vec<_> = fields.iter().map(|field| self.expr(&field.value)).collect();
+                // tuples with named fields are matched by their fields, which isn't tracked
+                match fields.iter().all(|field| field.name.is_none()) {
+                    true => Type::Tuple(elements),
+                    false => self.fresh(),
+                }
+            }
+            ExprKind::List(elements) => {
+                let element = self.fresh();
+                let origin = elements.first().map(|first| first.span);
+                for item in elements {
+                    let ty = self.expr(item);
+                    self.unify(&element, origin, &ty, item.span);
+                }
+                Type::Con("Array".into(), vec![element])
+            }
+            ExprKind::Apply { func, args } => {
+                let mut ty = self.expr(func);
+                for arg in args {
+                    let arg_ty = self.expr(arg);
+                    ty = self.apply(ty, func.span, arg_ty, arg.span);
+                }
+                ty
+            }
+            ExprKind::Method { receiver, method, args } => self.method(receiver.as_deref(), method, args),
+            // `-1` is a literal of its own, which `s8` fits unlike `1`
+            ExprKind::Unary { op, operand } if op.name == "-" && matches!(operand.kind, ExprKind::Literal(Literal::Number(_))) => {
+                let ExprKind::Literal(literal) = &operand.kind else { unreachable!() };
+                let ty = self.literal(literal, true, expr.span);
+                self.types.insert(operand.span, ty.clone());
+                ty
+            }
+            ExprKind::Unary { op, operand } => {
+                let ty = self.expr(operand);
+                match op.name.as_str() {
+                    "not" | "!" => {
+                        self.unify(&Type::named("bool"), None, &ty, operand.span);
+                        Type::named("bool")
+                    }
+                    _ => ty,
+                }
+            }
+            ExprKind::Binary { op, lhs, rhs } => self.binary(op, lhs, rhs, expr.span),
+            ExprKind::Block(stmts) => self.block(stmts),
+            ExprKind::For { iter, pattern, body } => {
+                let iter_ty = self.expr(iter);
+                let element = match self.unifier.shallow(&iter_ty) {
+                    Type::Con(name, mut args) if matches!(name.as_str(), "Array" | "Range") && args.len() == 1 => args.remove(0),
+                    _ => self.fresh(),
+                };
+                let ty = self.pattern(pattern);
+                self.unify(&element, Some(iter.span), &ty, pattern.span);
+                self.expr(body);
+                Type::unit()
+            }
+            ExprKind::While { cond, body, otherwise } => {
+                self.condition(cond);
+                if let Some(body) = body {
+                    self.expr(body);
+                }
+                otherwise.as_ref().map_or_else(Type::unit, |otherwise| self.expr(otherwise))
+            }
+            ExprKind::Cast { expr: value, ty } => {
+                let value_ty = self.expr(value);
+                let ty = self.type_expr(ty, &mut HashMap::new());
+                self.coerce(&value_ty, &ty, expr.span);
+                ty
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                let scrutinee_ty = self.expr(scrutinee);
+                let mut result: Option<(Type, Span)> = None;
+                for arm in arms {
+                    let ty = self.pattern(&arm.pattern);
+                    self.unify(&scrutinee_ty, Some(scrutinee.span), &ty, arm.pattern.span);
+                    if let Some(guard) = &arm.guard {
+                        self.condition(guard);
+                    }
+                    let ty = self.expr(&arm.body);
+                    match &result {
+                        Some((result, origin)) => self.unify(&result.clone(), Some(*origin), &ty, arm.body.span),
+                        None => result = Some((ty, arm.body.span)),
+                    }
+                }
+                result.map_or_else(|| self.fresh(), |(result, _)| result)
+            }
+            ExprKind::Lambda { params, body, generator, .. } => {
+                let mut params: Vec<_> = params.iter().map(|param| self.pattern(param)).collect();
+                if params.is_empty() {
+                    params.push(Type::unit());
+                }
+                let body = self.expr(body);
+                let result = if *generator { self.fresh() } else { body };
+                Type::function(params.into_iter(), result)
+            }
+            ExprKind::Annotated { expr, .. } => self.expr(expr),
+            ExprKind::Yield(value) => {
+                self.expr(value);
+                self.fresh()
+            }
+            ExprKind::Assign { place, value, .. } => {
+                let ty = self.expr(place);
+                let value_ty = self.expr(value);
+                self.unify(&ty, Some(place.span), &value_ty, value.span);
+                ty
+            }
+            ExprKind::Range { start, end, .. } => {
+                let element = self.fresh();
+                let origin = start.as_ref().map(|start| start.span);
+                for bound in [start, end].into_iter().flatten() {
+                    let ty = self.expr(bound);
+                    self.unify(&element, origin, &ty, bound.span);
+                }
+                Type::Con("Range".into(), vec![element])
+            }
+        };
+        self.types.insert(expr.span, ty.clone());
+        ty
+    }
+
+    /// Type of the definition the name at `span` refers to
+    fn name(&mut self, span: Span) -> Type {
+        let Some(&id) = self.resolution.resolved.get(&span) else { return self.fresh() };
+        let def = &self.resolution.defs[id];
+        let method = (def.kind == DefKind::Method).then(|| def.name.clone());
+        let scheme = match def.scope {
+            DefScope::Prelude => self.prelude.get(&def.name).map(|(scheme, _)| scheme),
+            _ => self.schemes.get(&id),
+        };
+        match scheme.cloned() {
+            Some(scheme) => self.instantiate(&scheme, span, method.as_ref()),
+            None => self.fresh(),
+        }
+    }
+
+    /// Type of a literal written at `span`, negated if `negative`
+    fn literal(&mut self, literal: &Literal, negative: bool, span: Span) -> Type {
+        let number = match literal {
+            Literal::Number(number) => number,
+            Literal::String(_) => return Type::named("String"),
+            Literal::Char(_) => return Type::named("char"),
+        };
+        let ty = match number.suffix() {
+            Some(suffix) => match NumberType::from_name(suffix) {
+                Some(ty) if ty.is_float() || !number.is_float() => Type::named(suffix),
+                _ => {
+                    let kind = ParseErrorKind::InvalidNumberSuffix { suffix: suffix.into() };
+                    self.diagnostics.push(Diagnostic { severity: Severity::Error, kind, span });
+                    return self.fresh();
+                }
+            },
+            None if number.is_float() => self.unifier.fresh(VarKind::Float),
+            None => self.unifier.fresh(VarKind::Integer),
+        };
+        if let Some(value) = Number::of_literal(number, negative) {
+            self.constants.insert(span, value.clone());
+            let written = &number.slice[..number.suf_pos.unwrap_or(number.slice.len())];
+            let written = if negative { format!("-{written}") } else { written.to_string() };
+            self.literals.push(LiteralCheck { written: written.into(), value, ty: ty.clone(), span });
+        }
+        ty
+    }
+
+    /// Reports the literals which don't fit in their type, once for each type
+    fn check_literals(&mut self) {
+        let mut reported = vec![];
+        for LiteralCheck { written, value, ty, span } in std::mem::take(&mut self.literals) {
+            let Type::Con(name, _) = self.unifier.shallow(&ty) else { continue };
+            if NumberType::from_name(&name).is_some_and(|ty| !ty.fits(&value)) && !reported.contains(&(span, name.clone())) {
+                // already reported, the arithmetic on it isn't
+                self.constants.remove(&span);
+                reported.push((span, name.clone()));
+                let kind = ParseErrorKind::LiteralOutOfRange { literal: written, ty: name };
+                self.diagnostics.push(Diagnostic { severity: Severity::Error, kind, span });
+            }
+        }
+    }
+
+    /// Computes the arithmetic on literals, reporting the operations which overflow or divide by zero
+    fn fold_constants(&mut self) {
+        for ConstantOp { op, overflow, operands: [lhs, rhs], span, ty } in std::mem::take(&mut self.constant_ops) {
+            let (Some(a), Some(b)) = (self.constants.get(&lhs), self.constants.get(&rhs)) else { continue };
+            let Type::Con(name, _) = self.unifier.shallow(&ty) else { continue };
+            let Some(number_ty) = NumberType::from_name(&name) else { continue };
+            let kind = match arithmetic(op, overflow, number_ty, a, b) {
+                Ok(value) => {
+                    self.constants.insert(span, value);
+                    continue;
+                }
+                Err(ArithmeticError::Overflow { result, .. }) => {
+                    ParseErrorKind::ConstantOverflow { result: result.to_string().into(), ty: name }
+                }
+                Err(ArithmeticError::DivisionByZero) => ParseErrorKind::DivisionByZero,
+            };
+            self.diagnostics.push(Diagnostic { severity: Severity::Error, kind, span });
+        }
+    }
+
+    /// Type of the result of applying a function of type `func` written at `func_span` to `arg`
+    fn apply(&mut self, func: Type, func_span: Span, arg: Type, arg_span: Span) -> Type {
+        match self.unifier.shallow(&func) {
+            Type::Fn(param, result) => {
+                self.unify(&param, Some(func_span), &arg, arg_span);
+                *result
+            }
+            _ => {
+                let result = self.fresh();
+                self.unify(&Type::Fn(Box::new(arg), Box::new(result.clone())), Some(arg_span), &func, func_span);
+                result
+            }
+        }
+    }
+
+    /// Type of a dot call, a section without receiver is a function of it
+    fn method(&mut self, receiver: Option<&Expr>, method: &Expr, args: &[Expr]) -> Type {
+        let (receiver_ty, receiver_span) = match receiver {
+            Some(receiver) => (self.expr(receiver), receiver.span),
+            None => (self.fresh(), method.span),
+        };
+        let ty = self.dot(&receiver_ty, receiver_span, method, args);
+        match receiver {
+            Some(_) => ty,
+            None => Type::Fn(Box::new(receiver_ty), Box::new(ty)),
+        }
+    }
+
+    /// Type of dot call `.method args` on a receiver of type `receiver_ty`
+    fn dot(&mut self, receiver_ty: &Type, receiver_span: Span, method: &Expr, args: &[Expr]) -> Type {
+        let field = match &method.kind {
+            ExprKind::Name(name) if args.is_empty() => self.field(receiver_ty, name),
+            _ => None,
+        };
+        let elements = match (&method.kind, self.unifier.shallow(receiver_ty), args) {
+            (ExprKind::Name(name), Type::Tuple(elements), [_]) if name == "map" && !elements.is_empty() => Some(elements),
+            _ => None,
+        };
+        let ty = match (field, elements) {
+            (Some(field), _) => field,
+            (None, Some(elements)) => self.map_tuple(&elements, &args[0]),
+            (None, None) => {
+                let func = match &method.kind {
+                    ExprKind::Name(name) => self.dot_call(name, receiver_ty, method.span),
+                    _ => self.expr(method),
+                };
+                let mut ty = self.apply(func, method.span, receiver_ty.clone(), receiver_span);
+                for arg in args {
+                    let arg_ty = self.expr(arg);
+                    ty = self.apply(ty, method.span, arg_ty, arg.span);
+                }
+                ty
+            }
+        };
+        self.types.insert(method.span, ty.clone());
+        ty
+    }
+
+    /// Type of `tuple.map function`, a tuple of the results of `function` on each element. `function` is
+    /// generic and used once for each element, so the elements can have different types
+    fn map_tuple(&mut self, elements: &[Type], function: &Expr) -> Type {
+        let ty = self.generic(|this| this.expr(function));
+        let scheme = self.generalize(&[], &[ty], vec![]).remove(0);
+        // the uses for each element meet the bounds instead
+        self.dispatch.remove(&function.span);
+        let method = self.resolution.resolved.get(&function.span)
+            .map(|&id| &self.resolution.defs[id])
+            .filter(|def| def.kind == DefKind::Method)
+            .map(|def| def.name.clone());
+        let results = elements.iter().enumerate().map(|(index, element)| {
+            let ty = self.instantiate(&scheme, function.span, method.as_ref());
+            let result = self.fresh();
+            let unifier = self.unifier.clone();
+            if !self.unifier.unify(&Type::Fn(Box::new(element.clone()), Box::new(result.clone())), &ty) {
+                self.unifier = unifier;
+                let element = self.unifier.describe(element).to_string().into();
+                let kind = ParseErrorKind::TupleMapElement { index, element, function: self.unifier.describe(&ty).to_string().into() };
+                self.diagnostics.push(Diagnostic { severity: Severity::Error, kind, span: function.span });
+            }
+            result
+        }).collect();
+        Type::Tuple(results)
+    }
+
+    /// Type of field `name` of a value of type `ty`, if it's a `class` with that field. A value whose type
+    /// isn't known yet is taken to be of the `class` with that field, if a single one has it
+    fn field(&mut self, ty: &Type, name: &ImStr) -> Option<Type> {
+        match self.unifier.shallow(ty) {
+            Type::Con(record, args) => Some(self.records.get(&record)?.get(name)?.field(&args)),
+            Type::Var(_) => {
+                let records: Vec<_> = self.records.iter()
+                    .filter_map(|(record, fields)| Some((record.clone(), fields.get(name)?.clone())))
+                    .collect();
+                let [(record, field)] = &records[..] else { return None };
+                let args: Vec<Type> = field.vars.iter().map(|_| self.fresh()).collect();
+                self.unifier.unify(ty, &Type::Con(record.clone(), args.clone()));
+                Some(field.field(&args))
+            }
+            _ => None,
+        }
+    }
+
+    fn binary(&mut self, op: &Ident, lhs: &Expr, rhs: &Expr, span: Span) -> Type {
+        let lhs_ty = self.expr(lhs);
+        // `value |> .method args` is `value.method args`, the section is inferred knowing its receiver
+        if op.name == "|>" && let ExprKind::Method { receiver: None, method, args } = &rhs.kind {
+            let ty = self.dot(&lhs_ty, lhs.span, method, args);
+            self.types.insert(rhs.span, Type::Fn(Box::new(lhs_ty), Box::new(ty.clone())));
+            return ty;
+        }
+        let rhs_ty = self.expr(rhs);
+        // overflowing constants are errors in release builds as well
+        let constant = |inferer: &Self, span| {
+            inferer.constants.contains_key(&span) || inferer.constant_ops.iter().any(|op| op.span == span)
+        };
+        if let Some((arithmetic, overflow)) = arithmetic_operator(&op.name, true)
+            && constant(self, lhs.span) && constant(self, rhs.span)
+        {
+            let operands = [lhs.span, rhs.span];
+            self.constant_ops.push(ConstantOp { op: arithmetic, overflow, operands, span, ty: lhs_ty.clone() });
+        }
+        match op.name.as_str() {
+            "|>" => self.apply(rhs_ty, rhs.span, lhs_ty, lhs.span),
+            "==" | "!=" | "<" | "<=" | ">" | ">=" => {
+                self.unify(&lhs_ty, Some(lhs.span), &rhs_ty, rhs.span);
+                Type::named("bool")
+            }
+            // wrapping and saturating operators only take integers
+            name if is_bounded_operator(name) => {
+                let integer = self.unifier.fresh(VarKind::Integer);
+                self.unify(&integer, Some(op.span), &lhs_ty, lhs.span);
+                self.unify(&lhs_ty, Some(lhs.span), &rhs_ty, rhs.span);
+                lhs_ty
+            }
+            // `a or b` is the first of them which is there, `and` and `or` of `bool`s included
+            "+" | "-" | "*" | "/" | "%" | "**" | "and" | "or" | "&&" | "||" => {
+                self.unify(&lhs_ty, Some(lhs.span), &rhs_ty, rhs.span);
+                lhs_ty
+            }
+            _ => self.fresh(),
+        }
+    }
+
+    /// Type of the last statement of a block if it's an expression, `()` otherwise
+    fn block(&mut self, stmts: &[Stmt]) -> Type {
+        let mut ty = Type::unit();
+        for stmt in stmts {
+            ty = match stmt {
+                Stmt::Expr(expr) => self.expr(expr),
+                Stmt::Let(let_) => {
+                    let scheme = match let_.mutable {
+                        true => Scheme::mono(self.let_value(let_)),
+                        false => {
+                            let ty = self.generic(|inferer| inferer.let_value(let_));
+                            self.generalize(&[], &[ty], vec![]).remove(0)
+                        }
+                    };
+                    self.define(&let_.name, scheme);
+                    Type::unit()
+                }
+                // local functions see themselves
+                Stmt::Function(function) => {
+                    let ty = self.generic(|inferer| {
+                        let placeholder = inferer.fresh();
+                        inferer.define(&function.name, Scheme::mono(placeholder.clone()));
+                        let ty = inferer.function(function, &mut HashMap::new());
+                        inferer.unify(&ty, None, &placeholder, function.name.span);
+                        ty
+                    });
+                    let scheme = self.generalize(&[], &[ty], vec![]).remove(0);
+                    self.define(&function.name, scheme);
+                    Type::unit()
+                }
+                _ => Type::unit(),
+            };
+        }
+        ty
+    }
+
+    /// Type of the values `pattern` matches, binding its names
+    fn pattern(&mut self, pattern: &Pattern) -> Type {
+        let ty = match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::Error => self.fresh(),
+            PatternKind::Bind { name, pattern, guard } => {
+                let ty = match pattern {
+                    Some(pattern) => self.pattern(pattern),
+                    None => self.fresh(),
+                };
+                self.define(name, Scheme::mono(ty.clone()));
+                if let Some(guard) = guard {
+                    self.condition(guard);
+                }
+                ty
+            }
+            PatternKind::Literal { value, negative } => self.literal(value, *negative, pattern.span),
+            PatternKind::Constructor { name, args } => {
+                let mut ty = self.name(name.span);
+                for arg in args {
+                    let arg_ty = self.pattern(arg);
+                    ty = self.apply(ty, name.span, arg_ty, arg.span);
+                }
+                // fields left out match anything
+                while let Type::Fn(_, result) = self.unifier.shallow(&ty) {
+                    ty = *result;
+                }
+                ty
+            }
+            PatternKind::Unit => Type::unit(),
+            PatternKind::Tuple(elements) => Type::Tuple(elements.iter().map(|element| self.pattern(element)).collect()),
+            PatternKind::List(elements) => {
+                let element = self.fresh();
+                let origin = elements.first().map(|first| first.span);
+                for item in elements {
+                    let ty = self.pattern(item);
+                    self.unify(&element, origin, &ty, item.span);
+                }
+                Type::Con("Array".into(), vec![element])
+            }
+            PatternKind::Optional { name, default } => {
+                let ty = self.fresh();
+                if let Some(default) = default {
+                    let default_ty = self.expr(default);
+                    self.unify(&ty, Some(name.span), &default_ty, default.span);
+                }
+                self.define(name, Scheme::mono(ty.clone()));
+                ty
+            }
+            PatternKind::Range { start, end, .. } => {
+                let element = self.fresh();
+                let origin = start.as_ref().map(|start| start.span);
+                for bound in [start, end].into_iter().flatten() {
+                    let ty = self.pattern(bound);
+                    self.unify(&element, origin, &ty, bound.span);
+                }
+                element
+            }
+        };
+        self.types.insert(pattern.span, ty.clone());
+        ty
+    }
+}
+
+/// Spans of the names and names of the methods used in a definition
+#[derive(Default)]
+struct Uses {
+    names: Vec<Span>,
+    methods: Vec<ImStr>,
+}
+
+impl Visit for Uses {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Name(_) => self.names.push(expr.span),
+            ExprKind::Method { method, .. } => {
+                if let ExprKind::Name(name) = &method.kind {
+                    self.methods.push(name.clone());
+                }
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Names of the types a type expression is written with
+#[derive(Default)]
+struct TypeNames(Vec<ImStr>);
+
+impl Visit for TypeNames {
+    fn visit_type(&mut self, ty: &TypeExpr) {
+        match &ty.kind {
+            TypeExprKind::Name(name) => self.0.push(name.clone()),
+            TypeExprKind::Apply { name, .. } => self.0.push(name.name.clone()),
+            _ => {}
+        }
+        walk_type(self, ty);
+    }
+}
+
+/// Strongly connected components of a graph given by the nodes each node has edges to, each after
+/// the ones it has edges to
+fn components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'e> {
+        edges: &'e [Vec<usize>],
+        /// Visit order and lowest visit order reachable of each visited node
+        visits: Vec<Option<(usize, usize)>>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            let order = self.stack.len() + self.components.iter().map(Vec::len).sum::<usize>();
+            self.visits[node] = Some((order, order));
+            self.stack.push(node);
+            self.on_stack[node] = true;
+            for &next in &self.edges[node] {
+                match self.visits[next] {
+                    None => {
+                        self.visit(next);
+                        let low = self.visits[next].unwrap().1;
+                        self.lower(node, low);
+                    }
+                    Some((next_order, _)) if self.on_stack[next] => self.lower(node, next_order),
+                    Some(_) => {}
+                }
+            }
+            let (order, low) = self.visits[node].unwrap();
+            if order == low {
+                let start = self.stack.iter().rposition(|&other| other == node).unwrap();
+                let mut component: Vec<usize> = self.stack.drain(start..).collect();
+                component.sort();
+                component.iter().for_each(|&other| self.on_stack[other] = false);
+                self.components.push(component);
+            }
+        }
+
+        fn lower(&mut self, node: usize, low: usize) {
+            if let Some((_, node_low)) = &mut self.visits[node] {
+                *node_low = (*node_low).min(low);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        edges,
+        visits: vec![None; edges.len()],
+        stack: vec![],
+        on_stack: vec![false; edges.len()],
+        components: vec![],
+    };
+    for node in 0..edges.len() {
+        if tarjan.visits[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.components
+}
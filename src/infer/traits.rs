@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+use crate::ImStr;
+use crate::infer::{Inferer, Bound, Scheme, Type};
+use crate::parser::*;
+use crate::resolve::DefKind;
+
+/// Trait declaration, its methods are generic over the trait parameters first and bounded by the trait
+#[derive(Debug, Clone)]
+pub(super) struct Trait {
+    pub(super) name: ImStr,
+    pub(super) params: usize,
+    /// Methods in declaration order, which is their slot in vtables
+    pub(super) methods: Vec<Method>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Method {
+    pub(super) name: ImStr,
+    pub(super) scheme: Scheme,
+    /// Whether the trait defines it, impls may then leave it out
+    pub(super) default: bool,
+}
+
+/// `trait Name args... for Type` generic over the type variables it's written with. The type of its
+/// scheme is the tuple of the trait arguments, `Type` first, and the bounds its `where` clause
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impl {
+    pub trait_name: ImStr,
+    pub scheme: Scheme,
+    pub span: Span,
+}
+
+/// How a bound is met
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dispatch {
+    /// By the impl at this index in [`Typing::impls`](super::Typing::impls), `bounds` meeting the ones of
+    /// its `where` clause
+    Static { impl_index: usize, bounds: Vec<Dispatch> },
+    /// By a bound of the generic definition the use is in, each instance of it picking its impl
+    Generic,
+    /// By a `dyn` value, whose vtable has the method used at `slot`
+    Dynamic { trait_name: ImStr, slot: Option<usize> },
+}
+
+/// Vtable of a value cast to `dyn` trait `trait_name`, whose methods are the ones of impl `impl_index`
+/// or the defaults of the trait
+#[derive(Debug, Clone, PartialEq)]
+pub struct VTable {
+    pub trait_name: ImStr,
+    pub impl_index: usize,
+    /// Method names by slot
+    pub slots: Vec<ImStr>,
+    /// How the bounds of the `where` clause of the impl are met
+    pub bounds: Vec<Dispatch>,
+    /// Span of the cast
+    pub span: Span,
+}
+
+/// Bound a use has to meet, met once its types pick an impl
+#[derive(Debug, Clone)]
+pub(super) struct Obligation {
+    bound: Bound,
+    span: Span,
+    origin: Origin,
+}
+
+#[derive(Debug, Clone)]
+enum Origin {
+    /// Bound `index` of the definition used, `method` of the trait if it's one
+    Use { index: usize, method: Option<ImStr> },
+    /// Cast to a `dyn` trait, which needs a vtable
+    Cast,
+    /// Bound of the `where` clause of an impl picked for another bound, at `path` in the dispatches at its
+    /// span, or in the bounds of vtable `vtable` if it's picked for a cast
+    Impl { vtable: Option<usize>, path: Vec<usize> },
+}
+
+impl Inferer<'_> {
+    fn report(&mut self, kind: ParseErrorKind, span: Span) {
+        self.diagnostics.push(Diagnostic { severity: Severity::Error, kind, span });
+    }
+
+    fn find_trait(&self, name: &ImStr) -> Option<&Trait> {
+        self.traits.iter().find(|trait_| trait_.name == *name)
+    }
+
+    /// Type of a use at `span` of a definition of type `scheme`, which must meet its bounds. `method` is
+    /// the name of the trait method it is. Several uses at a span, one per element of a mapped tuple,
+    /// have their dispatches one after the other
+    pub(super) fn instantiate(&mut self, scheme: &Scheme, span: Span, method: Option<&ImStr>) -> Type {
+        let (ty, bounds, literals) = self.unifier.instantiate_literals(scheme);
+        self.literals.extend(literals);
+        let mut offset = 0;
+        if !bounds.is_empty() {
+            let dispatches = self.dispatch.entry(span).or_default();
+            offset = dispatches.len();
+            dispatches.extend(vec![Dispatch::Generic; bounds.len()]);
+        }
+        for (index, bound) in bounds.into_iter().enumerate() {
+            let origin = Origin::Use { index: offset + index, method: method.cloned() };
+            self.obligations.push(Obligation { bound, span, origin });
+        }
+        ty
+    }
+
+    /// Takes the obligations on the variables of `types` made in definitions a level deeper
+    fn generic_obligations(&mut self, types: &[Type]) -> Vec<Obligation> {
+        let mut vars = vec![];
+        types.iter().for_each(|ty| self.unifier.generic_vars(ty, &mut vars));
+        let (generic, pending) = std::mem::take(&mut self.obligations).into_iter().partition(|obligation| {
+            let mut bound_vars = vec![];
+            obligation.bound.args.iter().for_each(|arg| self.unifier.generic_vars(arg, &mut bound_vars));
+            bound_vars.iter().any(|var| vars.contains(var))
+        });
+        self.obligations = pending;
+        generic
+    }
+
+    /// Schemes of definitions of `types` inferred a level deeper, bounded by `bounds` and by the ones their
+    /// uses left unmet on their type variables. The variables of `leading` come first. Bounds on other
+    /// variables are left to the uses deciding them or to literals defaulting
+    pub(super) fn generalize(&mut self, leading: &[Type], types: &[Type], mut bounds: Vec<Bound>) -> Vec<Scheme> {
+        self.solve();
+        let mut vars = leading.to_vec();
+        vars.extend(types.iter().cloned());
+        for obligation in self.generic_obligations(&vars) {
+            let bound = self.unifier.describe_bound(&obligation.bound);
+            if !bounds.iter().any(|other| self.unifier.describe_bound(other) == bound) {
+                bounds.push(obligation.bound);
+            }
+        }
+        types.iter().map(|ty| self.unifier.generalize(leading, ty, &bounds, &self.literals)).collect()
+    }
+
+    /// Meets the bounds whose types pick an impl and reports the ones no impl meets, until no more are met
+    pub(super) fn solve(&mut self) {
+        loop {
+            let mut progress = false;
+            for obligation in std::mem::take(&mut self.obligations) {
+                match self.meet(&obligation) {
+                    true => progress = true,
+                    false => self.obligations.push(obligation),
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+    }
+
+    /// Meets `obligation` if its types pick an impl, returns whether it's done with, reported included
+    fn meet(&mut self, obligation: &Obligation) -> bool {
+        let Obligation { bound, span, .. } = obligation;
+        let args: Vec<Type> = bound.args.iter().map(|arg| self.unifier.shallow(arg)).collect();
+        if let Some(Type::Con(name, inner)) = args.first() && name == "dyn"
+            && let [Type::Con(trait_name, given)] = inner.as_slice() && *trait_name == bound.trait_name
+        {
+            for (arg, given) in args[1..].iter().zip(given) {
+                self.unify(given, None, arg, *span);
+            }
+            let slot = match &obligation.origin {
+                Origin::Use { method: Some(method), .. } => self.find_trait(trait_name)
+                    .and_then(|trait_| trait_.methods.iter().position(|other| other.name == *method)),
+                _ => None,
+            };
+            self.record(obligation, Dispatch::Dynamic { trait_name: trait_name.clone(), slot });
+            return true;
+        }
+        let matching: Vec<usize> = (0..self.impls.len())
+            .filter(|&index| self.impls[index].trait_name == bound.trait_name && self.impl_matches(index, &args))
+            .collect();
+        match matching[..] {
+            [index] => {
+                let (ty, bounds) = self.unifier.instantiate(&self.impls[index].scheme);
+                self.unifier.unify(&ty, &Type::Tuple(args));
+                self.record(obligation, Dispatch::Static { impl_index: index, bounds: vec![Dispatch::Generic; bounds.len()] });
+                let (vtable, path) = match &obligation.origin {
+                    Origin::Use { index, .. } => (None, vec![*index]),
+                    Origin::Cast => (Some(self.vtables.len() - 1), vec![]),
+                    Origin::Impl { vtable, path } => (*vtable, path.clone()),
+                };
+                for (index, bound) in bounds.into_iter().enumerate() {
+                    let path = path.iter().copied().chain([index]).collect();
+                    self.obligations.push(Obligation { bound, span: *span, origin: Origin::Impl { vtable, path } });
+                }
+                true
+            }
+            [] if matches!(args.first(), Some(Type::Var(_))) => false,
+            [] => {
+                let bound = self.unifier.describe_bound(bound).to_string().into();
+                self.report(ParseErrorKind::NoImpl { bound }, *span);
+                true
+            }
+            _ if args.iter().all(|arg| self.unifier.is_known(arg)) => {
+                let bound = self.unifier.describe_bound(bound).to_string().into();
+                self.report(ParseErrorKind::AmbiguousImpl { bound }, *span);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether impl `index` can be the one of a bound on `args`
+    fn impl_matches(&mut self, index: usize, args: &[Type]) -> bool {
+        let saved = self.unifier.clone();
+        let (ty, _) = self.unifier.instantiate(&self.impls[index].scheme);
+        let matches = self.unifier.unify(&ty, &Type::Tuple(args.to_vec()));
+        self.unifier = saved;
+        matches
+    }
+
+    /// Records how `obligation` is met, the dispatches of the bounds of an impl picked start out generic
+    fn record(&mut self, obligation: &Obligation, dispatch: Dispatch) {
+        let (dispatches, path) = match &obligation.origin {
+            Origin::Use { index, .. } => (self.dispatch.get_mut(&obligation.span), std::slice::from_ref(index)),
+            Origin::Impl { vtable: None, path } => (self.dispatch.get_mut(&obligation.span), &path[..]),
+            Origin::Impl { vtable: Some(vtable), path } => (Some(&mut self.vtables[*vtable].bounds), &path[..]),
+            Origin::Cast => {
+                if let Dispatch::Static { impl_index, bounds } = dispatch {
+                    let trait_name = obligation.bound.trait_name.clone();
+                    let slots = self.find_trait(&trait_name)
+                        .map_or_else(Vec::new, |trait_| trait_.methods.iter().map(|method| method.name.clone()).collect());
+                    self.vtables.push(VTable { trait_name, impl_index, slots, bounds, span: obligation.span });
+                }
+                return;
+            }
+        };
+        let Some((last, parents)) = path.split_last() else { return };
+        let mut dispatches = dispatches;
+        for &index in parents {
+            dispatches = match dispatches.and_then(|dispatches| dispatches.get_mut(index)) {
+                Some(Dispatch::Static { bounds, .. }) => Some(bounds),
+                _ => None,
+            };
+        }
+        if let Some(entry) = dispatches.and_then(|dispatches| dispatches.get_mut(*last)) {
+            *entry = dispatch;
+        }
+    }
+
+    /// Checks a cast at `span` of a value of type `value` to `ty`, a `dyn` trait in `ty` needs an impl of the
+    /// trait for the type in its place. Casts convert between other types freely
+    pub(super) fn coerce(&mut self, value: &Type, ty: &Type, span: Span) {
+        match (self.unifier.shallow(value), ty) {
+            (value, Type::Con(name, inner)) if name == "dyn" => {
+                let [Type::Con(trait_name, given)] = inner.as_slice() else { return };
+                let Some(params) = self.find_trait(trait_name).map(|trait_| trait_.params) else { return };
+                let mut args = vec![value];
+                args.extend(given.iter().cloned());
+                while args.len() < params {
+                    args.push(self.fresh());
+                }
+                let bound = Bound { trait_name: trait_name.clone(), args };
+                self.obligations.push(Obligation { bound, span, origin: Origin::Cast });
+            }
+            (Type::Con(name, args), Type::Con(other, other_args)) if name == *other && args.len() == other_args.len() => {
+                for (arg, other) in args.iter().zip(other_args) {
+                    self.coerce(arg, other, span);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Declares a trait and its methods. A method parameter named like a trait parameter has its type. The
+    /// methods without a default are declared first, so default methods can call them
+    pub(super) fn trait_decl(&mut self, decl: &TraitDecl) {
+        let index = self.traits.len();
+        self.traits.push(Trait { name: decl.name.name.clone(), params: decl.params.len(), methods: vec![] });
+        let (signatures, defaults): (Vec<_>, Vec<_>) = decl.items.iter().partition(|item| matches!(item, Stmt::Signature(_)));
+        for item in signatures.into_iter().chain(defaults) {
+            let (name, default) = match item {
+                Stmt::Signature(signature) => (&signature.name, false),
+                Stmt::Function(function) => (&function.name, true),
+                _ => continue,
+            };
+            let (params, ty) = self.generic(|inferer| {
+                let mut vars: HashMap<ImStr, Type> = decl.params.iter().map(|param| (param.name.clone(), inferer.fresh())).collect();
+                let params: Vec<Type> = decl.params.iter().map(|param| vars[&param.name].clone()).collect();
+                let ty = match item {
+                    Stmt::Signature(signature) => {
+                        let param_types = inferer.params(&signature.params, &vars);
+                        let result = inferer.type_expr(&signature.ty, &mut vars);
+                        Type::function(param_types.into_iter(), result)
+                    }
+                    Stmt::Function(function) => inferer.function(function, &mut vars),
+                    _ => unreachable!(),
+                };
+                (params, ty)
+            });
+            let bound = Bound { trait_name: decl.name.name.clone(), args: params.clone() };
+            let scheme = self.generalize(&params, &[ty], vec![bound]).remove(0);
+            // only the first of the methods with the same name is declared
+            if self.defs.get(&name.span).is_some_and(|&id| !self.schemes.contains_key(&id)) {
+                self.define(name, scheme.clone());
+            }
+            let method = Method { name: name.name.clone(), scheme, default };
+            self.traits[index].methods.push(method);
+        }
+    }
+
+    /// Trait arguments of an impl, `for` type first, and bounds of its `where` clause, with its type
+    /// variables by name
+    fn impl_types(&mut self, decl: &ImplDecl, params: usize) -> (Vec<Type>, Vec<Bound>, HashMap<ImStr, Type>) {
+        let mut vars = HashMap::new();
+        let mut args = vec![self.type_expr(&decl.ty, &mut vars)];
+        args.extend(decl.trait_ref.args.iter().map(|arg| self.type_expr(arg, &mut vars)));
+        args.truncate(params);
+        // trait parameters left out are given by the methods
+        while args.len() < params {
+            args.push(self.fresh());
+        }
+        let mut bounds = vec![];
+        for predicate in &decl.where_clause {
+            let ty = self.type_expr(&predicate.ty, &mut vars);
+            for trait_ref in &predicate.bounds {
+                let Some(params) = self.find_trait(&trait_ref.name.name).map(|trait_| trait_.params) else {
+                    self.report(ParseErrorKind::UnknownTrait { name: trait_ref.name.name.clone() }, trait_ref.name.span);
+                    continue;
+                };
+                let mut args = vec![ty.clone()];
+                args.extend(trait_ref.args.iter().map(|arg| self.type_expr(arg, &mut vars)));
+                args.truncate(params);
+                while args.len() < params {
+                    args.push(self.fresh());
+                }
+                bounds.push(Bound { trait_name: trait_ref.name.name.clone(), args });
+            }
+        }
+        (args, bounds, vars)
+    }
+
+    /// Declares an impl for bounds to pick, before the definitions whose uses have them
+    pub(super) fn impl_header(&mut self, decl: &ImplDecl) {
+        let name = &decl.trait_ref.name;
+        let Some(params) = self.find_trait(&name.name).map(|trait_| trait_.params) else {
+            return self.report(ParseErrorKind::UnknownTrait { name: name.name.clone() }, name.span);
+        };
+        let (args, bounds) = self.generic(|inferer| {
+            let (args, bounds, _) = inferer.impl_types(decl, params);
+            (args, bounds)
+        });
+        let scheme = self.unifier.generalize(&[], &Type::Tuple(args), &bounds, &[]);
+        self.impls.push(Impl { trait_name: name.name.clone(), scheme, span: decl.span });
+    }
+
+    /// Checks the definitions of an impl against the methods of its trait. The bounds they need on the
+    /// type variables of the impl must be in its `where` clause
+    pub(super) fn impl_decl(&mut self, decl: &ImplDecl) {
+        let name = &decl.trait_ref.name;
+        let Some(trait_) = self.find_trait(&name.name).cloned() else {
+            for item in &decl.items {
+                if let Stmt::Function(function) = item {
+                    self.generic(|inferer| inferer.function(function, &mut HashMap::new()));
+                }
+            }
+            return;
+        };
+        let (args, bounds) = self.generic(|inferer| {
+            let (args, bounds, mut vars) = inferer.impl_types(decl, trait_.params);
+            for item in &decl.items {
+                let Stmt::Function(function) = item else { continue };
+                let ty = inferer.function(function, &mut vars);
+                let Some(method) = trait_.methods.iter().find(|method| method.name == function.name.name) else {
+                    let kind = ParseErrorKind::NotTraitMethod { method: function.name.name.clone(), trait_name: trait_.name.clone() };
+                    inferer.report(kind, function.name.span);
+                    continue;
+                };
+                let (expected, method_bounds) = inferer.unifier.instantiate(&method.scheme);
+                for (param, arg) in method_bounds[0].args.iter().zip(&args) {
+                    inferer.unifier.unify(param, arg);
+                }
+                inferer.unify(&expected, None, &ty, function.name.span);
+            }
+            for method in trait_.methods.iter().filter(|method| !method.default) {
+                let defined = decl.items.iter().any(|item| matches!(item, Stmt::Function(function) if function.name.name == method.name));
+                if !defined {
+                    let kind = ParseErrorKind::MissingTraitMethod { method: method.name.clone(), trait_name: trait_.name.clone() };
+                    inferer.report(kind, name.span);
+                }
+            }
+            (args, bounds)
+        });
+        self.solve();
+        for obligation in self.generic_obligations(&args) {
+            let met = bounds.iter().any(|bound| {
+                bound.trait_name == obligation.bound.trait_name && self.unifier.unify(&Type::Tuple(bound.args.clone()), &Type::Tuple(obligation.bound.args.clone()))
+            });
+            if !met {
+                let bound = self.unifier.generalize(&[], &Type::unit(), &[obligation.bound], &[]).bounds.remove(0).to_string().into();
+                self.report(ParseErrorKind::MissingBound { bound }, obligation.span);
+            }
+        }
+    }
+
+    /// Type of the function a dot call of `name` on a value of type `receiver` at `span` calls: the local,
+    /// module or prelude function or the method of a trait named `name` which takes the receiver first
+    pub(super) fn dot_call(&mut self, name: &ImStr, receiver: &Type, span: Span) -> Type {
+        let function = match (self.resolution.resolved.get(&span), self.globals.get(name)) {
+            (Some(id), _) => self.schemes.get(id),
+            (None, Some(id)) if self.resolution.defs[*id].kind != DefKind::Method => self.schemes.get(id),
+            (None, Some(_)) => None,
+            (None, None) => self.prelude.get(name).filter(|(_, kind)| *kind != DefKind::Method).map(|(scheme, _)| scheme),
+        };
+        let mut candidates: Vec<(Option<ImStr>, Scheme)> = function.map(|scheme| (None, scheme.clone())).into_iter().collect();
+        for trait_ in &self.traits {
+            if let Some(method) = trait_.methods.iter().find(|method| method.name == *name) {
+                candidates.push((Some(trait_.name.clone()), method.scheme.clone()));
+            }
+        }
+        let dynamic = match self.unifier.shallow(receiver) {
+            Type::Con(name, inner) if name == "dyn" => match inner.as_slice() {
+                [Type::Con(trait_name, _)] => Some(trait_name.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let matching: Vec<_> = candidates.iter()
+            .filter(|(trait_name, scheme)| match trait_name {
+                Some(trait_name) if dynamic.as_ref() == Some(trait_name) => true,
+                trait_name => self.takes_receiver(scheme, receiver, trait_name.as_ref()),
+            })
+            .collect();
+        match matching[..] {
+            // a `dyn` receiver meets the bound of a method with its vtable
+            [(trait_name, scheme)] => {
+                let (method, scheme) = (trait_name.is_some().then_some(name), scheme.clone());
+                self.instantiate(&scheme, span, method)
+            }
+            [] => {
+                let receiver = self.unifier.describe(receiver).to_string().into();
+                self.report(ParseErrorKind::NoMethod { method: name.clone(), receiver }, span);
+                self.fresh()
+            }
+            _ => {
+                let candidates = matching.iter()
+                    .map(|(trait_name, _)| match trait_name {
+                        Some(trait_name) => format!("{trait_name}.{name}").into(),
+                        None => name.clone(),
+                    })
+                    .collect();
+                self.report(ParseErrorKind::AmbiguousMethod { method: name.clone(), candidates }, span);
+                self.fresh()
+            }
+        }
+    }
+
+    /// Whether a function of type `scheme` takes a value of type `receiver` first. For a method of trait
+    /// `trait_name`, an impl of it must be able to take it too
+    fn takes_receiver(&mut self, scheme: &Scheme, receiver: &Type, trait_name: Option<&ImStr>) -> bool {
+        let saved = self.unifier.clone();
+        let (ty, bounds) = self.unifier.instantiate(scheme);
+        let takes = match self.unifier.shallow(&ty) {
+            Type::Fn(param, _) => self.unifier.unify(&param, receiver),
+            Type::Var(_) => true,
+            _ => false,
+        };
+        let takes = takes && match (trait_name, bounds.first()) {
+            (Some(trait_name), Some(bound)) => {
+                let args: Vec<Type> = bound.args.iter().map(|arg| self.unifier.shallow(arg)).collect();
+                matches!(args.first(), Some(Type::Var(_))) || (0..self.impls.len())
+                    .any(|index| self.impls[index].trait_name == *trait_name && self.impl_matches(index, &args))
+            }
+            _ => true,
+        };
+        self.unifier = saved;
+        takes
+    }
+}
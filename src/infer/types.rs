@@ -0,0 +1,419 @@
+use std::fmt::{Display, Formatter};
+use crate::ImStr;
+use crate::numeric::Number;
+use crate::parser::Span;
+
+/// Index of a type variable in the [`Unifier`]
+pub type TypeVar = usize;
+
+/// Integer types, which integer literals without suffix can take
+pub const INTEGER_TYPES: [&str; 11] = ["int", "s8", "s16", "s32", "s64", "s128", "u8", "u16", "u32", "u64", "u128"];
+/// Float types, which float literals without suffix can take
+pub const FLOAT_TYPES: [&str; 2] = ["f32", "f64"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Var(TypeVar),
+    /// Variable of a [`Scheme`] by its index, replaced by a fresh one each time the scheme is used
+    Gen(usize),
+    /// Named type applied to its arguments, `s32` or `'t .Option`
+    Con(ImStr, Vec<Type>),
+    /// Tuple, `()` included
+    Tuple(Vec<Type>),
+    Fn(Box<Type>, Box<Type>),
+}
+
+impl Type {
+    pub fn named(name: &str) -> Self {
+        Type::Con(name.into(), vec![])
+    }
+
+    pub fn unit() -> Self {
+        Type::Tuple(vec![])
+    }
+
+    /// Curried function of `params` giving `result`, `result` itself without parameters
+    pub fn function(params: impl DoubleEndedIterator<Item = Type>, result: Type) -> Self {
+        params.rev().fold(result, |result, param| Type::Fn(Box::new(param), Box::new(result)))
+    }
+
+    /// Replaces the variables of a scheme by `args`
+    fn substitute(&self, args: &[Type]) -> Type {
+        match self {
+            Type::Gen(i) => args[*i].clone(),
+            Type::Var(_) => self.clone(),
+            Type::Con(name, params) => Type::Con(name.clone(), params.iter().map(|param| param.substitute(args)).collect()),
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(|element| element.substitute(args)).collect()),
+            Type::Fn(param, result) => Type::Fn(Box::new(param.substitute(args)), Box::new(result.substitute(args))),
+        }
+    }
+}
+
+/// Kind of the types a variable can stand for, literals leave the exact type to the context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarKind {
+    Any,
+    Integer,
+    Float,
+}
+
+/// `'t is Trait args...`, the trait implemented for the types `args`, whose first one is the one written before `is`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bound {
+    pub trait_name: ImStr,
+    pub args: Vec<Type>,
+}
+
+impl Bound {
+    fn substitute(&self, args: &[Type]) -> Bound {
+        Bound { trait_name: self.trait_name.clone(), args: self.args.iter().map(|arg| arg.substitute(args)).collect() }
+    }
+}
+
+/// Number literal without suffix as written, checked to fit in its type once it's known
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiteralCheck {
+    pub written: ImStr,
+    pub value: Number,
+    pub ty: Type,
+    pub span: Span,
+}
+
+/// Type of a definition, generic over a variable of each of `vars` whose types must meet `bounds`.
+/// `literals` are the literals of the definition typed by its variables, checked again at each use
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scheme {
+    pub vars: Vec<VarKind>,
+    pub ty: Type,
+    pub bounds: Vec<Bound>,
+    pub literals: Vec<LiteralCheck>,
+}
+
+impl Scheme {
+    /// Type which isn't generic
+    pub fn mono(ty: Type) -> Self {
+        Scheme { vars: vec![], ty, bounds: vec![], literals: vec![] }
+    }
+
+    /// Type of a field or constructor of a type declared with `args` as its parameters
+    pub fn field(&self, args: &[Type]) -> Type {
+        self.ty.substitute(args)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Binding {
+    Bound(Type),
+    /// Variable created while inferring the definitions `level` deep, which it's generalized out of
+    Unbound { level: usize, kind: VarKind },
+}
+
+/// Type variables and what they are unified with
+#[derive(Debug, Default, Clone)]
+pub struct Unifier {
+    vars: Vec<Binding>,
+    /// Number of definitions being inferred the current one is in
+    pub level: usize,
+    /// Variable and the type holding it a unification failed to bind it to, as that would make an
+    /// infinite type
+    pub infinite: Option<(Type, Type)>,
+}
+
+impl Unifier {
+    pub fn fresh(&mut self, kind: VarKind) -> Type {
+        self.vars.push(Binding::Unbound { level: self.level, kind });
+        Type::Var(self.vars.len() - 1)
+    }
+
+    /// `ty` with its outermost bound variables replaced
+    pub fn shallow(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Var(var) => match &self.vars[*var] {
+                Binding::Bound(bound) => self.shallow(bound),
+                Binding::Unbound { .. } => ty.clone(),
+            },
+            _ => ty.clone(),
+        }
+    }
+
+    /// `ty` resolved to be shown, variables of literals are written like `{integer}`
+    pub fn describe(&self, ty: &Type) -> Type {
+        match self.shallow(ty) {
+            Type::Var(var) => match self.vars[var] {
+                Binding::Unbound { kind: VarKind::Integer, .. } => Type::named("{integer}"),
+                Binding::Unbound { kind: VarKind::Float, .. } => Type::named("{float}"),
+                _ => Type::Var(var),
+            },
+            Type::Con(name, args) => Type::Con(name, args.iter().map(|arg| self.describe(arg)).collect()),
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(|element| self.describe(element)).collect()),
+            Type::Fn(param, result) => Type::Fn(Box::new(self.describe(&param)), Box::new(self.describe(&result))),
+            ty => ty,
+        }
+    }
+
+    /// Gives the variables of literals left unbound their default type, `int` or `f64`
+    pub fn default_literals(&mut self) {
+        for binding in &mut self.vars {
+            let ty = match binding {
+                Binding::Unbound { kind: VarKind::Integer, .. } => Type::named("int"),
+                Binding::Unbound { kind: VarKind::Float, .. } => Type::named("f64"),
+                _ => continue,
+            };
+            *binding = Binding::Bound(ty);
+        }
+    }
+
+    pub fn describe_bound(&self, bound: &Bound) -> Bound {
+        Bound { trait_name: bound.trait_name.clone(), args: bound.args.iter().map(|arg| self.describe(arg)).collect() }
+    }
+
+    /// Makes `a` and `b` the same type, returns whether they can be
+    pub fn unify(&mut self, a: &Type, b: &Type) -> bool {
+        match (self.shallow(a), self.shallow(b)) {
+            (Type::Var(a), Type::Var(b)) if a == b => true,
+            (Type::Var(var), other) | (other, Type::Var(var)) => self.bind(var, &other),
+            (Type::Con(a, a_args), Type::Con(b, b_args)) => {
+                a == b && a_args.len() == b_args.len() && a_args.iter().zip(&b_args).all(|(a, b)| self.unify(a, b))
+            }
+            (Type::Tuple(a), Type::Tuple(b)) => a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| self.unify(a, b)),
+            (Type::Fn(a_param, a_result), Type::Fn(b_param, b_result)) => {
+                self.unify(&a_param, &b_param) && self.unify(&a_result, &b_result)
+            }
+            _ => false,
+        }
+    }
+
+    fn bind(&mut self, var: TypeVar, ty: &Type) -> bool {
+        let Binding::Unbound { level, kind } = self.vars[var] else { unreachable!("bound variables are resolved first") };
+        if let Type::Var(other) = *ty {
+            let Binding::Unbound { level: other_level, kind: other_kind } = self.vars[other] else { unreachable!() };
+            let kind = match (kind, other_kind) {
+                (VarKind::Any, kind) | (kind, VarKind::Any) => kind,
+                (kind, other_kind) if kind == other_kind => kind,
+                _ => return false,
+            };
+            self.vars[other] = Binding::Unbound { level: level.min(other_level), kind };
+        } else {
+            let fits = match kind {
+                VarKind::Any => true,
+                VarKind::Integer => matches!(ty, Type::Con(name, args) if args.is_empty() && INTEGER_TYPES.contains(&name.as_str())),
+                VarKind::Float => matches!(ty, Type::Con(name, args) if args.is_empty() && FLOAT_TYPES.contains(&name.as_str())),
+            };
+            if !fits {
+                return false;
+            }
+            if !self.adjust(var, level, ty) {
+                self.infinite = Some((Type::Var(var), ty.clone()));
+                return false;
+            }
+        }
+        self.vars[var] = Binding::Bound(ty.clone());
+        true
+    }
+
+    /// Lowers the levels of the variables in `ty` to `level` so they're generalized with `var`, returns
+    /// false if `var` is in `ty`, which would make an infinite type
+    fn adjust(&mut self, var: TypeVar, level: usize, ty: &Type) -> bool {
+        match self.shallow(ty) {
+            Type::Var(other) if other == var => false,
+            Type::Var(other) => {
+                if let Binding::Unbound { level: other_level, kind } = self.vars[other] {
+                    self.vars[other] = Binding::Unbound { level: other_level.min(level), kind };
+                }
+                true
+            }
+            Type::Con(_, args) | Type::Tuple(args) => args.iter().all(|arg| self.adjust(var, level, arg)),
+            Type::Fn(param, result) => self.adjust(var, level, &param) && self.adjust(var, level, &result),
+            Type::Gen(_) => true,
+        }
+    }
+
+    /// Keeps the variables of `ty` from being generalized out of the current definitions
+    pub fn fix(&mut self, ty: &Type) {
+        self.adjust(TypeVar::MAX, self.level, ty);
+    }
+
+    /// Variables of `ty` created in definitions deeper than the current one, added to `vars`
+    pub fn generic_vars(&self, ty: &Type, vars: &mut Vec<TypeVar>) {
+        match self.shallow(ty) {
+            Type::Var(var) => {
+                if matches!(self.vars[var], Binding::Unbound { level, .. } if level > self.level) && !vars.contains(&var) {
+                    vars.push(var);
+                }
+            }
+            Type::Con(_, args) | Type::Tuple(args) => args.iter().for_each(|arg| self.generic_vars(arg, vars)),
+            Type::Fn(param, result) => {
+                self.generic_vars(&param, vars);
+                self.generic_vars(&result, vars);
+            }
+            Type::Gen(_) => {}
+        }
+    }
+
+    /// Whether `ty` has no variables left
+    pub fn is_known(&self, ty: &Type) -> bool {
+        match self.shallow(ty) {
+            Type::Var(_) => false,
+            Type::Con(_, args) | Type::Tuple(args) => args.iter().all(|arg| self.is_known(arg)),
+            Type::Fn(param, result) => self.is_known(&param) && self.is_known(&result),
+            Type::Gen(_) => true,
+        }
+    }
+
+    /// Scheme of `ty` with `bounds` generic over its variables created in definitions deeper than the
+    /// current one, the variables of `leading` first. The ones of `literals` typed by these variables
+    /// are kept in the scheme
+    pub fn generalize(&self, leading: &[Type], ty: &Type, bounds: &[Bound], literals: &[LiteralCheck]) -> Scheme {
+        let mut vars = vec![];
+        for var in leading {
+            self.quantify(var, &mut vars);
+        }
+        let ty = self.quantify(ty, &mut vars);
+        let bounds = bounds.iter()
+            .map(|bound| Bound {
+                trait_name: bound.trait_name.clone(),
+                args: bound.args.iter().map(|arg| self.quantify(arg, &mut vars)).collect(),
+            })
+            .collect();
+        let literals = literals.iter()
+            .filter_map(|literal| match self.shallow(&literal.ty) {
+                Type::Var(var) => vars.iter().position(|&(other, _)| other == var),
+                _ => None,
+            }.map(|index| LiteralCheck { ty: Type::Gen(index), ..literal.clone() }))
+            .collect();
+        Scheme { vars: vars.into_iter().map(|(_, kind)| kind).collect(), ty, bounds, literals }
+    }
+
+    fn quantify(&self, ty: &Type, vars: &mut Vec<(TypeVar, VarKind)>) -> Type {
+        match self.shallow(ty) {
+            Type::Var(var) => match self.vars[var] {
+                Binding::Unbound { level, kind } if level > self.level => {
+                    let index = vars.iter().position(|&(other, _)| other == var).unwrap_or_else(|| {
+                        vars.push((var, kind));
+                        vars.len() - 1
+                    });
+                    Type::Gen(index)
+                }
+                _ => Type::Var(var),
+            },
+            Type::Con(name, args) => Type::Con(name, args.iter().map(|arg| self.quantify(arg, vars)).collect()),
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(|element| self.quantify(element, vars)).collect()),
+            Type::Fn(param, result) => Type::Fn(Box::new(self.quantify(&param, vars)), Box::new(self.quantify(&result, vars))),
+            ty => ty,
+        }
+    }
+
+    /// Type of a use of a definition of type `scheme` and the bounds the use must meet
+    pub fn instantiate(&mut self, scheme: &Scheme) -> (Type, Vec<Bound>) {
+        let (ty, bounds, _) = self.instantiate_literals(scheme);
+        (ty, bounds)
+    }
+
+    /// [`Unifier::instantiate`] with the literals of the definition at the types of the use
+    pub fn instantiate_literals(&mut self, scheme: &Scheme) -> (Type, Vec<Bound>, Vec<LiteralCheck>) {
+        let args: Vec<_> = scheme.vars.iter().map(|&kind| self.fresh(kind)).collect();
+        let literals = scheme.literals.iter()
+            .map(|literal| LiteralCheck { ty: literal.ty.substitute(&args), ..literal.clone() })
+            .collect();
+        (scheme.ty.substitute(&args), scheme.bounds.iter().map(|bound| bound.substitute(&args)).collect(), literals)
+    }
+}
+
+/// Precedence of types as written: a function, a postfix application or an atom
+const FUNCTION_TYPE: u8 = 0;
+const POSTFIX_TYPE: u8 = 1;
+const ATOM_TYPE: u8 = 2;
+
+impl Type {
+    fn fmt_at(&self, f: &mut Formatter<'_>, min: u8) -> std::fmt::Result {
+        let level = match self {
+            Type::Fn(..) => FUNCTION_TYPE,
+            Type::Con(_, args) if !args.is_empty() => POSTFIX_TYPE,
+            _ => ATOM_TYPE,
+        };
+        if level < min {
+            write!(f, "(")?;
+            self.fmt_at(f, FUNCTION_TYPE)?;
+            return write!(f, ")");
+        }
+        match self {
+            Type::Var(_) => write!(f, "_"),
+            Type::Gen(i) => match u8::try_from(*i).ok().filter(|&i| i < 26) {
+                Some(i) => write!(f, "'{}", (b'a' + i) as char),
+                None => write!(f, "'t{i}"),
+            },
+            Type::Con(name, args) if name == "dyn" && args.len() == 1 => {
+                args[0].fmt_at(f, POSTFIX_TYPE)?;
+                write!(f, " dyn")
+            }
+            Type::Con(name, args) => match args.split_first() {
+                None => write!(f, "{name}"),
+                Some((first, rest)) => {
+                    first.fmt_at(f, POSTFIX_TYPE)?;
+                    write!(f, " .{name}")?;
+                    for arg in rest {
+                        write!(f, " ")?;
+                        arg.fmt_at(f, ATOM_TYPE)?;
+                    }
+                    Ok(())
+                }
+            },
+            Type::Tuple(elements) => {
+                write!(f, "(")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.fmt_at(f, FUNCTION_TYPE)?;
+                }
+                if elements.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            Type::Fn(param, result) => {
+                param.fmt_at(f, POSTFIX_TYPE)?;
+                write!(f, " -> ")?;
+                result.fmt_at(f, FUNCTION_TYPE)
+            }
+        }
+    }
+}
+
+/// Types are written like type expressions, with `'a`, `'b`... for the variables of schemes
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_at(f, FUNCTION_TYPE)
+    }
+}
+
+impl Display for Bound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some((first, rest)) = self.args.split_first() else { return write!(f, "{}", self.trait_name) };
+        first.fmt_at(f, POSTFIX_TYPE)?;
+        write!(f, " is {}", self.trait_name)?;
+        for arg in rest {
+            write!(f, " ")?;
+            arg.fmt_at(f, ATOM_TYPE)?;
+        }
+        Ok(())
+    }
+}
+
+/// Schemes with bounds or variables of literals are written with a `where` clause, `'a is {integer}`
+/// for the variables of literals
+impl Display for Scheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.ty.fmt(f)?;
+        let kinds = self.vars.iter().enumerate().filter_map(|(i, kind)| match kind {
+            VarKind::Any => None,
+            VarKind::Integer => Some(format!("{} is {{integer}}", Type::Gen(i))),
+            VarKind::Float => Some(format!("{} is {{float}}", Type::Gen(i))),
+        });
+        let bounds = self.bounds.iter().map(|bound| bound.to_string());
+        for (i, constraint) in kinds.chain(bounds).enumerate() {
+            write!(f, "{}{constraint}", if i == 0 { " where " } else { ", " })?;
+        }
+        Ok(())
+    }
+}
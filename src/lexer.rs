@@ -6,8 +6,11 @@ use bitflags::bitflags;
 
 mod lex;
 mod lex_number;
+#[allow(clippy::suspicious_else_formatting)]
 mod lex_symbol;
+#[allow(clippy::redundant_pattern_matching)]
 mod lex_string;
+#[allow(clippy::from_str_radix_10)]
 mod lex_char;
 mod lex_operator;
 
@@ -35,6 +38,7 @@ pub enum TokenType{
     String{value: StringValue, quote_count: usize, prefix_len: usize, suffix_len: usize, errors: StringErrorFlags},
     Number{start_pos: usize, dot_pos: Option<usize>, exp_pos: Option<usize>, suf_pos: Option<usize>, errors: NumberErrorFlags},
     Indent,
+    Comment,
     Other,
     Error,
 }
@@ -55,7 +59,7 @@ struct State {
 }
 
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum StringValue
 {
     #[default]
@@ -72,9 +76,11 @@ pub fn is_keyword(str: &str) -> bool {
         |"by"
         |"class"
         |"const"
+        |"dyn"
         |"else"
         |"fn"
         |"for"
+        |"if"
         |"in"
         |"is"
         |"match"
@@ -90,8 +96,9 @@ pub fn is_keyword(str: &str) -> bool {
         |"trait"
         |"type"
         |"use"
-        |"with"
+        |"where"
         |"while"
+        |"with"
         |"yield"
     )
 }
@@ -81,7 +81,7 @@ impl Iterator for TokenIterator {
         let row = self.row;
         
         if num == 0 {
-            return Some(lex_indent(self));
+            return lex_indent(self);
         }
         
         match (char, char.general_category(), char.general_category_group()) {
@@ -94,6 +94,9 @@ impl Iterator for TokenIterator {
             ('\'', ..) => { Some(lex_char(self, start)) }
             ('#', ..) => {
                 self.next_char();
+                if let None | Some(State { char: ' ' | '\t' | '#', .. } | State { num: 0, .. }) = self.current {
+                    return Some(lex_comment(self, row, start));
+                }
                 Some(Token {
                     slice: '#'.into(),
                     row,
@@ -161,16 +164,32 @@ impl Iterator for TokenIterator {
     }
 }
 
-fn lex_indent(it: &mut TokenIterator) -> Token {
+fn lex_indent(it: &mut TokenIterator) -> Option<Token> {
     skip_whitespaces(it);
-    let Some(State {num, ..}) = it.current else { unreachable!() };
+    let State {num, ..} = it.current?;
     
-    Token{
+    Some(Token{
         slice: it.lines[it.row].slice(0..num),
         row: it.row,
         num: 0,
         col: 0,
         ty: Indent,
+    })
+}
+
+/// `#` followed by a space, another `#` or the line end comments out the rest of the line
+fn lex_comment(it: &mut TokenIterator, row: usize, start: State) -> Token {
+    let line = it.lines[row].clone();
+    while let Some(State { num: 1.., .. }) = it.current {
+        it.next_char();
+    }
+    
+    Token{
+        slice: line.slice(start.num..),
+        row,
+        col: start.col,
+        num: start.num,
+        ty: Comment,
     }
 }
 
@@ -199,6 +218,10 @@ pub(super) fn is_op(char: char) -> bool {
     !matches!(
         char,
         |';' |',' |'#' |'\'' |'"' |'_'
+    ) && !matches!(
+        char.general_category(),
+        | GeneralCategory::OpenPunctuation | GeneralCategory::InitialPunctuation
+        | GeneralCategory::ClosePunctuation | GeneralCategory::FinalPunctuation
     ) && matches!(
         char.general_category_group(),
         GeneralCategoryGroup::Punctuation | GeneralCategoryGroup::Symbol
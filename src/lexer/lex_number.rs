@@ -8,13 +8,13 @@ pub fn lex_number(it: &mut TokenIterator) -> Token {
     let line = it.lines[it.row].clone();
     it.next_char();
     
-    let start_pos 
+    let (start_pos, has_prefix)
         = match (fst_ch, it.current) {
         ('0', Some(State { char: 'x' | 'o' | 'b', num: 1.., .. })) => {
             it.next_char();
-            start_num + 2
+            (start_num + 2, true)
         }
-        _ => start_num
+        _ => (start_num, false)
     };
 
     let mut dot_pos = None;
@@ -24,7 +24,7 @@ pub fn lex_number(it: &mut TokenIterator) -> Token {
 
     // <0>.<1>e<2>
     let mut stage = 0;
-    let mut has_digit = [Some(fst_ch != '0'), None, None];
+    let mut has_digit = [Some(!has_prefix), None, None];
 
 
     'parse_loop: while let Some(State{char,  num: curr_num @ 1.., ..}) = it.current {
@@ -59,6 +59,8 @@ pub fn lex_number(it: &mut TokenIterator) -> Token {
 
                 continue 'parse_loop;
             },
+            // `0..2` is a range of integers, not `0.` followed by `.2`
+            '.' if stage == 0 && line[curr_num + 1..].starts_with('.') => break 'parse_loop,
             '.' if stage == 0 => {
                 dot_pos = Some(curr_num);
                 stage = 1;
@@ -8,7 +8,13 @@ pub fn lex_operator(it: &mut TokenIterator) -> Token {
     let row = it.row;
     let line = it.lines[row].clone();
     
-    skip_while_op(it);
+    // `..-1` and `..=-1` are ranges to a negative number
+    let range = ["..=", ".."].into_iter()
+        .find(|op| line[num..].strip_prefix(op).is_some_and(|rest| rest.starts_with('-')));
+    match range {
+        Some(op) => op.chars().for_each(|_| { it.next_char(); }),
+        None => skip_while_op(it),
+    }
     
     let end_pos= if let Some(State{num : end_pos @ 1.., ..}) = it.current { 
         end_pos
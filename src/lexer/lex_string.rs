@@ -81,7 +81,7 @@ pub fn lex_string(it: &mut TokenIterator, start : State) -> Token {
             col: start.col,
             num: start.num,
             ty: TokenType::String {
-                value: StringValue::SingleLine(start_line.slice(start.num + quote_count..last_pos - quote_count)),
+                value: StringValue::SingleLine(start_line.slice(post_start.num + quote_count..last_pos - suffix_len - quote_count)),
                 suffix_len,
                 prefix_len,
                 errors: StringErrorFlags::empty(),
@@ -98,7 +98,7 @@ pub fn lex_string(it: &mut TokenIterator, start : State) -> Token {
             col: start.col,
             num: start.num,
             ty: TokenType::String {
-                value: StringValue::SingleLine(start_line.slice(start.num + quote_count..)),
+                value: StringValue::SingleLine(start_line.slice(post_start.num + quote_count..)),
                 suffix_len: 0,
                 prefix_len,
                 errors: StringErrorFlags::UNCLOSED,
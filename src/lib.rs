@@ -3,8 +3,20 @@ extern crate core;
 use std::rc::Rc;
 use imstr::string::ImString;
 
+pub mod attributes;
+pub mod coverage;
+pub mod generator;
+pub mod infer;
 pub mod lexer;
+pub mod loader;
+pub mod macros;
+pub mod numeric;
 pub mod parser;
+pub mod prelude;
+pub mod printer;
+pub mod resolve;
+pub mod syntax;
+pub mod visit;
 
 type ImStrData = Rc<String>;
 type ImStr = ImString<ImStrData>;
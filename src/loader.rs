@@ -0,0 +1,154 @@
+//! Loading of packages laid out in several files.
+//!
+//! A module is either a file `name.rolang` or a directory `name/` with the entry file `.rolang`.
+//! `mod name` in a module file declares the module found next to that file,
+//! so directory modules hold their submodules and sibling files declare each other
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{macros, ImStr};
+use crate::lexer::TokenIterator;
+use crate::parser::{parse_with_diagnostics, Ast, Diagnostic, Span, Stmt};
+
+/// Entry file of a directory module
+pub const ENTRY_FILE: &str = ".rolang";
+pub const EXTENSION: &str = "rolang";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub name: ImStr,
+    pub file: PathBuf,
+    pub ast: Ast,
+    /// Macro expansion and parse diagnostics of `file`
+    pub diagnostics: Vec<Diagnostic>,
+    /// Modules declared with `mod`, in declaration order
+    pub children: Vec<Module>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadError {
+    pub kind: LoadErrorKind,
+    /// File of the `mod` declaration, the missing entry file for the package itself
+    pub file: PathBuf,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadErrorKind {
+    /// None of `candidates` exists
+    MissingFile { candidates: Vec<PathBuf> },
+    /// Both a file and a directory module exist
+    AmbiguousFile { candidates: Vec<PathBuf> },
+    /// Module declared while it is being loaded, `cycle` lists the files from that module to the declaring one
+    CyclicMod { cycle: Vec<PathBuf> },
+    /// The file exists but can not be read
+    Io(String),
+}
+
+impl Display for LoadErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let paths = |paths: &[PathBuf]| paths.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ");
+        match self {
+            LoadErrorKind::MissingFile { candidates } => write!(f, "module file not found, tried {}", paths(candidates)),
+            LoadErrorKind::AmbiguousFile { candidates } => write!(f, "module is defined by several files: {}", paths(candidates)),
+            LoadErrorKind::CyclicMod { cycle } => write!(f, "module declares itself through {}", paths(cycle)),
+            LoadErrorKind::Io(error) => write!(f, "can not read module: {error}"),
+        }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}: error: {}", self.file.display(), self.span.row + 1, self.span.num, self.kind)
+    }
+}
+
+/// Loads the package in directory `dir` starting from its entry file,
+/// modules which fail to load are left out and reported
+pub fn load_package(dir: &Path) -> (Option<Module>, Vec<LoadError>) {
+    let mut loader = Loader { stack: vec![], errors: vec![] };
+    let name = dir.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned());
+    let file = dir.join(ENTRY_FILE);
+    let module = match file.is_file() {
+        true => loader.load(ImStr::from(name), file.clone(), Span::default(), &file),
+        false => {
+            let kind = LoadErrorKind::MissingFile { candidates: vec![file.clone()] };
+            loader.errors.push(LoadError { kind, file, span: Span::default() });
+            None
+        }
+    };
+    (module, loader.errors)
+}
+
+/// Files a module declared in `file` can be loaded from
+pub fn module_candidates(file: &Path, name: &str) -> [PathBuf; 2] {
+    let dir = file.parent().unwrap_or(Path::new(""));
+    [dir.join(format!("{name}.{EXTENSION}")), dir.join(name).join(ENTRY_FILE)]
+}
+
+/// Expands the macros of a module file and parses it
+pub fn parse_source(source: &str) -> (Ast, Vec<Diagnostic>) {
+    let tokens: Vec<_> = TokenIterator::new(source).collect();
+    let expanded = macros::expand(&tokens);
+    let (mut ast, mut diagnostics) = parse_with_diagnostics(&expanded.tokens);
+    ast.definition_site = expanded.definition_site;
+    diagnostics.extend(expanded.diagnostics);
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span.row, diagnostic.span.num));
+    (ast, diagnostics)
+}
+
+struct Loader {
+    /// Files of the modules being loaded
+    stack: Vec<PathBuf>,
+    errors: Vec<LoadError>,
+}
+
+impl Loader {
+    /// Loads `file` declared at `span` of `parent`
+    fn load(&mut self, name: ImStr, file: PathBuf, span: Span, parent: &Path) -> Option<Module> {
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                let kind = LoadErrorKind::Io(error.to_string());
+                self.errors.push(LoadError { kind, file: parent.to_path_buf(), span });
+                return None;
+            }
+        };
+        let (ast, diagnostics) = parse_source(&source);
+
+        self.stack.push(file.clone());
+        let mut children = vec![];
+        for stmt in &ast.stmts {
+            let Stmt::Mod(decl) = stmt else { continue };
+            if let Some(child) = self.load_declared(&file, &decl.name.name, decl.span) {
+                children.push(child);
+            }
+        }
+        self.stack.pop();
+        Some(Module { name, file, ast, diagnostics, children })
+    }
+
+    /// Loads module `name` declared at `span` of `file`
+    fn load_declared(&mut self, file: &Path, name: &ImStr, span: Span) -> Option<Module> {
+        let candidates = module_candidates(file, name);
+        let found: Vec<_> = candidates.iter().filter(|candidate| candidate.is_file()).cloned().collect();
+        let error = |kind| LoadError { kind, file: file.to_path_buf(), span };
+        let child = match &found[..] {
+            [child] => child.clone(),
+            [] => {
+                self.errors.push(error(LoadErrorKind::MissingFile { candidates: candidates.to_vec() }));
+                return None;
+            }
+            _ => {
+                self.errors.push(error(LoadErrorKind::AmbiguousFile { candidates: found }));
+                return None;
+            }
+        };
+        if let Some(pos) = self.stack.iter().position(|loading| *loading == child) {
+            self.errors.push(error(LoadErrorKind::CyclicMod { cycle: self.stack[pos..].to_vec() }));
+            return None;
+        }
+        self.load(name.clone(), child, span, file)
+    }
+}
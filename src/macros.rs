@@ -0,0 +1,572 @@
+//! Token-tree macros.
+//!
+//! `macro name:` followed by indented rules `(pattern) => template` defines a macro of the whole file,
+//! `#name(args...)` with nothing between `#`, the name and the bracket calls it, so it doesn't clash
+//! with attributes. `$var` in a pattern matches one token tree, a macro call counting as one, and
+//! `$var ...` any number of them, other tokens match themselves. The call is replaced by the template of the first matching rule
+//! with the variables substituted. A `{...}` template is spliced without its braces to give statements
+//! or declarations, other brackets are kept so that the expansion stays a single operand.
+//!
+//! Macros are expanded on tokens before parsing. Expansion is hygienic: names bound by the template itself,
+//! `'name` binders and `name :=` definitions, are given names used nowhere else in every expansion, so they
+//! neither capture names of the arguments nor clash with the call site. The other names of the template
+//! are resolved where the macro is defined, at module level, so definitions at the call site don't capture
+//! them either. Template tokens keep their positions in the definition and argument tokens their positions
+//! at the call site
+
+use std::collections::{HashMap, HashSet};
+use crate::ImStr;
+use crate::lexer::{Token, TokenType};
+use crate::parser::{Diagnostic, Ident, ParseErrorKind, Severity, Span};
+
+/// Depth of nested expansions at which a macro is taken to expand forever
+pub const RECURSION_LIMIT: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct Expanded {
+    /// Tokens without macro definitions, calls replaced by their expansions
+    pub tokens: Vec<Token>,
+    /// Expansion that made each of `tokens`, `None` for tokens of the source
+    pub origins: Vec<Option<usize>>,
+    pub expansions: Vec<Expansion>,
+    /// Names of templates which the template doesn't bind, to be resolved at module level
+    pub definition_site: HashSet<Span>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub name: ImStr,
+    /// `#name(args...)`
+    pub call: Span,
+    /// `macro name` of the expanded macro
+    pub definition: Span,
+    /// Expansion whose template held the call
+    pub parent: Option<usize>,
+}
+
+impl Expanded {
+    /// Call sites of `expansion` and of the expansions it is nested in, innermost first
+    pub fn backtrace(&self, expansion: usize) -> Vec<Span> {
+        backtrace(&self.expansions, Some(expansion))
+    }
+
+    /// Backtrace of the first expanded token at `span`, empty if `span` is in the source
+    pub fn backtrace_at(&self, span: Span) -> Vec<Span> {
+        let origin = self.tokens.iter().zip(&self.origins)
+            .find(|(token, origin)| origin.is_some() && token.row == span.row && token.num == span.num)
+            .and_then(|(_, origin)| *origin);
+        backtrace(&self.expansions, origin)
+    }
+}
+
+fn backtrace(expansions: &[Expansion], mut next: Option<usize>) -> Vec<Span> {
+    let mut spans = vec![];
+    while let Some(index) = next {
+        spans.push(expansions[index].call);
+        next = expansions[index].parent;
+    }
+    spans
+}
+
+/// Collects the macro definitions of `tokens` and expands every call
+pub fn expand(tokens: &[Token]) -> Expanded {
+    let names = tokens.iter()
+        .filter_map(|token| match &token.ty {
+            TokenType::Symbol(name) | TokenType::Argument(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut expander = Expander {
+        macros: HashMap::new(),
+        expansions: vec![],
+        names,
+        definition_site: HashSet::new(),
+        diagnostics: vec![],
+    };
+    let source = expander.collect_definitions(tokens);
+    let mut out = vec![];
+    expander.expand_into(&source, None, 0, &mut out);
+    let (tokens, origins) = out.into_iter().unzip();
+    let mut diagnostics = expander.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span.row, diagnostic.span.num));
+    Expanded { tokens, origins, expansions: expander.expansions, definition_site: expander.definition_site, diagnostics }
+}
+
+#[derive(Debug, Clone)]
+struct Macro {
+    name: Ident,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    matchers: Vec<Matcher>,
+    /// Template tokens, `{}` braces already taken off
+    template: Vec<Token>,
+    /// Names the template binds, renamed in every expansion
+    bound: HashSet<ImStr>,
+    /// `$name ...` variables, whose `...` is dropped from the template as well
+    repeats: HashSet<ImStr>,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Token(Token),
+    /// `$name`
+    Var(ImStr),
+    /// `$name ...`
+    Repeat(ImStr),
+    Group { open: ImStr, matchers: Vec<Matcher> },
+}
+
+/// Token or brackets with the trees between them
+#[derive(Debug, Clone)]
+enum Tree {
+    Token(Token),
+    Group { open: Token, trees: Vec<Tree>, close: Token },
+    /// `#name(args...)` in the arguments of a call, expanded after substitution
+    Call(Vec<Tree>),
+}
+
+impl Tree {
+    fn flatten(&self, out: &mut Vec<Token>) {
+        match self {
+            Tree::Token(token) => out.push(token.clone()),
+            Tree::Call(trees) => trees.iter().for_each(|tree| tree.flatten(out)),
+            Tree::Group { open, trees, close } => {
+                out.push(open.clone());
+                trees.iter().for_each(|tree| tree.flatten(out));
+                out.push(close.clone());
+            }
+        }
+    }
+}
+
+/// Trees of `tokens`, `None` if brackets are unbalanced
+fn trees(tokens: &[Token]) -> Option<Vec<Tree>> {
+    let mut stack: Vec<(Token, Vec<Tree>)> = vec![];
+    let mut trees = vec![];
+    for token in tokens {
+        match token.ty {
+            TokenType::Open => stack.push((token.clone(), std::mem::take(&mut trees))),
+            TokenType::Close => {
+                let (open, outer) = stack.pop()?;
+                let group = Tree::Group { open, trees: std::mem::replace(&mut trees, outer), close: token.clone() };
+                trees.push(group);
+            }
+            _ => trees.push(Tree::Token(token.clone())),
+        }
+    }
+    stack.is_empty().then_some(trees)
+}
+
+/// `trees` with each macro call made a single tree
+fn group_calls(trees: Vec<Tree>) -> Vec<Tree> {
+    let mut out: Vec<Tree> = vec![];
+    for tree in trees {
+        let tree = match tree {
+            Tree::Group { open, trees, close } => Tree::Group { open, trees: group_calls(trees), close },
+            tree => tree,
+        };
+        if let Tree::Group { open, .. } = &tree
+            && let [.., Tree::Token(sharp), Tree::Token(name)] = &out[..]
+            && matches!(sharp.ty, TokenType::Sharp) && matches!(name.ty, TokenType::Symbol(_))
+            && adjacent(sharp, name) && adjacent(name, open)
+        {
+            let call = out.split_off(out.len() - 2);
+            out.push(Tree::Call(call.into_iter().chain([tree]).collect()));
+            continue;
+        }
+        out.push(tree);
+    }
+    out
+}
+
+/// Index of the bracket closing the one at `open`
+fn matching_close(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token.ty {
+            TokenType::Open => depth += 1,
+            TokenType::Close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn width(indent: &Token) -> usize {
+    indent.slice.chars().count()
+}
+
+/// Whether the line starting at `indent` holds nothing but comments
+fn blank_line(tokens: &[Token], indent: usize) -> bool {
+    tokens[indent + 1..].iter().find(|token| !matches!(token.ty, TokenType::Comment))
+        .is_none_or(|token| matches!(token.ty, TokenType::Indent))
+}
+
+/// Whether `b` follows `a` with nothing between them
+fn adjacent(a: &Token, b: &Token) -> bool {
+    a.row == b.row && a.num + a.slice.len() == b.num
+}
+
+fn is_op(token: &Token, op: &str) -> bool {
+    matches!(token.ty, TokenType::Operator) && token.slice == op
+}
+
+fn is_layout(token: &Token) -> bool {
+    matches!(token.ty, TokenType::Indent | TokenType::Comment)
+}
+
+/// Whether a pattern token matches an argument token, renamed names are compared by their new names
+fn same_token(a: &Token, b: &Token) -> bool {
+    match (&a.ty, &b.ty) {
+        (TokenType::Symbol(a), TokenType::Symbol(b)) | (TokenType::Argument(a), TokenType::Argument(b)) => a == b,
+        (a_ty, b_ty) => std::mem::discriminant(a_ty) == std::mem::discriminant(b_ty) && a.slice == b.slice,
+    }
+}
+
+fn var_name(tokens: &[Token], i: usize) -> Option<&ImStr> {
+    match tokens.get(i + 1) {
+        Some(Token { ty: TokenType::Symbol(name), .. }) if is_op(&tokens[i], "$") => Some(name),
+        _ => None,
+    }
+}
+
+struct Expander {
+    macros: HashMap<ImStr, Macro>,
+    expansions: Vec<Expansion>,
+    /// Names of the source, which renamed names must differ from
+    names: HashSet<ImStr>,
+    definition_site: HashSet<Span>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Expander {
+    fn error(&mut self, kind: ParseErrorKind, span: Span) {
+        self.diagnostics.push(Diagnostic { severity: Severity::Error, kind, span });
+    }
+
+    /// `name_id`, or `name_id_2`... if the source has that name already
+    fn fresh(&mut self, name: &ImStr, id: usize) -> ImStr {
+        let mut fresh = ImStr::from(format!("{name}_{id}"));
+        let mut count = 1;
+        while self.names.contains(&fresh) {
+            count += 1;
+            fresh = ImStr::from(format!("{name}_{id}_{count}"));
+        }
+        self.names.insert(fresh.clone());
+        fresh
+    }
+
+    /// Takes the definitions out of `tokens`, returning the rest
+    fn collect_definitions(&mut self, tokens: &[Token]) -> Vec<Token> {
+        let mut rest = vec![];
+        let mut depth = 0usize;
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            match token.ty {
+                TokenType::Open => depth += 1,
+                TokenType::Close => depth = depth.saturating_sub(1),
+                TokenType::Indent if depth == 0 => {
+                    if let Some(end) = self.definition(tokens, i) {
+                        i = end;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            rest.push(token.clone());
+            i += 1;
+        }
+        rest
+    }
+
+    /// Parses the definition on the line starting at `line`, returning the index after it
+    fn definition(&mut self, tokens: &[Token], line: usize) -> Option<usize> {
+        let [indent, keyword, Token { ty: TokenType::Symbol(name), .. }, colon, next, ..] = &tokens[line..] else {
+            return None;
+        };
+        if !matches!(&keyword.ty, TokenType::Symbol(keyword) if keyword == "macro") || !is_op(colon, ":") {
+            return None;
+        }
+        // rules are on the following lines, indented deeper
+        let body_indent = matches!(next.ty, TokenType::Indent).then(|| width(next)).filter(|&body| body > width(indent))?;
+
+        let mut end = line + 4;
+        let mut depth = 0usize;
+        let mut rules = vec![];
+        let mut rule_tokens = vec![];
+        while let Some(token) = tokens.get(end) {
+            if depth == 0 && matches!(token.ty, TokenType::Indent) && !blank_line(tokens, end) && width(token) <= body_indent {
+                self.flush_rule(&mut rule_tokens, &mut rules);
+                if width(token) < body_indent {
+                    break;
+                }
+            }
+            match token.ty {
+                TokenType::Open => depth += 1,
+                TokenType::Close => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            if depth > 0 || !is_layout(token) {
+                rule_tokens.push(token.clone());
+            }
+            end += 1;
+        }
+        self.flush_rule(&mut rule_tokens, &mut rules);
+
+        let ident = Ident { name: name.clone(), span: Span::of(&tokens[line + 2]) };
+        let span = Span::of(keyword).to(ident.span);
+        self.macros.insert(name.clone(), Macro { name: Ident { span, ..ident }, rules });
+        Some(end)
+    }
+
+    fn flush_rule(&mut self, tokens: &mut Vec<Token>, rules: &mut Vec<Rule>) {
+        if !tokens.is_empty() {
+            rules.extend(self.rule(tokens));
+            tokens.clear();
+        }
+    }
+
+    /// Parses `(pattern) => template...`
+    fn rule(&mut self, tokens: &[Token]) -> Option<Rule> {
+        let span = Span::of(&tokens[0]).to(Span::of(&tokens[tokens.len() - 1]));
+        let trees = trees(tokens);
+        let Some([Tree::Group { trees: pattern, .. }, Tree::Token(arrow), template @ ..]) = trees.as_deref() else {
+            self.error(ParseErrorKind::ExpectedMacroRule, span);
+            return None;
+        };
+        if !is_op(arrow, "=>") || template.is_empty() {
+            self.error(ParseErrorKind::ExpectedMacroRule, span);
+            return None;
+        }
+
+        let mut vars = HashMap::new();
+        let matchers = self.matchers(pattern, &mut vars)?;
+        let mut tokens = vec![];
+        match template {
+            [Tree::Group { open, trees, .. }] if open.slice == "{" => trees.iter().for_each(|tree| tree.flatten(&mut tokens)),
+            trees => trees.iter().for_each(|tree| tree.flatten(&mut tokens)),
+        }
+        let template = tokens;
+
+        let mut bound = HashSet::new();
+        let mut unknown = false;
+        for (i, token) in template.iter().enumerate() {
+            if let Some(name) = var_name(&template, i) {
+                if !vars.contains_key(name) {
+                    self.error(ParseErrorKind::UnknownMacroVar { name: name.clone() }, Span::of(token).to(Span::of(&template[i + 1])));
+                    unknown = true;
+                }
+                continue;
+            }
+            let after_dollar = i > 0 && var_name(&template, i - 1).is_some();
+            match &token.ty {
+                TokenType::Argument(name) => {
+                    bound.insert(name.clone());
+                }
+                TokenType::Symbol(name) if !after_dollar && template.get(i + 1).is_some_and(|next| is_op(next, ":=")) => {
+                    bound.insert(name.clone());
+                }
+                _ => {}
+            }
+        }
+        for (i, token) in template.iter().enumerate() {
+            let after_dollar = i > 0 && var_name(&template, i - 1).is_some();
+            if let TokenType::Symbol(name) | TokenType::Argument(name) = &token.ty && !after_dollar && !bound.contains(name) {
+                self.definition_site.insert(Span::of(token));
+            }
+        }
+        // a rule using variables it doesn't bind is left out
+        if unknown {
+            return None;
+        }
+        let repeats = vars.into_iter().filter(|&(_, repeat)| repeat).map(|(name, _)| name).collect();
+        Some(Rule { matchers, template, bound, repeats })
+    }
+
+    /// Matchers of the pattern `trees`, recording the variables in `vars` with whether they repeat
+    fn matchers(&mut self, trees: &[Tree], vars: &mut HashMap<ImStr, bool>) -> Option<Vec<Matcher>> {
+        let mut matchers = vec![];
+        let mut i = 0;
+        while i < trees.len() {
+            match &trees[i] {
+                Tree::Token(token) if is_layout(token) => {}
+                Tree::Token(token) if is_op(token, "$") => {
+                    let Some(Tree::Token(Token { ty: TokenType::Symbol(name), .. })) = trees.get(i + 1) else {
+                        self.error(ParseErrorKind::ExpectedMacroRule, Span::of(token));
+                        return None;
+                    };
+                    let repeat = matches!(trees.get(i + 2), Some(Tree::Token(token)) if is_op(token, "..."));
+                    vars.insert(name.clone(), repeat);
+                    matchers.push(if repeat { Matcher::Repeat(name.clone()) } else { Matcher::Var(name.clone()) });
+                    i += if repeat { 2 } else { 1 };
+                }
+                Tree::Token(token) => matchers.push(Matcher::Token(token.clone())),
+                Tree::Call(_) => unreachable!("calls are only grouped in arguments"),
+                Tree::Group { open, trees, .. } => {
+                    let inner = self.matchers(trees, vars)?;
+                    matchers.push(Matcher::Group { open: open.slice.clone(), matchers: inner });
+                }
+            }
+            i += 1;
+        }
+        Some(matchers)
+    }
+
+    /// Appends `tokens` to `out` with every call expanded, tokens made by expansion `origin`
+    fn expand_into(&mut self, tokens: &[Token], origin: Option<usize>, depth: usize, out: &mut Vec<(Token, Option<usize>)>) {
+        let mut i = 0;
+        while i < tokens.len() {
+            let Some((close, name)) = call_at(tokens, i) else {
+                out.push((tokens[i].clone(), origin));
+                i += 1;
+                continue;
+            };
+            match self.expansion(tokens, i, close, &name, origin, depth) {
+                Some((id, instance)) => self.expand_into(&instance, Some(id), depth + 1, out),
+                None => {
+                    // `()` in place of the call keeps the parser on track
+                    let unit = |token: &Token, slice: &str, ty| Token { slice: slice.into(), ty, ..token.clone() };
+                    out.push((unit(&tokens[i], "(", TokenType::Open), origin));
+                    out.push((unit(&tokens[close], ")", TokenType::Close), origin));
+                }
+            }
+            i = close + 1;
+        }
+    }
+
+    /// Expands the call from `sharp` to `close`, giving the expansion index and the template instance
+    fn expansion(
+        &mut self,
+        tokens: &[Token],
+        sharp: usize,
+        close: usize,
+        name: &ImStr,
+        origin: Option<usize>,
+        depth: usize,
+    ) -> Option<(usize, Vec<Token>)> {
+        let call = Span::of(&tokens[sharp]).to(Span::of(&tokens[close]));
+        let backtrace = backtrace(&self.expansions, origin);
+        let Some(mac) = self.macros.get(name) else {
+            self.error(ParseErrorKind::UnknownMacro { name: name.clone(), backtrace }, call);
+            return None;
+        };
+        if depth >= RECURSION_LIMIT {
+            self.error(ParseErrorKind::MacroRecursion { name: name.clone(), backtrace }, call);
+            return None;
+        }
+        let args: Vec<_> = tokens[sharp + 3..close].iter().filter(|token| !is_layout(token)).cloned().collect();
+        let args = group_calls(trees(&args)?);
+        let args: Vec<_> = args.iter().collect();
+        let Some((rule, bindings)) = mac.rules.iter().find_map(|rule| {
+            let mut bindings = HashMap::new();
+            match_trees(&rule.matchers, &args, &mut bindings).then_some((rule, bindings))
+        }) else {
+            self.error(ParseErrorKind::NoMacroRule { name: name.clone(), backtrace }, call);
+            return None;
+        };
+
+        let id = self.expansions.len();
+        let line_indent = tokens[..sharp].iter().rev().find(|token| matches!(token.ty, TokenType::Indent)).map_or(0, width);
+        let (rule, definition) = (rule.clone(), mac.name.span);
+        let renames = rule.bound.iter().map(|name| (name.clone(), self.fresh(name, id))).collect();
+        let instance = instantiate(&rule, &bindings, &renames, line_indent);
+        self.expansions.push(Expansion { name: name.clone(), call, definition, parent: origin });
+        Some((id, instance))
+    }
+}
+
+/// Closing bracket and name of the call `#name(...)` at `i`
+fn call_at(tokens: &[Token], i: usize) -> Option<(usize, ImStr)> {
+    let [sharp, name, open, ..] = &tokens[i..] else { return None };
+    let TokenType::Symbol(ident) = &name.ty else { return None };
+    if !matches!(sharp.ty, TokenType::Sharp) || !matches!(open.ty, TokenType::Open) || !adjacent(sharp, name) || !adjacent(name, open) {
+        return None;
+    }
+    Some((matching_close(tokens, i + 2)?, ident.clone()))
+}
+
+fn match_trees(matchers: &[Matcher], trees: &[&Tree], bindings: &mut HashMap<ImStr, Vec<Token>>) -> bool {
+    let Some((first, rest)) = matchers.split_first() else { return trees.is_empty() };
+    if let Matcher::Repeat(name) = first {
+        return (0..=trees.len()).any(|count| {
+            let mut attempt = bindings.clone();
+            let mut tokens = vec![];
+            trees[..count].iter().for_each(|tree| tree.flatten(&mut tokens));
+            attempt.insert(name.clone(), tokens);
+            let matched = match_trees(rest, &trees[count..], &mut attempt);
+            if matched {
+                *bindings = attempt;
+            }
+            matched
+        });
+    }
+    let Some((tree, trees)) = trees.split_first() else { return false };
+    let matched = match (first, tree) {
+        (Matcher::Token(expected), Tree::Token(token)) => same_token(expected, token),
+        (Matcher::Var(name), tree) => {
+            let mut tokens = vec![];
+            tree.flatten(&mut tokens);
+            bindings.insert(name.clone(), tokens);
+            true
+        }
+        (Matcher::Group { open: expected, matchers }, Tree::Group { open, trees, .. }) => {
+            *expected == open.slice && match_trees(matchers, &trees.iter().collect::<Vec<_>>(), bindings)
+        }
+        _ => false,
+    };
+    matched && match_trees(rest, trees, bindings)
+}
+
+/// Template of `rule` with variables substituted and bound names renamed by `renames`. Lines of the
+/// template are indented relative to `line_indent`, the indentation of the call line
+fn instantiate(
+    rule: &Rule,
+    bindings: &HashMap<ImStr, Vec<Token>>,
+    renames: &HashMap<ImStr, ImStr>,
+    line_indent: usize,
+) -> Vec<Token> {
+    let template = &rule.template;
+    let min_indent = template.iter().enumerate()
+        .filter(|(i, token)| matches!(token.ty, TokenType::Indent) && i + 1 < template.len())
+        .map(|(_, token)| width(token))
+        .min()
+        .unwrap_or(0);
+
+    let mut out = vec![];
+    let mut i = 0;
+    while i < template.len() {
+        let token = &template[i];
+        if let Some(name) = var_name(template, i) {
+            out.extend(bindings[name].iter().cloned());
+            i += 2;
+            if template.get(i).is_some_and(|token| is_op(token, "...")) && rule.repeats.contains(name) {
+                i += 1;
+            }
+            continue;
+        }
+        let ty = match &token.ty {
+            TokenType::Symbol(name) if rule.bound.contains(name) => TokenType::Symbol(renames[name].clone()),
+            TokenType::Argument(name) if rule.bound.contains(name) => TokenType::Argument(renames[name].clone()),
+            TokenType::Indent => {
+                let indent = " ".repeat(line_indent + width(token).saturating_sub(min_indent));
+                out.push(Token { slice: ImStr::from(indent), ..token.clone() });
+                i += 1;
+                continue;
+            }
+            ty => ty.clone(),
+        };
+        out.push(Token { ty, ..token.clone() });
+        i += 1;
+    }
+    out
+}
@@ -0,0 +1,215 @@
+//! Number types and their arithmetic.
+//!
+//! Integers are `s8`..`s128`, `u8`..`u128` and `int`, which is unbounded, floats are `f32` and `f64`.
+//! A literal takes the type of its suffix, or the one its uses give it, `int` or `f64` if none do.
+//! `+`, `-` and `*` are to fail on overflow in debug builds and wrap otherwise, `+%`, `-%` and `*%` always
+//! wrap and `+|`, `-|` and `*|` saturate at the bounds of the type.
+//!
+//! Only overflow known at compile time is checked: the type checker folds the arithmetic on literals with
+//! [`arithmetic`] and reports the operations which overflow or divide by zero. Checking overflow at run
+//! time in debug builds isn't done here, it's left to the backend lowering arithmetic, which doesn't
+//! exist yet. It's to run each operation as [`arithmetic_operator`] gives it for the build
+
+use std::fmt::{Display, Formatter};
+use num_bigint::BigInt;
+use crate::parser::NumberLiteral;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NumberType {
+    /// Unbounded integer
+    Int,
+    Signed(u32),
+    Unsigned(u32),
+    Float(u32),
+}
+
+impl NumberType {
+    /// Number type named `name`, `u32` or `int`
+    pub fn from_name(name: &str) -> Option<Self> {
+        if name == "int" {
+            return Some(NumberType::Int);
+        }
+        let (kind, bits) = name.split_at_checked(1)?;
+        let bits: u32 = bits.parse().ok().filter(|_| !bits.starts_with('0'))?;
+        match kind {
+            "s" if matches!(bits, 8 | 16 | 32 | 64 | 128) => Some(NumberType::Signed(bits)),
+            "u" if matches!(bits, 8 | 16 | 32 | 64 | 128) => Some(NumberType::Unsigned(bits)),
+            "f" if matches!(bits, 32 | 64) => Some(NumberType::Float(bits)),
+            _ => None,
+        }
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, NumberType::Float(_))
+    }
+
+    /// Smallest and largest values of a bounded integer type
+    pub fn bounds(self) -> Option<(BigInt, BigInt)> {
+        let one = BigInt::from(1);
+        match self {
+            NumberType::Signed(bits) => Some((-(&one << (bits - 1)), (&one << (bits - 1)) - 1)),
+            NumberType::Unsigned(bits) => Some((BigInt::ZERO, (&one << bits) - 1)),
+            NumberType::Int | NumberType::Float(_) => None,
+        }
+    }
+
+    /// Whether `value` is one of the values of the type, floats fit if they are finite once rounded to it
+    pub fn fits(self, value: &Number) -> bool {
+        match (self, value) {
+            (NumberType::Float(32), value) => (value.to_f64() as f32).is_finite(),
+            (NumberType::Float(_), value) => value.to_f64().is_finite(),
+            (_, Number::Float(_)) => false,
+            (ty, Number::Int(value)) => ty.bounds().is_none_or(|(min, max)| min <= *value && *value <= max),
+        }
+    }
+}
+
+impl Display for NumberType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberType::Int => write!(f, "int"),
+            NumberType::Signed(bits) => write!(f, "s{bits}"),
+            NumberType::Unsigned(bits) => write!(f, "u{bits}"),
+            NumberType::Float(bits) => write!(f, "f{bits}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Int(BigInt),
+    Float(f64),
+}
+
+impl Number {
+    /// Value written by `literal`, negated if `negative`
+    pub fn of_literal(literal: &NumberLiteral, negative: bool) -> Option<Self> {
+        let digits: String = literal.digits().chars().filter(|&char| char != crate::lexer::NUMBER_DELIMITER).collect();
+        let value = match literal.is_float() {
+            true => Number::Float(digits.parse().ok()?),
+            false => Number::Int(BigInt::parse_bytes(digits.as_bytes(), literal.radix())?),
+        };
+        Some(if negative { value.negate() } else { value })
+    }
+
+    fn negate(self) -> Self {
+        match self {
+            Number::Int(value) => Number::Int(-value),
+            Number::Float(value) => Number::Float(-value),
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        match self {
+            Number::Int(value) => value.to_string().parse().unwrap_or(f64::INFINITY),
+            Number::Float(value) => *value,
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Int(value) => write!(f, "{value}"),
+            Number::Float(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// What an arithmetic operator does with a result out of the bounds of its type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Fails, `+`, `-` and `*` in debug builds
+    Checked,
+    Wrapping,
+    Saturating,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// Operation of an arithmetic operator and what it does on overflow, `None` for other operators
+pub fn arithmetic_operator(op: &str, debug: bool) -> Option<(ArithmeticOp, Overflow)> {
+    let default = if debug { Overflow::Checked } else { Overflow::Wrapping };
+    let (op, overflow) = match op.split_at_checked(1)? {
+        (op, "") => (op, default),
+        (op, "%") => (op, Overflow::Wrapping),
+        (op, "|") => (op, Overflow::Saturating),
+        _ => return None,
+    };
+    let op = match op {
+        "+" => ArithmeticOp::Add,
+        "-" => ArithmeticOp::Sub,
+        "*" => ArithmeticOp::Mul,
+        "/" if overflow == default => ArithmeticOp::Div,
+        "%" if overflow == default => ArithmeticOp::Rem,
+        _ => return None,
+    };
+    Some((op, overflow))
+}
+
+/// Whether `op` is one of the wrapping or saturating operators, which only integers have
+pub fn is_bounded_operator(op: &str) -> bool {
+    arithmetic_operator(op, true).is_some_and(|(_, overflow)| overflow != Overflow::Checked)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArithmeticError {
+    /// Result of a checked operation out of the bounds of its type
+    Overflow { ty: NumberType, result: BigInt },
+    DivisionByZero,
+}
+
+impl Display for ArithmeticError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArithmeticError::Overflow { ty, result } => write!(f, "`{result}` overflows `{ty}`"),
+            ArithmeticError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+/// Result of `a op b` for values of type `ty`. Integer division rounds toward zero
+pub fn arithmetic(op: ArithmeticOp, overflow: Overflow, ty: NumberType, a: &Number, b: &Number) -> Result<Number, ArithmeticError> {
+    let (a, b) = match (a, b) {
+        (Number::Int(a), Number::Int(b)) if !ty.is_float() => (a, b),
+        (a, b) => {
+            let (a, b) = (a.to_f64(), b.to_f64());
+            let result = match op {
+                ArithmeticOp::Add => a + b,
+                ArithmeticOp::Sub => a - b,
+                ArithmeticOp::Mul => a * b,
+                ArithmeticOp::Div => a / b,
+                ArithmeticOp::Rem => a % b,
+            };
+            return Ok(Number::Float(if ty == NumberType::Float(32) { result as f32 as f64 } else { result }));
+        }
+    };
+    let result = match op {
+        ArithmeticOp::Add => a + b,
+        ArithmeticOp::Sub => a - b,
+        ArithmeticOp::Mul => a * b,
+        ArithmeticOp::Div | ArithmeticOp::Rem if *b == BigInt::ZERO => return Err(ArithmeticError::DivisionByZero),
+        ArithmeticOp::Div => a / b,
+        ArithmeticOp::Rem => a % b,
+    };
+    let Some((min, max)) = ty.bounds() else { return Ok(Number::Int(result)) };
+    if min <= result && result <= max {
+        return Ok(Number::Int(result));
+    }
+    match overflow {
+        Overflow::Checked => Err(ArithmeticError::Overflow { ty, result }),
+        Overflow::Saturating => Ok(Number::Int(if result < min { min } else { max })),
+        Overflow::Wrapping => {
+            let modulus = &max - &min + 1;
+            let wrapped = ((result - &min) % &modulus + &modulus) % &modulus + min;
+            Ok(Number::Int(wrapped))
+        }
+    }
+}
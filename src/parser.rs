@@ -1,6 +1,797 @@
-mod parser;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use crate::ImStr;
+use crate::syntax::SyntaxKind;
+use crate::lexer::{CharErrorFlags, NumberErrorFlags, StringErrorFlags, StringValue, Token, TokenType};
 
-#[allow(unused)]
-enum AST {
-    // todo
-}
\ No newline at end of file
+mod captures;
+mod parse;
+mod parse_attribute;
+mod parse_block;
+mod parse_decl;
+mod parse_expr;
+mod parse_module;
+mod parse_pattern;
+mod parse_range;
+mod parse_type;
+mod places;
+mod spans;
+mod yields;
+
+pub use self::parse::{parse, parse_with_diagnostics};
+pub(crate) use self::parse::parse_events;
+pub(crate) use self::parse_expr::{operator_precedence, Assoc, CHAIN, NOT, OR, POW, RANGE};
+pub(crate) use self::yields::yields;
+
+/// Syntax tree event recorded while parsing, see `syntax::parse_source`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Event {
+    Start(SyntaxKind),
+    /// Index of the consumed token in the lexer output
+    Token(usize),
+    Finish,
+}
+
+/// Source range in token coordinates (see `Token`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    /// First line number (from 0 to n)
+    pub row: usize,
+    /// First byte number in `row`
+    pub num: usize,
+    /// Last line number
+    pub end_row: usize,
+    /// Byte number after the last byte in `end_row`
+    pub end_num: usize,
+}
+
+impl Span {
+    pub fn of(token: &Token) -> Self {
+        match &token.ty {
+            TokenType::String { value: StringValue::MultiLine { lines, last_line_slice }, .. } => Span {
+                row: token.row,
+                num: token.num,
+                end_row: lines.end,
+                end_num: last_line_slice.len(),
+            },
+            _ => Span {
+                row: token.row,
+                num: token.num,
+                end_row: token.row,
+                end_num: token.num + token.slice.len(),
+            },
+        }
+    }
+
+    /// Span from the start of `self` to the end of `end`
+    pub fn to(self, end: Span) -> Self {
+        Span { end_row: end.end_row, end_num: end.end_num, ..self }
+    }
+
+    /// Whether `other` lies within this span
+    pub fn contains(self, other: Span) -> bool {
+        (self.row, self.num) <= (other.row, other.num) && (other.end_row, other.end_num) <= (self.end_row, self.end_num)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ast {
+    pub stmts: Vec<Stmt>,
+    /// Names written in macro templates, which are resolved where the macro is defined
+    pub definition_site: HashSet<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    /// `[mut] name := value`
+    Let(Let),
+    /// `name params := [body]` followed by its `name params ::= body` clauses
+    Function(FunctionDef),
+    Mod(ModDecl),
+    Use(UseDecl),
+    /// `type` or `class` declaration
+    Type(TypeDecl),
+    Trait(TraitDecl),
+    Impl(ImplDecl),
+    /// `name params : Type`, a method without default body, only found in traits
+    Signature(Signature),
+    Expr(Expr),
+}
+
+impl Stmt {
+    /// Attributes of a declaration, expression statements keep theirs in `ExprKind::Annotated`
+    pub fn attrs(&self) -> &[Attribute] {
+        match self {
+            Stmt::Let(Let { attrs, .. })
+            | Stmt::Function(FunctionDef { attrs, .. })
+            | Stmt::Mod(ModDecl { attrs, .. })
+            | Stmt::Use(UseDecl { attrs, .. })
+            | Stmt::Type(TypeDecl { attrs, .. })
+            | Stmt::Trait(TraitDecl { attrs, .. })
+            | Stmt::Impl(ImplDecl { attrs, .. })
+            | Stmt::Signature(Signature { attrs, .. }) => attrs,
+            Stmt::Expr(_) => &[],
+        }
+    }
+
+    fn attrs_mut(&mut self) -> Option<&mut Vec<Attribute>> {
+        match self {
+            Stmt::Let(Let { attrs, .. })
+            | Stmt::Function(FunctionDef { attrs, .. })
+            | Stmt::Mod(ModDecl { attrs, .. })
+            | Stmt::Use(UseDecl { attrs, .. })
+            | Stmt::Type(TypeDecl { attrs, .. })
+            | Stmt::Trait(TraitDecl { attrs, .. })
+            | Stmt::Impl(ImplDecl { attrs, .. })
+            | Stmt::Signature(Signature { attrs, .. }) => Some(attrs),
+            Stmt::Expr(_) => None,
+        }
+    }
+}
+
+/// `pub`, `priv` or nothing before a declaration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Visibility {
+    /// Private, no modifier given
+    #[default]
+    Default,
+    Pub,
+    Priv,
+}
+
+/// `#name args...`, the arguments are literals
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: Ident,
+    pub args: Vec<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Let {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    pub mutable: bool,
+    /// `name : Type := value`
+    pub ty: Option<TypeExpr>,
+    pub value: Expr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDef {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    /// Signature parameters, also fixing the function arity
+    pub params: Vec<Pattern>,
+    /// Result type given by `name params : Type :=`
+    pub ty: Option<TypeExpr>,
+    /// Body given right after the signature, `None` for functions defined by clauses
+    pub body: Option<Expr>,
+    /// Clauses in source order, the first matching one is taken
+    pub clauses: Vec<Clause>,
+    /// Whether the body or a clause yields, a call then makes a lazy iterator of the yielded values
+    pub generator: bool,
+    /// Span of the signature
+    pub span: Span,
+}
+
+/// `mod name`, the module is loaded from another file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModDecl {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    pub span: Span,
+}
+
+/// `use tree`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UseDecl {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub tree: UseTree,
+    pub span: Span,
+}
+
+/// `a.b.c`, `a.b as c`, `a.*` or `a.(tree, ...)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UseTree {
+    /// Path before the imported item, glob or group, including the item itself
+    pub path: Vec<Ident>,
+    pub kind: UseKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UseKind {
+    /// The last path segment, optionally renamed with `as`
+    Single { rename: Option<Ident> },
+    /// `path.*`, everything visible in `path`
+    Glob,
+    /// `path.(tree, ...)`
+    Group(Vec<UseTree>),
+}
+
+/// `type Name 'params... := Type`, `type Name 'params...:` followed by constructors
+/// or `class Name 'params...:` followed by fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDecl {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    pub params: Vec<Ident>,
+    pub where_clause: Vec<Predicate>,
+    pub body: TypeBody,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeBody {
+    /// Another name of the type
+    Alias(TypeExpr),
+    /// Sum type, a value is built by one of the constructors
+    Variants(Vec<Variant>),
+    /// Record type declared with `class`
+    Record(Vec<RecordField>),
+}
+
+/// `Name fields...`, a constructor of a sum type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: Ident,
+    pub fields: Vec<TypeExpr>,
+    pub span: Span,
+}
+
+/// `name: Type` in a `class`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordField {
+    pub vis: Visibility,
+    pub name: Ident,
+    pub ty: TypeExpr,
+    pub span: Span,
+}
+
+/// `trait Name 'self 'params...:` followed by signatures and default definitions,
+/// the first parameter stands for the implementing type
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitDecl {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    pub params: Vec<Ident>,
+    pub where_clause: Vec<Predicate>,
+    pub items: Vec<Stmt>,
+    pub span: Span,
+}
+
+/// `trait Name args... for Type:` followed by definitions implementing the trait for `ty`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImplDecl {
+    pub attrs: Vec<Attribute>,
+    pub trait_ref: TraitRef,
+    pub ty: TypeExpr,
+    pub where_clause: Vec<Predicate>,
+    pub items: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    pub params: Vec<Pattern>,
+    pub ty: TypeExpr,
+    pub span: Span,
+}
+
+/// `Type is Trait + ...` in a `where` clause
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    pub ty: TypeExpr,
+    pub bounds: Vec<TraitRef>,
+    pub span: Span,
+}
+
+/// `Name args...`, a trait given the types after the implementing one
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitRef {
+    pub name: Ident,
+    pub args: Vec<TypeExpr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub params: Vec<Pattern>,
+    pub body: Expr,
+    pub span: Span,
+}
+
+/// Identifier or operator together with its position
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ident {
+    pub name: ImStr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Name(ImStr),
+    /// `'name`, refers to the binder of a guard
+    Argument(ImStr),
+    /// `_`
+    Hole,
+    Literal(Literal),
+    /// `()`
+    Unit,
+    /// `(first, name := second...)`, a single positional field needs a trailing comma
+    Tuple(Vec<Field>),
+    /// `[first, second...]`
+    List(Vec<Expr>),
+    /// `func args...`
+    Apply { func: Box<Expr>, args: Vec<Expr> },
+    /// `receiver.method`, `receiver .method args...` or a section `.method args...` without receiver
+    Method { receiver: Option<Box<Expr>>, method: Box<Expr>, args: Vec<Expr> },
+    Unary { op: Ident, operand: Box<Expr> },
+    Binary { op: Ident, lhs: Box<Expr>, rhs: Box<Expr> },
+    /// Indented block, its value is the value of the last statement
+    Block(Vec<Stmt>),
+    /// `iter for pattern: body`
+    For { iter: Box<Expr>, pattern: Box<Pattern>, body: Box<Expr> },
+    /// `while: cond then: body else: otherwise`, `otherwise` is the value after the loop ends
+    While { cond: Box<Expr>, body: Option<Box<Expr>>, otherwise: Option<Box<Expr>> },
+    /// `expr as Type`
+    Cast { expr: Box<Expr>, ty: TypeExpr },
+    /// `match scrutinee:` followed by indented arms, the first matching one is taken
+    Match { scrutinee: Box<Expr>, arms: Vec<Arm> },
+    /// `fn params...: body`, `captures` are the names used in the body which the lambda doesn't bind
+    Lambda { params: Vec<Pattern>, body: Box<Expr>, captures: Vec<Ident>, generator: bool },
+    /// `#attrs... expr`
+    Annotated { attrs: Vec<Attribute>, expr: Box<Expr> },
+    /// `yield value`, its own value is the one the generator is resumed with
+    Yield(Box<Expr>),
+    /// `place <- value`, `place op<- rhs` or `place .<- method args...`, whose value is the new one.
+    /// Updates are desugared, `value` is then `place op rhs` or `place .method args...`
+    Assign { place: Box<Expr>, op: Ident, value: Box<Expr> },
+    /// `start..end`, `start..=end`, `start..`, `..end` or `..`, a value of the standard `Range` type
+    Range { start: Option<Box<Expr>>, end: Option<Box<Expr>>, inclusive: bool },
+    /// Tokens skipped by error recovery
+    Error,
+    /// Expected but absent expression
+    Missing,
+}
+
+/// Tuple field, `name := value` or a positional `value`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: Option<Ident>,
+    pub value: Expr,
+}
+
+/// `pattern [if guard] => body`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind {
+    /// `_`
+    Wildcard,
+    /// `'name`, `'name = pattern` or `('name guard...)`
+    Bind { name: Ident, pattern: Option<Box<Pattern>>, guard: Option<Box<Expr>> },
+    /// `literal` or `-number`
+    Literal { value: Literal, negative: bool },
+    /// `Name args...`
+    Constructor { name: Ident, args: Vec<Pattern> },
+    /// `()`
+    Unit,
+    /// `(first, second...)`
+    Tuple(Vec<Pattern>),
+    /// `[first, second...]`
+    List(Vec<Pattern>),
+    /// `?'name` or `?'name = default`, matches a missing argument as well
+    Optional { name: Ident, default: Option<Box<Expr>> },
+    /// `start..end`, `start..=end`, `start..` or `..end` with literal bounds
+    Range { start: Option<Box<Pattern>>, end: Option<Box<Pattern>>, inclusive: bool },
+    /// Tokens skipped by error recovery
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeExpr {
+    pub kind: TypeExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExprKind {
+    /// `s32`, `Array`
+    Name(ImStr),
+    /// `'t`, a type parameter
+    Var(ImStr),
+    /// `_`, a type left to inference
+    Hole,
+    /// `first .Name rest...`, type application written postfix like a method call
+    Apply { name: Ident, args: Vec<TypeExpr> },
+    /// `()`
+    Unit,
+    /// `(first, name: second...)`, named fields come after positional ones
+    Tuple(Vec<TypeField>),
+    /// `param -> result`
+    Function { param: Box<TypeExpr>, result: Box<TypeExpr> },
+    /// `dyn Trait` or `Trait dyn`, a trait object
+    Dyn(Box<TypeExpr>),
+    /// Tokens skipped by error recovery
+    Error,
+}
+
+/// Tuple type field, `name: Type` or a positional `Type`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeField {
+    pub name: Option<Ident>,
+    pub ty: TypeExpr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(NumberLiteral),
+    String(StringLiteral),
+    Char(char),
+}
+
+/// String literal, `prefix"value"suffix`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringLiteral {
+    pub prefix: ImStr,
+    pub value: StringValue,
+    pub suffix: ImStr,
+}
+
+/// Number literal, positions are relative to `slice`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberLiteral {
+    pub slice: ImStr,
+    pub start_pos: usize,
+    pub dot_pos: Option<usize>,
+    pub exp_pos: Option<usize>,
+    pub suf_pos: Option<usize>,
+}
+
+impl NumberLiteral {
+    pub fn radix(&self) -> u32 {
+        match self.slice.get(..2) {
+            Some("0x") => 16,
+            Some("0o") => 8,
+            Some("0b") => 2,
+            _ => 10,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        self.dot_pos.is_some() || self.exp_pos.is_some() && self.radix() == 10
+    }
+
+    pub fn suffix(&self) -> Option<&str> {
+        self.suf_pos.map(|pos| &self.slice[pos..])
+    }
+
+    /// Digits without radix prefix and suffix
+    pub fn digits(&self) -> &str {
+        &self.slice[self.start_pos..self.suf_pos.unwrap_or(self.slice.len())]
+    }
+
+    /// Value of an integer literal, `None` for floats and literals too big for `u128`
+    pub fn int_value(&self) -> Option<u128> {
+        if self.is_float() {
+            return None;
+        }
+        let digits: String = self.digits().chars().filter(|&char| char != crate::lexer::NUMBER_DELIMITER).collect();
+        u128::from_str_radix(&digits, self.radix()).ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: ParseErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// Lexer `Error` token
+    InvalidToken,
+    InvalidNumber(NumberErrorFlags),
+    InvalidString(StringErrorFlags),
+    InvalidChar(CharErrorFlags),
+    UnexpectedToken,
+    /// Line indented less than the first line of the file
+    UnexpectedDedent,
+    ExpectedExpression,
+    ExpectedName,
+    ExpectedPattern,
+    /// `'name` expected after `?` or before a guard
+    ExpectedBinder,
+    ExpectedType,
+    UnclosedBracket,
+    MismatchedBracket,
+    /// Value definition without a value
+    MissingBody,
+    ExpectedColon,
+    ExpectedArrow,
+    /// `:` ends the line but the next one is not indented deeper
+    ExpectedBlock,
+    /// `then` or `else` not following a construct they belong to
+    UnexpectedContinuation,
+    /// `mut` on a function definition
+    MutableFunction,
+    /// Clause has other number of parameters than its signature
+    ArityMismatch { expected: usize, found: usize },
+    /// Clause is not adjacent to the signature (at `signature`) or to other clauses of its function
+    SeparatedClause { signature: Span },
+    ClauseWithoutSignature,
+    /// Clauses given for a function whose signature already has a body (at `body`)
+    ClauseAfterBody { body: Span },
+    /// Tuple field named like an earlier one (at `first`)
+    DuplicateField { first: Span },
+    /// Positional field after a named one where fields are matched by position
+    PositionalAfterNamed,
+    /// `name := value` in a list
+    NamedListElement,
+    /// `name params : Type ::= body`, clauses take the type of their function
+    AscribedClause,
+    /// `pub` or `priv` on a clause, clauses share the visibility of their function
+    ClauseVisibility,
+    /// Something other than `'name` among the parameters of a type or trait
+    ExpectedTypeParam,
+    /// `pub` or `priv` on a trait implementation, which is visible wherever its trait and type are
+    ImplVisibility,
+    /// Attributes ending a block, with nothing to apply to
+    DanglingAttribute,
+    /// Attribute not known to the registry, a warning
+    UnknownAttribute { name: ImStr },
+    /// Attribute given other number of arguments than it takes, or not string ones
+    AttributeArgs { min: usize, max: usize },
+    /// Attribute given to something it doesn't apply to
+    MisplacedAttribute,
+    /// `yield` inside an expression, generators only stop at statements and definition values
+    MisplacedYield,
+    /// `yield` outside of a function or lambda
+    YieldOutsideFunction,
+    /// Assignment to something other than a binding or a field
+    NotAPlace,
+    /// Assignment to a binding declared at `binding` without `mut`
+    ImmutableAssignment { binding: Span },
+    /// Line of a macro definition other than `(pattern) => template`
+    ExpectedMacroRule,
+    /// `$name` in a template which the pattern doesn't bind
+    UnknownMacroVar { name: ImStr },
+    /// `#name(...)` calling no macro; `backtrace` holds the calls of the expansions it's in, innermost first
+    UnknownMacro { name: ImStr, backtrace: Vec<Span> },
+    /// No rule of the macro matches the arguments
+    NoMacroRule { name: ImStr, backtrace: Vec<Span> },
+    /// Expansions nested deeper than `macros::RECURSION_LIMIT`
+    MacroRecursion { name: ImStr, backtrace: Vec<Span> },
+    /// `start..=` without an end
+    InclusiveRangeWithoutEnd,
+    /// Range bound of a type without an order to count through
+    InvalidRangeBound { ty: ImStr },
+    /// Literal range bounds of different types
+    RangeBoundMismatch { start: ImStr, end: ImStr },
+    /// Range pattern whose start is past its end
+    EmptyRangePattern,
+    /// Name defined nowhere in scope, `suggestion` is a visible one it may be a typo of
+    UnresolvedName { name: ImStr, suggestion: Option<ImStr> },
+    /// Binding hiding the one of an enclosing scope at `shadowed` without using it, a warning
+    ShadowedBinding { name: ImStr, shadowed: Span },
+    /// Module level definition named like an earlier one (at `first`)
+    DuplicateDefinition { first: Span },
+    /// Call whose constant arguments, written like `args` with `_` for unknown ones, no clause of `function` matches
+    UncoveredCall { function: ImStr, args: ImStr },
+    /// Arguments like `missing` match no clause of `function`, a warning
+    NonExhaustiveClauses { function: ImStr, missing: ImStr },
+    /// Clause whose arguments the clauses before it all match, a warning
+    UnreachableClause,
+    /// Expression or pattern of type `found` where `expected` is needed because of the code at `origin`
+    TypeMismatch { expected: ImStr, found: ImStr, origin: Option<Span> },
+    /// Type variable `var` which would have to be `ty`, which holds it
+    InfiniteType { var: ImStr, ty: ImStr },
+    /// Alias whose expansion holds it again
+    CyclicAlias { name: ImStr },
+    /// Number suffix which isn't a number type or is an integer type on a float
+    InvalidNumberSuffix { suffix: ImStr },
+    /// Number literal whose value doesn't fit in its type
+    LiteralOutOfRange { literal: ImStr, ty: ImStr },
+    /// Arithmetic on literals giving `result`, which doesn't fit in its type
+    ConstantOverflow { result: ImStr, ty: ImStr },
+    /// Division or remainder of literals by zero
+    DivisionByZero,
+    /// Bound of a use which no impl meets
+    NoImpl { bound: ImStr },
+    /// Bound of a use which several impls meet
+    AmbiguousImpl { bound: ImStr },
+    UnknownTrait { name: ImStr },
+    /// Definition in an impl which its trait doesn't have
+    NotTraitMethod { method: ImStr, trait_name: ImStr },
+    /// Method without default an impl doesn't define
+    MissingTraitMethod { method: ImStr, trait_name: ImStr },
+    /// Bound the definitions of an impl need which isn't in its `where` clause
+    MissingBound { bound: ImStr },
+    /// Dot call whose receiver no function or trait method of its name takes
+    NoMethod { method: ImStr, receiver: ImStr },
+    /// Dot call whose receiver several functions or trait methods of its name take
+    AmbiguousMethod { method: ImStr, candidates: Vec<ImStr> },
+    /// Element of a tuple `.map` is called on which its function, of type `function`, can't take
+    TupleMapElement { index: usize, element: ImStr, function: ImStr },
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::InvalidToken => write!(f, "invalid token"),
+            ParseErrorKind::InvalidNumber(errors) => write!(f, "invalid number literal: {errors:?}"),
+            ParseErrorKind::InvalidString(errors) => write!(f, "invalid string literal: {errors:?}"),
+            ParseErrorKind::InvalidChar(errors) => write!(f, "invalid character literal: {errors:?}"),
+            ParseErrorKind::UnexpectedToken => write!(f, "unexpected token"),
+            ParseErrorKind::UnexpectedDedent => write!(f, "line is indented less than the file start"),
+            ParseErrorKind::ExpectedExpression => write!(f, "expected expression"),
+            ParseErrorKind::ExpectedName => write!(f, "expected name"),
+            ParseErrorKind::ExpectedPattern => write!(f, "expected pattern"),
+            ParseErrorKind::ExpectedBinder => write!(f, "expected `'name`"),
+            ParseErrorKind::ExpectedType => write!(f, "expected type"),
+            ParseErrorKind::UnclosedBracket => write!(f, "unclosed bracket"),
+            ParseErrorKind::MismatchedBracket => write!(f, "mismatched closing bracket"),
+            ParseErrorKind::MissingBody => write!(f, "definition has no value"),
+            ParseErrorKind::ExpectedColon => write!(f, "expected `:`"),
+            ParseErrorKind::ExpectedArrow => write!(f, "expected `=>`"),
+            ParseErrorKind::ExpectedBlock => write!(f, "expected indented block"),
+            ParseErrorKind::UnexpectedContinuation => write!(f, "no construct to continue"),
+            ParseErrorKind::MutableFunction => write!(f, "function can not be `mut`"),
+            ParseErrorKind::ArityMismatch { expected, found } => {
+                write!(f, "clause has {found} parameters, but the signature has {expected}")
+            }
+            ParseErrorKind::SeparatedClause { .. } => write!(f, "clause is separated from its function"),
+            ParseErrorKind::ClauseWithoutSignature => write!(f, "clause has no function signature before it"),
+            ParseErrorKind::ClauseAfterBody { .. } => write!(f, "function with a body can not have clauses"),
+            ParseErrorKind::DuplicateField { .. } => write!(f, "field is already given"),
+            ParseErrorKind::PositionalAfterNamed => write!(f, "positional field after named ones"),
+            ParseErrorKind::NamedListElement => write!(f, "list elements can not be named"),
+            ParseErrorKind::AscribedClause => write!(f, "clause can not have a type"),
+            ParseErrorKind::ClauseVisibility => write!(f, "clause can not have a visibility"),
+            ParseErrorKind::ExpectedTypeParam => write!(f, "expected type parameter `'name`"),
+            ParseErrorKind::ImplVisibility => write!(f, "trait implementation can not have a visibility"),
+            ParseErrorKind::DanglingAttribute => write!(f, "attributes are not followed by anything to apply to"),
+            ParseErrorKind::UnknownAttribute { name } => write!(f, "unknown attribute `{name}`"),
+            ParseErrorKind::AttributeArgs { min, max } if min == max => write!(f, "attribute takes {min} string arguments"),
+            ParseErrorKind::AttributeArgs { min, max } => write!(f, "attribute takes {min} to {max} string arguments"),
+            ParseErrorKind::MisplacedAttribute => write!(f, "attribute can not be applied here"),
+            ParseErrorKind::MisplacedYield => write!(f, "`yield` can only be a statement or the value of a definition"),
+            ParseErrorKind::YieldOutsideFunction => write!(f, "`yield` outside of a function"),
+            ParseErrorKind::NotAPlace => write!(f, "only bindings and their fields can be assigned to"),
+            ParseErrorKind::ImmutableAssignment { .. } => write!(f, "assignment to a binding which is not `mut`"),
+            ParseErrorKind::ExpectedMacroRule => write!(f, "expected macro rule `(pattern) => template`"),
+            ParseErrorKind::UnknownMacroVar { name } => write!(f, "macro variable `${name}` is not bound by the pattern"),
+            ParseErrorKind::UnknownMacro { name, backtrace } => {
+                write!(f, "unknown macro `{name}`")?;
+                write_backtrace(f, backtrace)
+            }
+            ParseErrorKind::NoMacroRule { name, backtrace } => {
+                write!(f, "no rule of macro `{name}` matches the arguments")?;
+                write_backtrace(f, backtrace)
+            }
+            ParseErrorKind::MacroRecursion { name, backtrace } => {
+                write!(f, "expansion of macro `{name}` doesn't terminate")?;
+                write_backtrace(f, backtrace)
+            }
+            ParseErrorKind::InclusiveRangeWithoutEnd => write!(f, "inclusive range needs an end"),
+            ParseErrorKind::InvalidRangeBound { ty } => write!(f, "{ty} can not bound a range"),
+            ParseErrorKind::RangeBoundMismatch { start, end } => {
+                write!(f, "range bounds have different types, {start} and {end}")
+            }
+            ParseErrorKind::EmptyRangePattern => write!(f, "range pattern matches no value"),
+            ParseErrorKind::UnresolvedName { name, suggestion } => {
+                write!(f, "cannot find `{name}` in this scope")?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", did you mean `{suggestion}`?"),
+                    None => Ok(()),
+                }
+            }
+            ParseErrorKind::ShadowedBinding { name, shadowed } => {
+                write!(f, "`{name}` shadows the binding at {}:{}", shadowed.row + 1, shadowed.num)
+            }
+            ParseErrorKind::DuplicateDefinition { .. } => write!(f, "name is already defined in this module"),
+            ParseErrorKind::UncoveredCall { function, args } => {
+                write!(f, "no clause of `{function}` matches the arguments `{args}`")
+            }
+            ParseErrorKind::NonExhaustiveClauses { function, missing } => {
+                write!(f, "clauses of `{function}` don't cover `{missing}`")
+            }
+            ParseErrorKind::UnreachableClause => write!(f, "clause is never reached, the ones before match its arguments"),
+            ParseErrorKind::TypeMismatch { expected, found, origin } => {
+                write!(f, "expected `{expected}`, found `{found}`")?;
+                match origin {
+                    Some(origin) => write!(f, ", expected because of {}:{}", origin.row + 1, origin.num),
+                    None => Ok(()),
+                }
+            }
+            ParseErrorKind::InvalidNumberSuffix { suffix } => write!(f, "`{suffix}` isn't a type this number can have"),
+            ParseErrorKind::LiteralOutOfRange { literal, ty } => write!(f, "literal `{literal}` doesn't fit in `{ty}`"),
+            ParseErrorKind::ConstantOverflow { result, ty } => write!(f, "this gives `{result}`, which overflows `{ty}`"),
+            ParseErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ParseErrorKind::NoImpl { bound } => write!(f, "no impl meets `{bound}`"),
+            ParseErrorKind::AmbiguousImpl { bound } => write!(f, "several impls meet `{bound}`"),
+            ParseErrorKind::InfiniteType { var, ty } => write!(f, "infinite type: `{var}` would have to be `{ty}`, which holds it"),
+            ParseErrorKind::CyclicAlias { name } => write!(f, "alias `{name}` expands to itself"),
+            ParseErrorKind::UnknownTrait { name } => write!(f, "no trait named `{name}`"),
+            ParseErrorKind::NotTraitMethod { method, trait_name } => write!(f, "`{method}` isn't a method of `{trait_name}`"),
+            ParseErrorKind::MissingTraitMethod { method, trait_name } => write!(f, "`{method}` of `{trait_name}` isn't defined"),
+            ParseErrorKind::MissingBound { bound } => write!(f, "`{bound}` is needed, add it to the `where` clause"),
+            ParseErrorKind::NoMethod { method, receiver } => write!(f, "no function or trait method `{method}` takes a `{receiver}` first"),
+            ParseErrorKind::AmbiguousMethod { method, candidates } => {
+                write!(f, "`.{method}` could call ")?;
+                for (i, candidate) in candidates.iter().enumerate() {
+                    let separator = match i {
+                        0 => "",
+                        _ if i + 1 == candidates.len() => " or ",
+                        _ => ", ",
+                    };
+                    write!(f, "{separator}`{candidate}`")?;
+                }
+                Ok(())
+            }
+            ParseErrorKind::TupleMapElement { index, element, function } => {
+                write!(f, "element {index} of the tuple is a `{element}`, which a `{function}` can't take")
+            }
+        }
+    }
+}
+
+fn write_backtrace(f: &mut Formatter<'_>, backtrace: &[Span]) -> std::fmt::Result {
+    for span in backtrace {
+        write!(f, "\n  in expansion at {}:{}", span.row + 1, span.num)?;
+    }
+    Ok(())
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}:{}: {severity}: {}", self.span.row + 1, self.span.num, self.kind)
+    }
+}
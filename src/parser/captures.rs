@@ -0,0 +1,109 @@
+use crate::ImStr;
+use crate::parser::*;
+use crate::visit::{walk_expr, walk_pattern, Visit};
+
+/// Names used in a lambda body which neither its parameters nor the body itself bind,
+/// in order of the first use. Later passes tell captured locals from global names
+pub(super) fn captures(params: &[Pattern], body: &Expr) -> Vec<Ident> {
+    let mut scope = Scope { bound: vec![], free: vec![] };
+    for param in params {
+        scope.visit_pattern(param);
+    }
+    scope.visit_expr(body);
+    scope.free
+}
+
+struct Scope {
+    /// Names bound so far, truncated back when leaving a nested scope
+    bound: Vec<ImStr>,
+    free: Vec<Ident>,
+}
+
+impl Scope {
+    fn reference(&mut self, name: &ImStr, span: Span) {
+        if !self.bound.contains(name) && !self.free.iter().any(|free| free.name == *name) {
+            self.free.push(Ident { name: name.clone(), span });
+        }
+    }
+
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        let len = self.bound.len();
+        f(self);
+        self.bound.truncate(len);
+    }
+}
+
+impl Visit for Scope {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Let(Let { name, value, .. }) => {
+                self.visit_expr(value);
+                self.bound.push(name.name.clone());
+            }
+            // functions see themselves
+            Stmt::Function(function) => {
+                self.bound.push(function.name.name.clone());
+                self.nested(|scope| {
+                    function.params.iter().for_each(|param| scope.visit_pattern(param));
+                    if let Some(body) = &function.body {
+                        scope.visit_expr(body);
+                    }
+                });
+                for clause in &function.clauses {
+                    self.nested(|scope| scope.visit_clause(clause));
+                }
+            }
+            Stmt::Expr(expr) => self.visit_expr(expr),
+            Stmt::Mod(_) | Stmt::Use(_) | Stmt::Type(_) | Stmt::Trait(_) | Stmt::Impl(_) | Stmt::Signature(_) => {}
+        }
+    }
+
+    fn visit_attribute(&mut self, _attr: &Attribute) {}
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Name(name) => self.reference(name, expr.span),
+            ExprKind::Block(_) => self.nested(|scope| walk_expr(scope, expr)),
+            ExprKind::For { iter, pattern, body } => {
+                self.visit_expr(iter);
+                self.nested(|scope| {
+                    scope.visit_pattern(pattern);
+                    scope.visit_expr(body);
+                });
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.nested(|scope| scope.visit_arm(arm));
+                }
+            }
+            // names captured by a nested lambda are captured by this one unless bound here
+            ExprKind::Lambda { captures, .. } => {
+                captures.iter().for_each(|capture| self.reference(&capture.name, capture.span));
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    /// Binds the names of `pattern`, its guards and defaults are visited as well
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Bind { name, pattern, guard } => {
+                if let Some(pattern) = pattern {
+                    self.visit_pattern(pattern);
+                }
+                self.bound.push(name.name.clone());
+                if let Some(guard) = guard {
+                    self.visit_expr(guard);
+                }
+            }
+            PatternKind::Optional { name, default } => {
+                if let Some(default) = default {
+                    self.visit_expr(default);
+                }
+                self.bound.push(name.name.clone());
+            }
+            _ => walk_pattern(self, pattern),
+        }
+    }
+}
@@ -0,0 +1,638 @@
+use crate::lexer::{Token, TokenType};
+use crate::parser::*;
+use crate::parser::places::immutable_assignments;
+use crate::parser::yields::yields;
+use crate::syntax::SyntaxKind;
+
+pub(super) type PResult<T> = Result<T, ParseError>;
+
+pub fn parse(seq: &[Token]) -> Result<Ast, Vec<ParseError>> {
+    let (ast, diagnostics) = parse_with_diagnostics(seq);
+    let errors: Vec<_> = diagnostics.into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|Diagnostic { kind, span, .. }| ParseError { kind, span })
+        .collect();
+    if errors.is_empty() { Ok(ast) } else { Err(errors) }
+}
+
+/// Parses the whole sequence whatever errors it has,
+/// erroneous parts are replaced with `Error` and `Missing` nodes
+pub fn parse_with_diagnostics(seq: &[Token]) -> (Ast, Vec<Diagnostic>) {
+    let (ast, diagnostics, _) = parse_events(seq);
+    (ast, diagnostics)
+}
+
+/// Parses the sequence recording the syntax tree events too
+pub(crate) fn parse_events(seq: &[Token]) -> (Ast, Vec<Diagnostic>, Vec<Event>) {
+    let mut parser = Parser::new(seq);
+    parser.report_lexer_errors(seq);
+    let stmts = parser.parse_file();
+
+    let mut diagnostics = parser.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span.row, diagnostic.span.num));
+    (Ast { stmts, ..Ast::default() }, diagnostics, parser.events)
+}
+
+pub(super) struct Parser<'t> {
+    tokens: Vec<&'t Token>,
+    /// Index of every token in the lexer output
+    indices: Vec<usize>,
+    pos: usize,
+    /// Last consumed token
+    last: Option<&'t Token>,
+    layout: Vec<Layout>,
+    diagnostics: Vec<Diagnostic>,
+    events: Vec<Event>,
+    /// Layout depth of an expression ended by `:`, where `:` doesn't pass a block argument
+    before_colon: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    /// Indentation block, its lines start at the given width
+    Block(usize),
+    /// Inside of brackets, line breaks are ignored unless they dedent the enclosing block
+    Group,
+}
+
+/// Block statement before clauses are grouped into functions
+enum Item {
+    Stmt(Stmt),
+    Clause(Ident, Clause),
+}
+
+fn width(indent: &Token) -> usize {
+    indent.slice.chars().count()
+}
+
+impl<'t> Parser<'t> {
+    fn new(seq: &'t [Token]) -> Self {
+        let tokens: Vec<_> = seq.iter().enumerate()
+            .filter(|(_, token)| !matches!(token.ty, TokenType::Comment))
+            .collect();
+        // lines holding nothing but comments
+        let (indices, tokens) = tokens.iter().enumerate()
+            .filter(|(i, (_, token))| !matches!(token.ty, TokenType::Indent)
+                || tokens.get(i + 1).is_some_and(|(_, next)| !matches!(next.ty, TokenType::Indent)))
+            .map(|(_, token)| *token)
+            .unzip();
+
+        Parser { tokens, indices, pos: 0, last: None, layout: vec![], diagnostics: vec![], events: vec![], before_colon: None }
+    }
+
+    fn report_lexer_errors(&mut self, seq: &[Token]) {
+        for token in seq {
+            let kind = match &token.ty {
+                TokenType::Error => ParseErrorKind::InvalidToken,
+                TokenType::Number { errors, .. } if !errors.is_empty() => ParseErrorKind::InvalidNumber(*errors),
+                TokenType::String { errors, .. } if !errors.is_empty() => ParseErrorKind::InvalidString(*errors),
+                TokenType::Character { errors, .. } if !errors.is_empty() => ParseErrorKind::InvalidChar(*errors),
+                _ => continue,
+            };
+            self.report(ParseError { kind, span: Span::of(token) });
+        }
+    }
+
+    pub(super) fn report(&mut self, error: ParseError) {
+        self.diagnostics.push(Diagnostic { severity: Severity::Error, kind: error.kind, span: error.span });
+    }
+
+    /// Next token of the current logical line, `None` at a line break ending it or at the end.
+    /// Skips line breaks inside brackets and before continuation lines
+    pub(super) fn peek(&mut self) -> Option<&'t Token> {
+        while let Some(token) = self.tokens.get(self.pos).copied() {
+            let TokenType::Indent = token.ty else { return Some(token) };
+            match self.layout.last() {
+                Some(Layout::Group) if width(token) >= self.indent() => self.pos += 1,
+                Some(&Layout::Block(indent)) if width(token) > indent => self.pos += 1,
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Consumes the token returned by the last `peek`
+    pub(super) fn bump(&mut self) -> &'t Token {
+        let token = self.tokens[self.pos];
+        self.events.push(Event::Token(self.indices[self.pos]));
+        self.pos += 1;
+        self.last = Some(token);
+        token
+    }
+
+    /// Position to start a syntax node at once its kind is known
+    pub(super) fn checkpoint(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Wraps everything consumed since `checkpoint` into a syntax node
+    pub(super) fn node(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        self.events.insert(checkpoint, Event::Start(kind));
+        self.events.push(Event::Finish);
+    }
+
+    /// Makes the node started at `start` begin at `checkpoint` instead, taking in everything consumed in between
+    pub(super) fn precede(&mut self, checkpoint: usize, start: usize) {
+        let event = self.events.remove(start);
+        self.events.insert(checkpoint, event);
+    }
+
+    /// Whether the last consumed token ends its line
+    pub(super) fn at_line_end(&self) -> bool {
+        matches!(self.tokens.get(self.pos), None | Some(Token { ty: TokenType::Indent, .. }))
+    }
+
+    /// Whether the next token directly follows the last consumed one
+    pub(super) fn is_adjacent(&self) -> bool {
+        match (self.last, self.tokens.get(self.pos)) {
+            (Some(last), Some(next)) => {
+                let last = Span::of(last);
+                !matches!(next.ty, TokenType::Indent) && last.end_row == next.row && last.end_num == next.num
+            }
+            _ => false,
+        }
+    }
+
+    /// Token after the one returned by `peek` on the same logical line
+    pub(super) fn peek_next(&mut self) -> Option<&'t Token> {
+        self.peek()?;
+        let pos = self.pos;
+        self.pos += 1;
+        let next = self.peek();
+        self.pos = pos;
+        next
+    }
+
+    pub(super) fn is_op(&mut self, op: &str) -> bool {
+        matches!(self.peek(), Some(Token { ty: TokenType::Operator, slice, .. }) if slice == op)
+    }
+
+    pub(super) fn is_keyword(&mut self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token { ty: TokenType::Keyword(name), .. }) if name == keyword)
+    }
+
+    pub(super) fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.bump();
+        }
+        found
+    }
+
+    /// Span of the last consumed token
+    pub(super) fn last_span(&self) -> Span {
+        self.last.map(Span::of).unwrap_or_default()
+    }
+
+    /// Span from `start` to the end of the last consumed token
+    pub(super) fn span_from(&self, start: Span) -> Span {
+        start.to(self.last_span())
+    }
+
+    /// Empty span right after the last consumed token
+    pub(super) fn end_span(&self) -> Span {
+        let last = self.last_span();
+        Span { row: last.end_row, num: last.end_num, ..last }
+    }
+
+    /// Error at the next token, or right after the last one at a line end
+    pub(super) fn error(&mut self, kind: ParseErrorKind) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError { kind, span: Span::of(token) },
+            None => ParseError { kind, span: self.end_span() },
+        }
+    }
+
+    /// Reports an expected but absent expression
+    pub(super) fn missing(&mut self, kind: ParseErrorKind) -> Expr {
+        let error = self.error(kind);
+        self.report(error);
+        self.node(self.checkpoint(), SyntaxKind::Missing);
+        Expr { kind: ExprKind::Missing, span: self.end_span() }
+    }
+
+    /// Reports `error` and skips the rest of the current line or bracket group
+    /// entered at layout depth `depth`, returning the node replacing the skipped tokens
+    pub(super) fn recover(&mut self, depth: usize, error: ParseError) -> Expr {
+        self.layout.truncate(depth);
+        self.report(error);
+
+        let in_group = matches!(self.layout.last(), Some(Layout::Group));
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of);
+        let mut nesting = 0usize;
+        while let Some(token) = self.peek() {
+            match token.ty {
+                TokenType::Open => nesting += 1,
+                TokenType::Close if nesting == 0 && in_group => break,
+                TokenType::Close => nesting = nesting.saturating_sub(1),
+                TokenType::Semicolon if nesting == 0 && !in_group => break,
+                _ => {}
+            }
+            self.bump();
+        }
+        let span = start.map_or(self.end_span(), |start| self.span_from(start));
+        self.node(checkpoint, SyntaxKind::Error);
+        Expr { kind: ExprKind::Error, span }
+    }
+
+    /// Width of the innermost indentation block
+    fn indent(&self) -> usize {
+        self.layout.iter().rev()
+            .find_map(|layout| match layout {
+                Layout::Block(indent) => Some(*indent),
+                Layout::Group => None,
+            })
+            .unwrap_or(0)
+    }
+
+    /// Enters brackets, returns the layout depth to recover at
+    pub(super) fn enter_group(&mut self) -> usize {
+        self.layout.push(Layout::Group);
+        self.layout.len()
+    }
+
+    pub(super) fn layout_depth(&self) -> usize {
+        self.layout.len()
+    }
+
+    pub(super) fn leave_group(&mut self) {
+        self.layout.pop();
+    }
+
+    /// Parses an expression ended by `:`, like the scrutinee of `match`
+    pub(super) fn parse_expr_before_colon(&mut self) -> PResult<Expr> {
+        let outer = self.before_colon.replace(self.layout.len());
+        let expr = self.parse_expr();
+        self.before_colon = outer;
+        expr
+    }
+
+    /// Whether `:` can pass a block argument to an application here
+    pub(super) fn colon_args(&self) -> bool {
+        self.before_colon != Some(self.layout.len())
+    }
+
+    pub(super) fn parse_ident(&mut self) -> PResult<Ident> {
+        match self.peek() {
+            Some(Token { ty: TokenType::Symbol(name), .. }) => {
+                let token = self.bump();
+                Ok(Ident { name: name.clone(), span: Span::of(token) })
+            }
+            _ => Err(self.error(ParseErrorKind::ExpectedName)),
+        }
+    }
+
+    fn parse_file(&mut self) -> Vec<Stmt> {
+        let mut stmts = vec![];
+        while let Some(line) = self.tokens.get(self.pos).copied() {
+            self.pos += 1;
+            if !stmts.is_empty() {
+                let error = self.error(ParseErrorKind::UnexpectedDedent);
+                self.report(error);
+            }
+            stmts.extend(self.parse_block(width(line)));
+        }
+        for stmt in &stmts {
+            if let Stmt::Let(Let { value: expr, .. }) | Stmt::Expr(expr) = stmt {
+                let yields = yields(&[], expr);
+                for span in yields.stops.into_iter().chain(yields.misplaced) {
+                    self.report(ParseError { kind: ParseErrorKind::YieldOutsideFunction, span });
+                }
+            }
+        }
+        for error in immutable_assignments(&stmts) {
+            self.report(error);
+        }
+        self.node(0, SyntaxKind::Root);
+        stmts
+    }
+
+    /// Parses statements of a block whose lines start at `indent`,
+    /// the first line break is expected to be consumed already
+    pub(super) fn parse_block(&mut self, indent: usize) -> Vec<Stmt> {
+        let parse = |parser: &mut Self| parser.parse_attributed(Self::parse_stmt);
+        let items = self.parse_lines(indent, parse, |error| Item::Stmt(Stmt::Expr(error)));
+        self.group_clauses(items)
+    }
+
+    /// Parses the lines of a trait body, definitions and signatures of methods without default
+    pub(super) fn parse_trait_items(&mut self, indent: usize) -> Vec<Stmt> {
+        let parse = |parser: &mut Self| match parser.find_definition() {
+            Some(clause) => parser.parse_definition(clause),
+            None => parser.parse_signature().map(|signature| Item::Stmt(Stmt::Signature(signature))),
+        };
+        let items = self.parse_lines(indent, |parser| parser.parse_attributed(parse), |error| Item::Stmt(Stmt::Expr(error)));
+        self.group_clauses(items)
+    }
+
+    /// Parses lines starting at `indent` with `parse`, several items on a line are separated by `;`.
+    /// Skipped erroneous tokens are turned into items by `error`
+    pub(super) fn parse_lines<T>(
+        &mut self,
+        indent: usize,
+        mut parse: impl FnMut(&mut Self) -> PResult<T>,
+        error: impl Fn(Expr) -> T,
+    ) -> Vec<T> {
+        self.layout.push(Layout::Block(indent));
+        let depth = self.layout.len();
+        let mut items = vec![];
+        loop {
+            let item = parse(self).unwrap_or_else(|err| error(self.recover(depth, err)));
+            items.push(item);
+            if self.peek().is_some_and(|token| !matches!(token.ty, TokenType::Semicolon)) {
+                let err = self.error(ParseErrorKind::UnexpectedToken);
+                items.push(error(self.recover(depth, err)));
+            }
+            if self.peek().is_some() {
+                self.bump();
+                if self.peek().is_some() {
+                    continue;
+                }
+            }
+            match self.tokens.get(self.pos) {
+                Some(token @ Token { ty: TokenType::Indent, .. }) if width(token) == indent => self.pos += 1,
+                _ => break,
+            }
+        }
+        self.layout.pop();
+        items
+    }
+
+    /// Parses what follows `:=`, `::=` or `:`:
+    /// an indented block if the line ends here, otherwise the rest of the line
+    pub(super) fn parse_body(&mut self) -> PResult<Option<Expr>> {
+        if !self.at_line_end() {
+            return self.parse_expr().map(Some);
+        }
+        let Some(indent) = self.enter_indented() else { return Ok(None) };
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let stmts = self.parse_block(indent);
+        self.node(checkpoint, SyntaxKind::Block);
+        Ok(Some(Expr { kind: ExprKind::Block(stmts), span: self.span_from(start) }))
+    }
+
+    /// Consumes the line break before lines indented deeper than the current block,
+    /// returns their indentation
+    pub(super) fn enter_indented(&mut self) -> Option<usize> {
+        if !self.at_line_end() {
+            return None;
+        }
+        match self.tokens.get(self.pos).copied() {
+            Some(token) if width(token) > self.indent() => {
+                self.pos += 1;
+                Some(width(token))
+            }
+            _ => None,
+        }
+    }
+
+    /// Consumes the line break before the next line of the current block if the current line has ended
+    /// and the next one doesn't start with a closing bracket or a separator
+    pub(super) fn enter_next_line(&mut self) -> bool {
+        if self.peek().is_some() {
+            return false;
+        }
+        match self.tokens.get(self.pos) {
+            Some(line) if width(line) == self.indent() => {
+                self.pos += 1;
+                let ends = |token: &Token| matches!(token.ty, TokenType::Close | TokenType::Comma | TokenType::Semicolon);
+                if self.peek().is_none_or(ends) {
+                    self.pos -= 1;
+                    return false;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Consumes `keyword` continuing the current construct,
+    /// either on the current line or at the start of the next line of the block
+    pub(super) fn eat_continuation(&mut self, keyword: &str) -> bool {
+        if self.eat_keyword(keyword) {
+            return true;
+        }
+        if self.peek().is_some() {
+            return false;
+        }
+        match (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            (Some(line), Some(Token { ty: TokenType::Keyword(name), .. }))
+            if width(line) == self.indent() && name == keyword => {
+                self.pos += 1;
+                self.bump();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses a line item with `parse`, giving it the attributes before it
+    fn parse_attributed(&mut self, parse: impl FnOnce(&mut Self) -> PResult<Item>) -> PResult<Item> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        if !self.at_attribute() {
+            return parse(self);
+        }
+        let attrs = self.parse_outer_attributes()?;
+        if self.peek().is_none() {
+            let missing = self.missing(ParseErrorKind::DanglingAttribute);
+            return Ok(Item::Stmt(Stmt::Expr(self.annotate(checkpoint, start, attrs, missing))));
+        }
+        let item_start = self.checkpoint();
+        let item = parse(self)?;
+        let mut stmt = match item {
+            Item::Stmt(Stmt::Expr(expr)) => return Ok(Item::Stmt(Stmt::Expr(self.annotate(checkpoint, start, attrs, expr)))),
+            Item::Clause(name, clause) => {
+                for attr in &attrs {
+                    self.report(ParseError { kind: ParseErrorKind::MisplacedAttribute, span: attr.span });
+                }
+                self.precede(checkpoint, item_start);
+                return Ok(Item::Clause(name, clause));
+            }
+            Item::Stmt(stmt) => stmt,
+        };
+        self.precede(checkpoint, item_start);
+        if let Some(stmt_attrs) = stmt.attrs_mut() {
+            *stmt_attrs = attrs;
+        }
+        Ok(Item::Stmt(stmt))
+    }
+
+    fn parse_stmt(&mut self) -> PResult<Item> {
+        let declaration = match self.peek() {
+            Some(Token { ty: TokenType::Keyword(keyword), .. }) if keyword == "pub" || keyword == "priv" => self.peek_next(),
+            token => token,
+        };
+        match declaration.map(|token| &token.ty) {
+            Some(TokenType::Keyword(keyword)) if keyword == "mod" => return self.parse_mod().map(Item::Stmt),
+            Some(TokenType::Keyword(keyword)) if keyword == "use" => return self.parse_use().map(Item::Stmt),
+            Some(TokenType::Keyword(keyword)) if keyword == "type" || keyword == "class" => {
+                return self.parse_type_decl().map(Item::Stmt);
+            }
+            Some(TokenType::Keyword(keyword)) if keyword == "trait" => return self.parse_trait().map(Item::Stmt),
+            _ => {}
+        }
+        match self.find_definition() {
+            Some(clause) => self.parse_definition(clause),
+            None => Ok(Item::Stmt(Stmt::Expr(self.parse_expr()?))),
+        }
+    }
+
+    /// Looks for `:=` (`Some(false)`) or `::=` (`Some(true)`) on the current line outside of brackets
+    fn find_definition(&self) -> Option<bool> {
+        let mut depth = 0usize;
+        // whether a `:` can start a type ascription
+        let mut signature = true;
+        for token in &self.tokens[self.pos..] {
+            match &token.ty {
+                TokenType::Keyword(keyword) if depth == 0 && !matches!(keyword.as_str(), "pub" | "priv" | "mut" | "_") => {
+                    signature = false;
+                }
+                TokenType::Open => depth += 1,
+                TokenType::Close => depth = depth.checked_sub(1)?,
+                TokenType::Indent | TokenType::Semicolon if depth == 0 => return None,
+                // unclosed brackets end with the block
+                TokenType::Indent if width(token) < self.indent() => return None,
+                TokenType::Operator if depth == 0 => match token.slice.as_str() {
+                    ":=" => return Some(false),
+                    "::=" => return Some(true),
+                    ":" if !signature => return None,
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Whether `for` follows on the current line before `:`, making the `trait` being parsed an implementation
+    pub(super) fn is_impl(&self) -> bool {
+        let mut depth = 0usize;
+        for token in &self.tokens[self.pos..] {
+            match &token.ty {
+                TokenType::Open => depth += 1,
+                TokenType::Close if depth == 0 => return false,
+                TokenType::Close => depth -= 1,
+                TokenType::Indent | TokenType::Semicolon if depth == 0 => return false,
+                TokenType::Indent if width(token) < self.indent() => return false,
+                TokenType::Keyword(keyword) if depth == 0 && keyword == "for" => return true,
+                TokenType::Operator if depth == 0 && token.slice == ":" => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn parse_definition(&mut self, clause: bool) -> PResult<Item> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let vis = self.parse_visibility();
+        let mutable = self.eat_keyword("mut");
+        let name = self.parse_ident()?;
+        let mut params = vec![];
+        while !self.is_op(":=") && !self.is_op("::=") && !self.is_op(":") {
+            params.push(self.parse_pattern_atom()?);
+        }
+        let ty = match self.is_op(":") {
+            true => {
+                self.bump();
+                Some(self.parse_type()?)
+            }
+            false => None,
+        };
+        let signature = self.span_from(start);
+        if !self.is_op(":=") && !self.is_op("::=") {
+            return Err(self.error(ParseErrorKind::UnexpectedToken));
+        }
+        self.bump();
+        let body = self.parse_body()?;
+
+        if clause {
+            if let Some(ty) = &ty {
+                self.report(ParseError { kind: ParseErrorKind::AscribedClause, span: ty.span });
+            }
+            if vis != Visibility::Default {
+                self.report(ParseError { kind: ParseErrorKind::ClauseVisibility, span: start });
+            }
+            let body = body.unwrap_or_else(|| self.missing(ParseErrorKind::ExpectedExpression));
+            self.node(checkpoint, SyntaxKind::Clause);
+            let span = signature.to(body.span);
+            return Ok(Item::Clause(name, Clause { params, body, span }));
+        }
+        self.node(checkpoint, SyntaxKind::Definition);
+        if params.is_empty() {
+            let value = body.unwrap_or_else(|| {
+                self.report(ParseError { kind: ParseErrorKind::MissingBody, span: signature });
+                Expr { kind: ExprKind::Missing, span: self.end_span() }
+            });
+            let span = signature.to(value.span);
+            return Ok(Item::Stmt(Stmt::Let(Let { attrs: vec![], vis, name, mutable, ty, value, span })));
+        }
+        if mutable {
+            self.report(ParseError { kind: ParseErrorKind::MutableFunction, span: signature });
+        }
+        let generator = body.as_ref().is_some_and(|body| self.check_generator(&params, body));
+        let function = FunctionDef { attrs: vec![], vis, name, params, ty, body, clauses: vec![], generator, span: signature };
+        Ok(Item::Stmt(Stmt::Function(function)))
+    }
+
+    /// Reports yields a generator can't stop at, returns whether the function or lambda yields
+    pub(super) fn check_generator(&mut self, params: &[Pattern], body: &Expr) -> bool {
+        let yields = yields(params, body);
+        for &span in &yields.misplaced {
+            self.report(ParseError { kind: ParseErrorKind::MisplacedYield, span });
+        }
+        !yields.stops.is_empty() || !yields.misplaced.is_empty()
+    }
+
+    /// Attaches clauses to the function signature right before them
+    fn group_clauses(&mut self, items: Vec<Item>) -> Vec<Stmt> {
+        let mut stmts: Vec<Stmt> = vec![];
+        // index of the function still accepting clauses
+        let mut open = None;
+
+        for item in items {
+            let (name, clause) = match item {
+                Item::Stmt(stmt) => {
+                    open = matches!(stmt, Stmt::Function(_)).then_some(stmts.len());
+                    stmts.push(stmt);
+                    continue;
+                }
+                Item::Clause(name, clause) => (name, clause),
+            };
+            let generator = self.check_generator(&clause.params, &clause.body);
+
+            let function = open
+                .and_then(|i| match &mut stmts[i] {
+                    Stmt::Function(function) if function.name.name == name.name => Some(function),
+                    _ => None,
+                });
+            let Some(function) = function else {
+                let signature = stmts.iter().rev().find_map(|stmt| match stmt {
+                    Stmt::Function(function) if function.name.name == name.name => Some(function.span),
+                    _ => None,
+                });
+                let kind = match signature {
+                    Some(signature) => ParseErrorKind::SeparatedClause { signature },
+                    None => ParseErrorKind::ClauseWithoutSignature,
+                };
+                self.report(ParseError { kind, span: clause.span });
+                open = None;
+                continue;
+            };
+
+            if let Some(body) = &function.body {
+                self.report(ParseError { kind: ParseErrorKind::ClauseAfterBody { body: body.span }, span: clause.span });
+            }
+            if clause.params.len() != function.params.len() {
+                let kind = ParseErrorKind::ArityMismatch { expected: function.params.len(), found: clause.params.len() };
+                self.report(ParseError { kind, span: clause.span });
+            }
+            function.generator |= generator;
+            function.clauses.push(clause);
+        }
+        stmts
+    }
+}
@@ -0,0 +1,82 @@
+use crate::lexer::TokenType;
+use crate::parser::parse::{PResult, Parser};
+use crate::parser::*;
+use crate::syntax::SyntaxKind;
+
+impl Parser<'_> {
+    pub(super) fn at_attribute(&mut self) -> bool {
+        self.peek().is_some_and(|token| matches!(token.ty, TokenType::Sharp))
+    }
+
+    /// Parses attributes before a statement,
+    /// a line holding nothing but attributes gives them to the next line of the block
+    pub(super) fn parse_outer_attributes(&mut self) -> PResult<Vec<Attribute>> {
+        let mut attrs = vec![];
+        while self.at_attribute() {
+            attrs.extend(self.parse_attributes()?);
+            self.enter_next_line();
+        }
+        Ok(attrs)
+    }
+
+    /// Parses `#attrs... expr`
+    pub(super) fn parse_annotated(&mut self) -> PResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let mut attrs = vec![];
+        while self.at_attribute() {
+            attrs.extend(self.parse_attributes()?);
+        }
+        let expr = self.parse_expr()?;
+        Ok(self.annotate(checkpoint, start, attrs, expr))
+    }
+
+    /// Wraps `expr` into a node with the attributes parsed since `checkpoint`
+    pub(super) fn annotate(&mut self, checkpoint: usize, start: Span, attrs: Vec<Attribute>, expr: Expr) -> Expr {
+        self.node(checkpoint, SyntaxKind::Annotated);
+        Expr { kind: ExprKind::Annotated { attrs, expr: Box::new(expr) }, span: self.span_from(start) }
+    }
+
+    /// Parses `#name args...` or `#[name args..., ...]`
+    fn parse_attributes(&mut self) -> PResult<Vec<Attribute>> {
+        let checkpoint = self.checkpoint();
+        let start = Span::of(self.bump());
+        if !self.peek().is_some_and(|token| matches!(token.ty, TokenType::Open) && token.slice == "[") {
+            return Ok(vec![self.parse_attribute(checkpoint, start)?]);
+        }
+
+        let open = self.bump();
+        let depth = self.enter_group();
+        let mut attrs = vec![];
+        while !self.at_close() && self.peek().is_some() {
+            let start = self.peek().map(Span::of).unwrap_or_default();
+            match self.parse_attribute(self.checkpoint(), start) {
+                Ok(attr) => attrs.push(attr),
+                Err(error) => {
+                    self.recover(depth, error);
+                }
+            }
+            if !self.peek().is_some_and(|token| matches!(token.ty, TokenType::Comma)) {
+                break;
+            }
+            self.bump();
+        }
+        self.expect_close(open);
+        self.leave_group();
+        self.node(checkpoint, SyntaxKind::AttributeGroup);
+        Ok(attrs)
+    }
+
+    /// Parses `name args...` of an attribute starting at `checkpoint`
+    fn parse_attribute(&mut self, checkpoint: usize, start: Span) -> PResult<Attribute> {
+        let name = self.parse_ident()?;
+        let mut args = vec![];
+        while self.peek().is_some_and(|token| {
+            matches!(token.ty, TokenType::Number { .. } | TokenType::String { .. } | TokenType::Character { .. })
+        }) {
+            args.push(self.parse_atom()?);
+        }
+        self.node(checkpoint, SyntaxKind::Attribute);
+        Ok(Attribute { name, args, span: self.span_from(start) })
+    }
+}
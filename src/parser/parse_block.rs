@@ -0,0 +1,125 @@
+use crate::lexer::TokenType;
+use crate::parser::captures::captures;
+use crate::parser::parse::{PResult, Parser};
+use crate::parser::*;
+use crate::syntax::SyntaxKind;
+
+impl Parser<'_> {
+    /// Parses `: body` where the body is an indented block if the line ends after `:`
+    pub(super) fn parse_colon_body(&mut self) -> PResult<Expr> {
+        if !self.is_op(":") {
+            return Ok(self.missing(ParseErrorKind::ExpectedColon));
+        }
+        self.bump();
+        match self.parse_body()? {
+            Some(body) => Ok(body),
+            None => Ok(self.missing(ParseErrorKind::ExpectedBlock)),
+        }
+    }
+
+    /// Parses `:` followed by an indented block whose lines are parsed by `lines`,
+    /// reporting a missing colon or block
+    pub(super) fn parse_colon_block<T>(&mut self, lines: impl FnOnce(&mut Self, usize) -> Vec<T>) -> Vec<T> {
+        let indent = match self.is_op(":") {
+            true => {
+                self.bump();
+                self.enter_indented().ok_or(ParseErrorKind::ExpectedBlock)
+            }
+            false => Err(ParseErrorKind::ExpectedColon),
+        };
+        match indent {
+            Ok(indent) => lines(self, indent),
+            Err(kind) => {
+                let error = self.error(kind);
+                self.report(error);
+                vec![]
+            }
+        }
+    }
+
+    /// Parses `pattern: body` after `iter for`
+    pub(super) fn parse_for(&mut self, iter: Expr) -> PResult<Expr> {
+        let simple_binder = matches!(self.peek().map(|token| &token.ty), Some(TokenType::Symbol(_)))
+            && self.peek_next().is_some_and(|next| matches!(next.ty, TokenType::Operator) && next.slice == ":");
+        let pattern = if simple_binder {
+            let checkpoint = self.checkpoint();
+            let name = self.parse_ident()?;
+            self.node(checkpoint, SyntaxKind::BindPat);
+            let span = name.span;
+            Pattern { kind: PatternKind::Bind { name, pattern: None, guard: None }, span }
+        } else {
+            self.parse_pattern()?
+        };
+        let body = self.parse_colon_body()?;
+        let span = iter.span.to(body.span);
+        Ok(Expr { kind: ExprKind::For { iter: Box::new(iter), pattern: Box::new(pattern), body: Box::new(body) }, span })
+    }
+
+    /// Parses `while: cond` with optional `then: body` and `else: otherwise` continuations
+    pub(super) fn parse_while(&mut self) -> PResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let start = Span::of(self.bump());
+        let cond = self.parse_colon_body()?;
+        let body = match self.eat_continuation("then") {
+            true => Some(Box::new(self.parse_colon_body()?)),
+            false => None,
+        };
+        let otherwise = match self.eat_continuation("else") {
+            true => Some(Box::new(self.parse_colon_body()?)),
+            false => None,
+        };
+        self.node(checkpoint, SyntaxKind::While);
+        Ok(Expr { kind: ExprKind::While { cond: Box::new(cond), body, otherwise }, span: self.span_from(start) })
+    }
+
+    /// Parses `fn params...: body`
+    pub(super) fn parse_lambda(&mut self) -> PResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let start = Span::of(self.bump());
+        let mut params = vec![];
+        while !self.is_op(":") && self.peek().is_some() {
+            params.push(self.parse_pattern_atom()?);
+        }
+        let body = self.parse_colon_body()?;
+        self.node(checkpoint, SyntaxKind::Lambda);
+        let captures = captures(&params, &body);
+        let generator = self.check_generator(&params, &body);
+        let kind = ExprKind::Lambda { params, body: Box::new(body), captures, generator };
+        Ok(Expr { kind, span: self.span_from(start) })
+    }
+
+    /// Parses `match scrutinee:` followed by indented `pattern [if guard] => body` arms
+    pub(super) fn parse_match(&mut self) -> PResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let start = Span::of(self.bump());
+        let scrutinee = self.parse_expr_before_colon()?;
+        let arms = self.parse_colon_block(|parser, indent| parser.parse_lines(indent, Self::parse_arm, |body| Arm {
+            pattern: Pattern { kind: PatternKind::Error, span: body.span },
+            guard: None,
+            span: body.span,
+            body,
+        }));
+        self.node(checkpoint, SyntaxKind::Match);
+        Ok(Expr { kind: ExprKind::Match { scrutinee: Box::new(scrutinee), arms }, span: self.span_from(start) })
+    }
+
+    fn parse_arm(&mut self) -> PResult<Arm> {
+        let checkpoint = self.checkpoint();
+        let pattern = self.parse_pattern()?;
+        let guard = match self.eat_keyword("if") {
+            true => Some(self.parse_expr()?),
+            false => None,
+        };
+        if !self.is_op("=>") {
+            return Err(self.error(ParseErrorKind::ExpectedArrow));
+        }
+        self.bump();
+        let body = match self.parse_body()? {
+            Some(body) => body,
+            None => self.missing(ParseErrorKind::ExpectedExpression),
+        };
+        self.node(checkpoint, SyntaxKind::Arm);
+        let span = pattern.span.to(body.span);
+        Ok(Arm { pattern, guard, body, span })
+    }
+}
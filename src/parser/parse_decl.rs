@@ -0,0 +1,169 @@
+use crate::lexer::{Token, TokenType};
+use crate::parser::parse::{PResult, Parser};
+use crate::parser::*;
+use crate::syntax::SyntaxKind;
+
+impl Parser<'_> {
+    /// Parses `type Name 'params... := Type`, `type Name 'params...:` followed by constructors
+    /// or `class Name 'params...:` followed by fields, all with an optional `where` clause before the body
+    pub(super) fn parse_type_decl(&mut self) -> PResult<Stmt> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let vis = self.parse_visibility();
+        let class = self.bump().slice == "class";
+        let (name, params) = self.parse_decl_head()?;
+        let where_clause = self.parse_where_clause()?;
+        let body = if class {
+            let fields: Vec<_> = self.parse_colon_block(|parser, indent| {
+                parser.parse_lines(indent, |parser| parser.parse_record_field().map(Some), |_| None)
+            }).into_iter().flatten().collect();
+            self.check_fields(fields.iter().map(|field| &field.name));
+            TypeBody::Record(fields)
+        } else if self.is_op(":=") {
+            self.bump();
+            TypeBody::Alias(self.parse_type()?)
+        } else {
+            let variants = self.parse_colon_block(|parser, indent| {
+                parser.parse_lines(indent, |parser| parser.parse_variant().map(Some), |_| None)
+            });
+            TypeBody::Variants(variants.into_iter().flatten().collect())
+        };
+        self.node(checkpoint, SyntaxKind::TypeDecl);
+        Ok(Stmt::Type(TypeDecl { attrs: vec![], vis, name, params, where_clause, body, span: self.span_from(start) }))
+    }
+
+    /// Parses a trait declaration, or an implementation if `for` follows the trait
+    pub(super) fn parse_trait(&mut self) -> PResult<Stmt> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let vis = self.parse_visibility();
+        self.bump();
+        if !self.is_impl() {
+            let (name, params) = self.parse_decl_head()?;
+            let where_clause = self.parse_where_clause()?;
+            let items = self.parse_colon_block(Self::parse_trait_items);
+            self.node(checkpoint, SyntaxKind::TraitDecl);
+            return Ok(Stmt::Trait(TraitDecl { attrs: vec![], vis, name, params, where_clause, items, span: self.span_from(start) }));
+        }
+
+        if vis != Visibility::Default {
+            self.report(ParseError { kind: ParseErrorKind::ImplVisibility, span: start });
+        }
+        let trait_ref = self.parse_trait_ref()?;
+        if !self.eat_keyword("for") {
+            return Err(self.error(ParseErrorKind::UnexpectedToken));
+        }
+        let ty = self.parse_type()?;
+        let where_clause = self.parse_where_clause()?;
+        let items = self.parse_colon_block(|parser, indent| {
+            let checkpoint = parser.checkpoint();
+            let stmts = parser.parse_block(indent);
+            parser.node(checkpoint, SyntaxKind::Block);
+            stmts
+        });
+        self.node(checkpoint, SyntaxKind::Impl);
+        Ok(Stmt::Impl(ImplDecl { attrs: vec![], trait_ref, ty, where_clause, items, span: self.span_from(start) }))
+    }
+
+    /// Parses `[pub] name params : Type` of a method without default
+    pub(super) fn parse_signature(&mut self) -> PResult<Signature> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let vis = self.parse_visibility();
+        let name = self.parse_ident()?;
+        let mut params = vec![];
+        while !self.is_op(":") && self.peek().is_some() {
+            params.push(self.parse_pattern_atom()?);
+        }
+        if !self.is_op(":") {
+            return Err(self.error(ParseErrorKind::ExpectedColon));
+        }
+        self.bump();
+        let ty = self.parse_type()?;
+        self.node(checkpoint, SyntaxKind::Signature);
+        Ok(Signature { attrs: vec![], vis, name, params, ty, span: self.span_from(start) })
+    }
+
+    /// Parses the declared `Name` and its `'params...`
+    fn parse_decl_head(&mut self) -> PResult<(Ident, Vec<Ident>)> {
+        let name = self.parse_ident()?;
+        let mut params = vec![];
+        while let Some(Token { ty: TokenType::Argument(param), .. }) = self.peek() {
+            let token = self.bump();
+            params.push(Ident { name: param.clone(), span: Span::of(token) });
+        }
+        if self.at_type_atom() {
+            return Err(self.error(ParseErrorKind::ExpectedTypeParam));
+        }
+        Ok((name, params))
+    }
+
+    /// Parses `where Type is Trait + ..., ...` if present
+    fn parse_where_clause(&mut self) -> PResult<Vec<Predicate>> {
+        let checkpoint = self.checkpoint();
+        if !self.eat_keyword("where") {
+            return Ok(vec![]);
+        }
+        let mut predicates = vec![self.parse_predicate()?];
+        while self.peek().is_some_and(|token| matches!(token.ty, TokenType::Comma)) {
+            self.bump();
+            predicates.push(self.parse_predicate()?);
+        }
+        self.node(checkpoint, SyntaxKind::WhereClause);
+        Ok(predicates)
+    }
+
+    fn parse_predicate(&mut self) -> PResult<Predicate> {
+        let checkpoint = self.checkpoint();
+        let ty = self.parse_type()?;
+        if !self.eat_keyword("is") {
+            return Err(self.error(ParseErrorKind::UnexpectedToken));
+        }
+        let mut bounds = vec![self.parse_trait_ref()?];
+        while self.is_op("+") {
+            self.bump();
+            bounds.push(self.parse_trait_ref()?);
+        }
+        self.node(checkpoint, SyntaxKind::Predicate);
+        Ok(Predicate { span: self.span_from(ty.span), ty, bounds })
+    }
+
+    /// Parses `Name args...` naming a trait
+    fn parse_trait_ref(&mut self) -> PResult<TraitRef> {
+        let checkpoint = self.checkpoint();
+        let name = self.parse_ident()?;
+        let mut args = vec![];
+        while self.at_type_atom() {
+            args.push(self.parse_type_atom()?);
+        }
+        self.node(checkpoint, SyntaxKind::TraitRef);
+        Ok(TraitRef { span: self.span_from(name.span), name, args })
+    }
+
+    /// Parses `Name fields...` of a sum type
+    fn parse_variant(&mut self) -> PResult<Variant> {
+        let checkpoint = self.checkpoint();
+        let name = self.parse_ident()?;
+        let mut fields = vec![];
+        while self.at_type_atom() {
+            fields.push(self.parse_type_atom()?);
+        }
+        self.node(checkpoint, SyntaxKind::Variant);
+        Ok(Variant { span: self.span_from(name.span), name, fields })
+    }
+
+    /// Parses `[pub] name: Type` of a class
+    fn parse_record_field(&mut self) -> PResult<RecordField> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let vis = self.parse_visibility();
+        let name = self.parse_ident()?;
+        if !self.is_op(":") {
+            return Err(self.error(ParseErrorKind::ExpectedColon));
+        }
+        self.bump();
+        let ty = self.parse_type()?;
+        self.node(checkpoint, SyntaxKind::RecordField);
+        Ok(RecordField { vis, name, ty, span: self.span_from(start) })
+    }
+}
@@ -0,0 +1,432 @@
+use crate::lexer::{StringValue, Token, TokenType};
+use crate::parser::parse::{PResult, Parser};
+use crate::parser::*;
+use crate::syntax::SyntaxKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Assoc {
+    Left,
+    Right,
+}
+
+/// `place <- value` and the `op<-` family
+pub(crate) const ASSIGN: u8 = 0;
+pub(crate) const OR: u8 = 1;
+pub(crate) const AND: u8 = 2;
+pub(crate) const NOT: u8 = 3;
+pub(crate) const COMPARE: u8 = 4;
+/// Spaced `.method`, `for` and `as`
+pub(crate) const CHAIN: u8 = 5;
+/// `..` and `..=`, binding tighter than a method chain so that `0..2 .iter` iterates the range
+pub(crate) const RANGE: u8 = 6;
+pub(crate) const ADD: u8 = 7;
+pub(crate) const MUL: u8 = 8;
+pub(crate) const POW: u8 = 9;
+
+fn binary_precedence(token: &Token) -> Option<(u8, Assoc)> {
+    match &token.ty {
+        TokenType::Keyword(keyword) => match keyword.as_str() {
+            "for" | "as" => Some((CHAIN, Assoc::Left)),
+            "or" | "and" => operator_precedence(keyword),
+            _ => None,
+        },
+        TokenType::Operator => operator_precedence(&token.slice),
+        _ => None,
+    }
+}
+
+/// Precedence of a binary operator, `and` or `or`, operators are classified by their first character.
+/// `<-` after nothing, `.` or another binary operator makes an assignment
+pub(crate) fn operator_precedence(op: &str) -> Option<(u8, Assoc)> {
+    match op {
+        ":" | ":=" | "::=" | "=>" => None,
+        op if assignment_operator(op).is_some() => Some((ASSIGN, Assoc::Right)),
+        "." => Some((CHAIN, Assoc::Left)),
+        ".." | "..=" => Some((RANGE, Assoc::Left)),
+        "or" | "||" => Some((OR, Assoc::Left)),
+        "and" | "&&" => Some((AND, Assoc::Left)),
+        op if op.starts_with("**") => Some((POW, Assoc::Right)),
+        op => match op.chars().next() {
+            Some('=' | '<' | '>' | '|' | '&' | '$' | '!' | '~') => Some((COMPARE, Assoc::Left)),
+            Some('*' | '/' | '%') => Some((MUL, Assoc::Left)),
+            _ => Some((ADD, Assoc::Left)),
+        },
+    }
+}
+
+/// Operator `op` updates the place with, `""` for a plain `<-` and `"."` for `.<-`
+pub(crate) fn assignment_operator(op: &str) -> Option<&str> {
+    op.strip_suffix("<-")
+        .filter(|inner| inner.is_empty() || !inner.ends_with("<-") && operator_precedence(inner).is_some())
+}
+
+/// Whether `expr` is a binding or a field of a place, which assignments can update
+pub(crate) fn is_place(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Name(_) => true,
+        ExprKind::Method { receiver: Some(receiver), method, args } => {
+            args.is_empty() && matches!(method.kind, ExprKind::Name(_)) && is_place(receiver)
+        }
+        _ => false,
+    }
+}
+
+impl Parser<'_> {
+    pub(super) fn parse_expr(&mut self) -> PResult<Expr> {
+        self.parse_binary(ASSIGN)
+    }
+
+    pub(super) fn parse_binary(&mut self, min_prec: u8) -> PResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let lhs = self.parse_unary()?;
+        self.parse_binary_from(checkpoint, lhs, min_prec)
+    }
+
+    /// Parses the rest of an expression whose first operand is `lhs` parsed since `checkpoint`
+    pub(super) fn parse_binary_rest(&mut self, checkpoint: usize, lhs: Expr) -> PResult<Expr> {
+        self.parse_binary_from(checkpoint, lhs, ASSIGN)
+    }
+
+    fn parse_binary_from(&mut self, checkpoint: usize, mut lhs: Expr, min_prec: u8) -> PResult<Expr> {
+        while let Some(token) = self.peek() {
+            let Some((prec, assoc)) = binary_precedence(token) else { break };
+            if prec < min_prec {
+                break;
+            }
+            self.bump();
+
+            if let TokenType::Keyword(keyword) = &token.ty && keyword == "as" {
+                let ty = self.parse_type()?;
+                self.node(checkpoint, SyntaxKind::Cast);
+                let span = lhs.span.to(ty.span);
+                lhs = Expr { kind: ExprKind::Cast { expr: Box::new(lhs), ty }, span };
+                continue;
+            }
+            if let TokenType::Keyword(_) = token.ty && prec == CHAIN {
+                lhs = self.parse_for(lhs)?;
+                self.node(checkpoint, SyntaxKind::For);
+                continue;
+            }
+            if prec == ASSIGN {
+                lhs = self.parse_assign(checkpoint, lhs, token)?;
+                continue;
+            }
+            if prec == RANGE {
+                lhs = self.parse_range(checkpoint, Some(lhs), token)?;
+                continue;
+            }
+            if prec == CHAIN {
+                let (method, args) = self.parse_method()?;
+                let span = lhs.span.to(self.last_span());
+                lhs = Expr { kind: ExprKind::Method { receiver: Some(Box::new(lhs)), method, args }, span };
+                self.node(checkpoint, SyntaxKind::Method);
+                continue;
+            }
+
+            let op = Ident { name: token.slice.clone(), span: Span::of(token) };
+            let rhs = self.parse_binary(if assoc == Assoc::Left { prec + 1 } else { prec })?;
+            let span = lhs.span.to(rhs.span);
+            lhs = Expr { kind: ExprKind::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) }, span };
+            self.node(checkpoint, SyntaxKind::Binary);
+        }
+        Ok(lhs)
+    }
+
+    /// Parses the rest of an assignment to `place` after its operator `token`, desugaring the update:
+    /// `place op<- rhs` assigns `place op rhs` and `place .<- method args...` assigns `place .method args...`
+    fn parse_assign(&mut self, checkpoint: usize, place: Expr, token: &Token) -> PResult<Expr> {
+        if !is_place(&place) {
+            self.report(ParseError { kind: ParseErrorKind::NotAPlace, span: place.span });
+        }
+        let op = Ident { name: token.slice.clone(), span: Span::of(token) };
+        let value = match assignment_operator(&token.slice) {
+            Some("") => self.parse_binary(ASSIGN)?,
+            Some(".") => {
+                let (method, args) = self.parse_method()?;
+                let span = place.span.to(self.last_span());
+                Expr { kind: ExprKind::Method { receiver: Some(Box::new(place.clone())), method, args }, span }
+            }
+            _ => {
+                let rhs = self.parse_binary(ASSIGN)?;
+                let name = token.slice.slice(..token.slice.len() - 2);
+                let inner = Ident { name, span: Span { end_num: op.span.end_num - 2, ..op.span } };
+                let span = place.span.to(rhs.span);
+                Expr { kind: ExprKind::Binary { op: inner, lhs: Box::new(place.clone()), rhs: Box::new(rhs) }, span }
+            }
+        };
+        self.node(checkpoint, SyntaxKind::Assign);
+        let span = place.span.to(self.last_span());
+        Ok(Expr { kind: ExprKind::Assign { place: Box::new(place), op, value: Box::new(value) }, span })
+    }
+
+    /// Parses `method args...` after a spaced `.`
+    fn parse_method(&mut self) -> PResult<(Box<Expr>, Vec<Expr>)> {
+        let method = self.parse_postfix()?;
+        let mut args = vec![];
+        while self.at_atom() {
+            args.push(self.parse_postfix()?);
+        }
+        Ok((Box::new(method), args))
+    }
+
+    fn parse_unary(&mut self) -> PResult<Expr> {
+        let Some(token) = self.peek() else { return self.parse_application() };
+        let start = Span::of(token);
+        let checkpoint = self.checkpoint();
+        match &token.ty {
+            TokenType::Operator if token.slice == "." => {
+                self.bump();
+                let (method, args) = self.parse_method()?;
+                self.node(checkpoint, SyntaxKind::Method);
+                Ok(Expr { kind: ExprKind::Method { receiver: None, method, args }, span: self.span_from(start) })
+            }
+            TokenType::Operator if self.at_range_op() => {
+                self.bump();
+                self.parse_range(checkpoint, None, token)
+            }
+            TokenType::Operator if binary_precedence(token).is_some() => {
+                self.bump();
+                let op = Ident { name: token.slice.clone(), span: start };
+                let operand = self.parse_unary()?;
+                self.node(checkpoint, SyntaxKind::Unary);
+                let span = start.to(operand.span);
+                Ok(Expr { kind: ExprKind::Unary { op, operand: Box::new(operand) }, span })
+            }
+            TokenType::Keyword(keyword) if keyword == "not" => {
+                self.bump();
+                let op = Ident { name: keyword.clone(), span: start };
+                let operand = self.parse_binary(NOT)?;
+                self.node(checkpoint, SyntaxKind::Unary);
+                let span = start.to(operand.span);
+                Ok(Expr { kind: ExprKind::Unary { op, operand: Box::new(operand) }, span })
+            }
+            // like `fn`, `yield` takes the rest of the expression
+            TokenType::Keyword(keyword) if keyword == "yield" => {
+                self.bump();
+                let value = self.parse_expr()?;
+                self.node(checkpoint, SyntaxKind::Yield);
+                let span = start.to(value.span);
+                Ok(Expr { kind: ExprKind::Yield(Box::new(value)), span })
+            }
+            _ => self.parse_application(),
+        }
+    }
+
+    /// Parses `func args...`, `func args...: block` passes the block as the last argument
+    fn parse_application(&mut self) -> PResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let func = self.parse_postfix()?;
+        let mut args = vec![];
+        while self.at_atom() {
+            args.push(self.parse_postfix()?);
+        }
+        if self.colon_args() && self.is_op(":") {
+            args.push(self.parse_colon_body()?);
+        }
+        if args.is_empty() {
+            return Ok(func);
+        }
+        self.node(checkpoint, SyntaxKind::Apply);
+        let span = func.span.to(self.last_span());
+        Ok(Expr { kind: ExprKind::Apply { func: Box::new(func), args }, span })
+    }
+
+    /// Whether the next token starts an atom
+    pub(super) fn at_atom(&mut self) -> bool {
+        match self.peek().map(|token| &token.ty) {
+            Some(
+                | TokenType::Symbol(_) | TokenType::Argument(_) | TokenType::Number { .. }
+                | TokenType::String { .. } | TokenType::Character { .. } | TokenType::Open
+                | TokenType::Sharp | TokenType::Error
+            ) => true,
+            Some(TokenType::Keyword(keyword)) => matches!(keyword.as_str(), "_" | "while" | "fn" | "match"),
+            _ => false,
+        }
+    }
+
+    /// Parses an atom followed by `.method` without spaces around the dot
+    pub(super) fn parse_postfix(&mut self) -> PResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let mut expr = self.parse_atom()?;
+        while self.is_adjacent() && self.is_op(".") {
+            self.bump();
+            let method = self.parse_atom()?;
+            self.node(checkpoint, SyntaxKind::Method);
+            let span = expr.span.to(method.span);
+            expr = Expr { kind: ExprKind::Method { receiver: Some(Box::new(expr)), method: Box::new(method), args: vec![] }, span };
+        }
+        Ok(expr)
+    }
+
+    pub(super) fn parse_atom(&mut self) -> PResult<Expr> {
+        let Some(token) = self.peek() else { return Ok(self.missing(ParseErrorKind::ExpectedExpression)) };
+        let kind = match &token.ty {
+            TokenType::Close | TokenType::Comma | TokenType::Semicolon => {
+                return Ok(self.missing(ParseErrorKind::ExpectedExpression));
+            }
+            // already reported with the lexer errors
+            TokenType::Error => ExprKind::Error,
+            TokenType::Symbol(name) => ExprKind::Name(name.clone()),
+            TokenType::Argument(name) => ExprKind::Argument(name.clone()),
+            TokenType::Keyword(keyword) if keyword == "_" => ExprKind::Hole,
+            TokenType::Number { .. } | TokenType::String { .. } | TokenType::Character { .. } => {
+                ExprKind::Literal(literal(token).unwrap())
+            }
+            TokenType::Open => return self.parse_group(),
+            TokenType::Sharp => return self.parse_annotated(),
+            TokenType::Keyword(keyword) => match keyword.as_str() {
+                "while" => return self.parse_while(),
+                "fn" => return self.parse_lambda(),
+                "match" => return self.parse_match(),
+                "then" | "else" => return Err(self.error(ParseErrorKind::UnexpectedContinuation)),
+                _ => return Err(self.error(ParseErrorKind::ExpectedExpression)),
+            },
+            _ => return Err(self.error(ParseErrorKind::ExpectedExpression)),
+        };
+        let checkpoint = self.checkpoint();
+        self.bump();
+        self.node(checkpoint, match kind {
+            ExprKind::Error => SyntaxKind::Error,
+            ExprKind::Name(_) => SyntaxKind::Name,
+            ExprKind::Argument(_) => SyntaxKind::Argument,
+            ExprKind::Hole => SyntaxKind::Hole,
+            _ => SyntaxKind::Literal,
+        });
+        Ok(Expr { kind, span: Span::of(token) })
+    }
+
+    /// Parses `()`, `(expr)`, a tuple `(first, name := second...)` or a list `[first, second...]`
+    fn parse_group(&mut self) -> PResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let open = self.bump();
+        let start = Span::of(open);
+        if open.slice != "(" && open.slice != "[" {
+            return Err(ParseError { kind: ParseErrorKind::UnexpectedToken, span: start });
+        }
+
+        let depth = self.enter_group();
+        let mut fields = vec![];
+        let mut trailing_comma = false;
+        while !self.at_close() && self.peek().is_some() {
+            let field = self.parse_field().unwrap_or_else(|error| Field { name: None, value: self.recover(depth, error) });
+            fields.push(field);
+            trailing_comma = self.peek().is_some_and(|token| matches!(token.ty, TokenType::Comma));
+            if !trailing_comma {
+                break;
+            }
+            self.bump();
+        }
+        self.expect_close(open);
+        self.leave_group();
+
+        let (kind, syntax_kind) = match (open.slice.as_str(), &fields[..], trailing_comma) {
+            ("[", ..) => {
+                let elements = fields.into_iter()
+                    .map(|Field { name, value }| {
+                        if let Some(name) = name {
+                            self.report(ParseError { kind: ParseErrorKind::NamedListElement, span: name.span });
+                        }
+                        value
+                    })
+                    .collect();
+                (ExprKind::List(elements), SyntaxKind::List)
+            }
+            (_, [], _) => (ExprKind::Unit, SyntaxKind::Unit),
+            (_, [Field { name: None, .. }], false) => (fields.pop().unwrap().value.kind, SyntaxKind::Paren),
+            _ => {
+                self.check_fields(fields.iter().filter_map(|field| field.name.as_ref()));
+                (ExprKind::Tuple(fields), SyntaxKind::Tuple)
+            }
+        };
+        self.node(checkpoint, syntax_kind);
+        Ok(Expr { kind, span: self.span_from(start) })
+    }
+
+    /// Parses `name := value` or `value`
+    fn parse_field(&mut self) -> PResult<Field> {
+        let named = matches!(self.peek().map(|token| &token.ty), Some(TokenType::Symbol(_)))
+            && self.peek_next().is_some_and(|next| matches!(next.ty, TokenType::Operator) && next.slice == ":=");
+        if !named {
+            return Ok(Field { name: None, value: self.parse_expr()? });
+        }
+        let checkpoint = self.checkpoint();
+        let name = self.parse_ident()?;
+        self.bump();
+        let value = self.parse_expr()?;
+        self.node(checkpoint, SyntaxKind::Field);
+        Ok(Field { name: Some(name), value })
+    }
+
+    /// Reports field names used by earlier fields
+    pub(super) fn check_fields<'f>(&mut self, names: impl IntoIterator<Item = &'f Ident>) {
+        let mut seen: Vec<&Ident> = vec![];
+        for name in names {
+            match seen.iter().find(|first| first.name == name.name) {
+                Some(first) => {
+                    let kind = ParseErrorKind::DuplicateField { first: first.span };
+                    self.report(ParseError { kind, span: name.span });
+                }
+                None => seen.push(name),
+            }
+        }
+    }
+
+    pub(super) fn at_close(&mut self) -> bool {
+        matches!(self.peek(), Some(Token { ty: TokenType::Close, .. }))
+    }
+
+    /// Consumes the bracket closing `open`, skipping anything before it
+    pub(super) fn expect_close(&mut self, open: &Token) {
+        let depth = self.layout_depth();
+        if !self.at_close() && self.peek().is_some() {
+            let error = self.error(ParseErrorKind::UnexpectedToken);
+            self.recover(depth, error);
+        }
+        let Some(token) = self.peek() else {
+            return self.report(ParseError { kind: ParseErrorKind::UnclosedBracket, span: Span::of(open) });
+        };
+        if closing(&open.slice) != Some(token.slice.as_str()) {
+            self.report(ParseError { kind: ParseErrorKind::MismatchedBracket, span: Span::of(token) });
+        }
+        self.bump();
+    }
+}
+
+/// Literal value of a number, string or character token
+pub(super) fn literal(token: &Token) -> Option<Literal> {
+    match &token.ty {
+        TokenType::Number { start_pos, dot_pos, exp_pos, suf_pos, .. } => {
+            let relative = |pos: &Option<usize>| pos.map(|pos| pos - token.num);
+            Some(Literal::Number(NumberLiteral {
+                slice: token.slice.clone(),
+                start_pos: start_pos - token.num,
+                dot_pos: relative(dot_pos),
+                exp_pos: relative(exp_pos),
+                suf_pos: relative(suf_pos),
+            }))
+        }
+        TokenType::String { value, prefix_len, suffix_len, .. } => {
+            // the suffix of a multi-line string ends its last line
+            let end = match value {
+                StringValue::MultiLine { last_line_slice, .. } => last_line_slice,
+                _ => &token.slice,
+            };
+            Some(Literal::String(StringLiteral {
+                prefix: token.slice.slice(..*prefix_len),
+                value: value.clone(),
+                suffix: end.slice(end.len() - suffix_len..),
+            }))
+        }
+        TokenType::Character { value, .. } => Some(Literal::Char(*value)),
+        _ => None,
+    }
+}
+
+pub(super) fn closing(open: &str) -> Option<&'static str> {
+    match open {
+        "(" => Some(")"),
+        "[" => Some("]"),
+        "{" => Some("}"),
+        _ => None,
+    }
+}
@@ -0,0 +1,93 @@
+use crate::lexer::TokenType;
+use crate::parser::parse::{PResult, Parser};
+use crate::parser::*;
+use crate::syntax::SyntaxKind;
+
+impl Parser<'_> {
+    pub(super) fn parse_visibility(&mut self) -> Visibility {
+        if self.eat_keyword("pub") {
+            Visibility::Pub
+        } else if self.eat_keyword("priv") {
+            Visibility::Priv
+        } else {
+            Visibility::Default
+        }
+    }
+
+    /// Parses `[pub] mod name`
+    pub(super) fn parse_mod(&mut self) -> PResult<Stmt> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let vis = self.parse_visibility();
+        self.bump();
+        let name = self.parse_ident()?;
+        self.node(checkpoint, SyntaxKind::Mod);
+        Ok(Stmt::Mod(ModDecl { attrs: vec![], vis, name, span: self.span_from(start) }))
+    }
+
+    /// Parses `[pub] use tree`
+    pub(super) fn parse_use(&mut self) -> PResult<Stmt> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let vis = self.parse_visibility();
+        self.bump();
+        let tree = self.parse_use_tree()?;
+        self.node(checkpoint, SyntaxKind::Use);
+        Ok(Stmt::Use(UseDecl { attrs: vec![], vis, tree, span: self.span_from(start) }))
+    }
+
+    /// Parses `a.b [as c]`, `a.*` or `a.(tree, ...)`
+    fn parse_use_tree(&mut self) -> PResult<UseTree> {
+        let checkpoint = self.checkpoint();
+        let start = self.peek().map(Span::of).unwrap_or_default();
+        let mut path = vec![];
+        let kind = loop {
+            if self.peek().is_some_and(|token| matches!(token.ty, TokenType::Open)) {
+                break UseKind::Group(self.parse_use_group()?);
+            }
+            path.push(self.parse_ident()?);
+            if self.is_op(".*") {
+                self.bump();
+                break UseKind::Glob;
+            }
+            if !self.is_op(".") {
+                let rename = match self.eat_keyword("as") {
+                    true => Some(self.parse_ident()?),
+                    false => None,
+                };
+                break UseKind::Single { rename };
+            }
+            self.bump();
+            if self.is_op("*") {
+                self.bump();
+                break UseKind::Glob;
+            }
+        };
+        self.node(checkpoint, SyntaxKind::UseTree);
+        Ok(UseTree { path, kind, span: self.span_from(start) })
+    }
+
+    fn parse_use_group(&mut self) -> PResult<Vec<UseTree>> {
+        let open = self.bump();
+        if open.slice != "(" {
+            return Err(ParseError { kind: ParseErrorKind::UnexpectedToken, span: Span::of(open) });
+        }
+        let depth = self.enter_group();
+        let mut trees = vec![];
+        while !self.at_close() && self.peek().is_some() {
+            match self.parse_use_tree() {
+                Ok(tree) => trees.push(tree),
+                Err(error) => {
+                    self.recover(depth, error);
+                }
+            }
+            if !self.peek().is_some_and(|token| matches!(token.ty, TokenType::Comma)) {
+                break;
+            }
+            self.bump();
+        }
+        self.expect_close(open);
+        self.leave_group();
+        Ok(trees)
+    }
+}
@@ -0,0 +1,176 @@
+use crate::lexer::{Token, TokenType};
+use crate::parser::parse::{PResult, Parser};
+use crate::parser::parse_expr::literal;
+use crate::parser::*;
+use crate::syntax::SyntaxKind;
+
+impl Parser<'_> {
+    /// Parses a pattern: `Name args...`, `'name = pattern`, `?'name [= default]` or a pattern atom
+    pub(super) fn parse_pattern(&mut self) -> PResult<Pattern> {
+        let Some(token) = self.peek() else { return Err(self.error(ParseErrorKind::ExpectedPattern)) };
+        let start = Span::of(token);
+        let checkpoint = self.checkpoint();
+        match &token.ty {
+            TokenType::Symbol(_) => {
+                let name = self.parse_ident()?;
+                let mut args = vec![];
+                while self.at_pattern_atom() {
+                    args.push(self.parse_pattern_atom()?);
+                }
+                self.node(checkpoint, SyntaxKind::ConstructorPat);
+                Ok(Pattern { kind: PatternKind::Constructor { name, args }, span: self.span_from(start) })
+            }
+            TokenType::Argument(_) if self.peek_next().is_some_and(|next| is_op(next, "=")) => {
+                let name = self.parse_binder()?;
+                self.bump();
+                let pattern = self.parse_pattern()?;
+                self.node(checkpoint, SyntaxKind::BindPat);
+                let span = start.to(pattern.span);
+                Ok(Pattern { kind: PatternKind::Bind { name, pattern: Some(Box::new(pattern)), guard: None }, span })
+            }
+            TokenType::Operator if token.slice == "?" => {
+                self.bump();
+                let name = self.parse_binder()?;
+                let default = if self.is_op("=") {
+                    self.bump();
+                    Some(Box::new(self.parse_expr()?))
+                } else {
+                    None
+                };
+                self.node(checkpoint, SyntaxKind::OptionalPat);
+                Ok(Pattern { kind: PatternKind::Optional { name, default }, span: self.span_from(start) })
+            }
+            _ => self.parse_pattern_atom(),
+        }
+    }
+
+    /// Whether the next token starts a pattern atom
+    pub(super) fn at_pattern_atom(&mut self) -> bool {
+        match self.peek() {
+            Some(token @ Token { ty: TokenType::Operator, .. }) => matches!(token.slice.as_str(), "?" | "-" | ".." | "..="),
+            _ => self.at_atom(),
+        }
+    }
+
+    /// Parses a pattern which can be a parameter or a constructor argument without brackets
+    pub(super) fn parse_pattern_atom(&mut self) -> PResult<Pattern> {
+        let checkpoint = self.checkpoint();
+        if self.at_range_op() {
+            return self.parse_range_pattern(checkpoint, None);
+        }
+        let pattern = self.parse_simple_pattern()?;
+        if matches!(pattern.kind, PatternKind::Literal { .. }) && self.at_range_op() {
+            return self.parse_range_pattern(checkpoint, Some(pattern));
+        }
+        Ok(pattern)
+    }
+
+    /// Parses a pattern atom other than a range
+    pub(super) fn parse_simple_pattern(&mut self) -> PResult<Pattern> {
+        let Some(token) = self.peek() else { return Err(self.error(ParseErrorKind::ExpectedPattern)) };
+        let start = Span::of(token);
+        let checkpoint = self.checkpoint();
+        let kind = match &token.ty {
+            TokenType::Keyword(keyword) if keyword == "_" => PatternKind::Wildcard,
+            TokenType::Argument(name) => PatternKind::Bind {
+                name: Ident { name: name.clone(), span: start },
+                pattern: None,
+                guard: None,
+            },
+            TokenType::Symbol(name) => PatternKind::Constructor {
+                name: Ident { name: name.clone(), span: start },
+                args: vec![],
+            },
+            TokenType::Number { .. } | TokenType::String { .. } | TokenType::Character { .. } => {
+                PatternKind::Literal { value: literal(token).unwrap(), negative: false }
+            }
+            TokenType::Operator if token.slice == "-" => {
+                self.bump();
+                let number = self.peek().filter(|next| self.is_adjacent() && matches!(next.ty, TokenType::Number { .. }));
+                let Some(number) = number else { return Err(self.error(ParseErrorKind::ExpectedPattern)) };
+                self.bump();
+                self.node(checkpoint, SyntaxKind::LiteralPat);
+                let kind = PatternKind::Literal { value: literal(number).unwrap(), negative: true };
+                return Ok(Pattern { kind, span: start.to(Span::of(number)) });
+            }
+            TokenType::Operator if token.slice == "?" => {
+                self.bump();
+                let name = self.parse_binder()?;
+                self.node(checkpoint, SyntaxKind::OptionalPat);
+                let span = start.to(name.span);
+                return Ok(Pattern { kind: PatternKind::Optional { name, default: None }, span });
+            }
+            TokenType::Open => return self.parse_pattern_group(),
+            _ => return Err(self.error(ParseErrorKind::ExpectedPattern)),
+        };
+        self.bump();
+        self.node(checkpoint, match kind {
+            PatternKind::Wildcard => SyntaxKind::WildcardPat,
+            PatternKind::Bind { .. } => SyntaxKind::BindPat,
+            PatternKind::Constructor { .. } => SyntaxKind::ConstructorPat,
+            _ => SyntaxKind::LiteralPat,
+        });
+        Ok(Pattern { kind, span: start })
+    }
+
+    fn parse_binder(&mut self) -> PResult<Ident> {
+        match self.peek() {
+            Some(Token { ty: TokenType::Argument(name), .. }) => {
+                let token = self.bump();
+                Ok(Ident { name: name.clone(), span: Span::of(token) })
+            }
+            _ => Err(self.error(ParseErrorKind::ExpectedBinder)),
+        }
+    }
+
+    /// Parses `()`, `('name guard...)`, `(pattern)`, `(first, second...)` or `[first, second...]`
+    fn parse_pattern_group(&mut self) -> PResult<Pattern> {
+        let checkpoint = self.checkpoint();
+        let open = self.bump();
+        let start = Span::of(open);
+        if open.slice != "(" && open.slice != "[" {
+            return Err(ParseError { kind: ParseErrorKind::UnexpectedToken, span: start });
+        }
+
+        let depth = self.enter_group();
+        let guarded = matches!(self.peek().map(|token| &token.ty), Some(TokenType::Argument(_)))
+            && self.peek_next().is_some_and(|next| matches!(next.ty, TokenType::Operator) && !is_op(next, "="));
+        let (kind, syntax_kind) = if open.slice == "(" && guarded {
+            let binder_checkpoint = self.checkpoint();
+            let name = self.parse_binder()?;
+            self.node(binder_checkpoint, SyntaxKind::Argument);
+            let binder = Expr { kind: ExprKind::Argument(name.name.clone()), span: name.span };
+            let guard = self.parse_binary_rest(binder_checkpoint, binder).unwrap_or_else(|error| self.recover(depth, error));
+            (PatternKind::Bind { name, pattern: None, guard: Some(Box::new(guard)) }, SyntaxKind::GuardPat)
+        } else {
+            let mut elements = vec![];
+            let mut trailing_comma = false;
+            while !self.at_close() && self.peek().is_some() {
+                let element = self.parse_pattern().unwrap_or_else(|error| {
+                    let Expr { span, .. } = self.recover(depth, error);
+                    Pattern { kind: PatternKind::Error, span }
+                });
+                elements.push(element);
+                trailing_comma = self.peek().is_some_and(|token| matches!(token.ty, TokenType::Comma));
+                if !trailing_comma {
+                    break;
+                }
+                self.bump();
+            }
+            match (open.slice.as_str(), elements.len(), trailing_comma) {
+                ("[", ..) => (PatternKind::List(elements), SyntaxKind::ListPat),
+                (_, 0, _) => (PatternKind::Unit, SyntaxKind::UnitPat),
+                (_, 1, false) => (elements.pop().unwrap().kind, SyntaxKind::ParenPat),
+                _ => (PatternKind::Tuple(elements), SyntaxKind::TuplePat),
+            }
+        };
+        self.expect_close(open);
+        self.leave_group();
+        self.node(checkpoint, syntax_kind);
+        Ok(Pattern { kind, span: self.span_from(start) })
+    }
+}
+
+fn is_op(token: &Token, op: &str) -> bool {
+    matches!(token.ty, TokenType::Operator) && token.slice == op
+}
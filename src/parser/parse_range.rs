@@ -0,0 +1,133 @@
+use crate::lexer::{Token, TokenType};
+use crate::parser::parse::{PResult, Parser};
+use crate::parser::parse_expr::RANGE;
+use crate::parser::*;
+use crate::syntax::SyntaxKind;
+
+impl Parser<'_> {
+    pub(super) fn at_range_op(&mut self) -> bool {
+        self.is_op("..") || self.is_op("..=")
+    }
+
+    /// Parses the rest of a range after its operator `token`, a range without an end is open
+    pub(super) fn parse_range(&mut self, checkpoint: usize, start: Option<Expr>, token: &Token) -> PResult<Expr> {
+        let inclusive = token.slice == "..=";
+        let end = match self.at_atom() || self.is_op("-") {
+            true => Some(self.parse_binary(RANGE + 1)?),
+            false => None,
+        };
+        self.node(checkpoint, SyntaxKind::Range);
+        let first = start.as_ref().map_or(Span::of(token), |start| start.span);
+        let span = first.to(self.last_span());
+        if inclusive && end.is_none() {
+            self.report(ParseError { kind: ParseErrorKind::InclusiveRangeWithoutEnd, span });
+        }
+        self.check_range_bounds(expr_bound(start.as_ref()), expr_bound(end.as_ref()));
+        Ok(Expr { kind: ExprKind::Range { start: start.map(Box::new), end: end.map(Box::new), inclusive }, span })
+    }
+
+    /// Parses the rest of a range pattern after its `start`, the bounds are literals
+    pub(super) fn parse_range_pattern(&mut self, checkpoint: usize, start: Option<Pattern>) -> PResult<Pattern> {
+        let op = self.bump();
+        let inclusive = op.slice == "..=";
+        let at_literal = matches!(
+            self.peek().map(|token| &token.ty),
+            Some(TokenType::Number { .. } | TokenType::String { .. } | TokenType::Character { .. }),
+        );
+        let end = match at_literal || self.is_op("-") {
+            true => Some(self.parse_simple_pattern()?),
+            false => None,
+        };
+        self.node(checkpoint, SyntaxKind::RangePat);
+        let span = start.as_ref().map_or(Span::of(op), |start| start.span).to(self.last_span());
+        if inclusive && end.is_none() {
+            self.report(ParseError { kind: ParseErrorKind::InclusiveRangeWithoutEnd, span });
+        }
+        let (start_bound, end_bound) = (pattern_bound(start.as_ref()), pattern_bound(end.as_ref()));
+        if self.check_range_bounds(start_bound, end_bound)
+            && let (Some((start, _)), Some((end, _))) = (start_bound, end_bound)
+            && let (Some(start), Some(end)) = (bound_value(start), bound_value(end))
+            && (start > end || start == end && !inclusive)
+        {
+            self.report(ParseError { kind: ParseErrorKind::EmptyRangePattern, span });
+        }
+        let kind = PatternKind::Range { start: start.map(Box::new), end: end.map(Box::new), inclusive };
+        Ok(Pattern { kind, span })
+    }
+
+    /// Reports bounds whose literals can't bound a range or differ in type, returns whether they are fine
+    fn check_range_bounds(&mut self, start: Option<(LiteralBound, Span)>, end: Option<(LiteralBound, Span)>) -> bool {
+        let mut valid = true;
+        for (bound, span) in [start, end].into_iter().flatten() {
+            if let (Literal::String(_), _) = bound {
+                self.report(ParseError { kind: ParseErrorKind::InvalidRangeBound { ty: literal_type(bound.0) }, span });
+                valid = false;
+            }
+        }
+        if valid && let (Some((start, _)), Some((end, end_span))) = (start, end) && !compatible(start.0, end.0) {
+            let kind = ParseErrorKind::RangeBoundMismatch { start: literal_type(start.0), end: literal_type(end.0) };
+            self.report(ParseError { kind, span: end_span });
+            valid = false;
+        }
+        valid
+    }
+}
+
+/// Literal and whether it is negated
+type LiteralBound<'l> = (&'l Literal, bool);
+
+/// Literal of a range bound, `-number` included, with its span
+fn expr_bound(bound: Option<&Expr>) -> Option<(LiteralBound<'_>, Span)> {
+    let bound = bound?;
+    let literal = match &bound.kind {
+        ExprKind::Literal(literal) => (literal, false),
+        ExprKind::Unary { op, operand } if op.name == "-" => match &operand.kind {
+            ExprKind::Literal(literal @ Literal::Number(_)) => (literal, true),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some((literal, bound.span))
+}
+
+fn pattern_bound(bound: Option<&Pattern>) -> Option<(LiteralBound<'_>, Span)> {
+    match bound? {
+        Pattern { kind: PatternKind::Literal { value, negative }, span } => Some(((value, *negative), *span)),
+        _ => None,
+    }
+}
+
+/// Type of a literal as written in errors, the suffix of a number or whether it's an integer or a float
+fn literal_type(literal: &Literal) -> ImStr {
+    match literal {
+        Literal::Number(number) => match number.suffix() {
+            Some(suffix) => suffix.into(),
+            None if number.is_float() => "float".into(),
+            None => "integer".into(),
+        },
+        Literal::String(_) => "string".into(),
+        Literal::Char(_) => "char".into(),
+    }
+}
+
+/// Whether both literals can have the same type, numbers without a suffix take the one of the other bound
+fn compatible(start: &Literal, end: &Literal) -> bool {
+    match (start, end) {
+        (Literal::Number(start), Literal::Number(end)) => {
+            let float = |number: &NumberLiteral| number.suffix().map_or(number.is_float(), |suffix| suffix.starts_with('f'));
+            float(start) == float(end) && (start.suffix().is_none() || end.suffix().is_none() || start.suffix() == end.suffix())
+        }
+        (Literal::Char(_), Literal::Char(_)) => true,
+        _ => false,
+    }
+}
+
+/// Value of an integer or character bound to compare it with the other one
+fn bound_value((literal, negative): LiteralBound) -> Option<i128> {
+    let value = match literal {
+        Literal::Number(number) => i128::try_from(number.int_value()?).ok()?,
+        Literal::Char(char) => *char as i128,
+        Literal::String(_) => return None,
+    };
+    Some(if negative { -value } else { value })
+}
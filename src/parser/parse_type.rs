@@ -0,0 +1,141 @@
+use crate::lexer::TokenType;
+use crate::parser::parse::{PResult, Parser};
+use crate::parser::*;
+use crate::syntax::SyntaxKind;
+
+impl Parser<'_> {
+    /// Parses a type: `param -> result` or a postfix type
+    pub(super) fn parse_type(&mut self) -> PResult<TypeExpr> {
+        let checkpoint = self.checkpoint();
+        let param = self.parse_postfix_type()?;
+        if !self.is_op("->") {
+            return Ok(param);
+        }
+        self.bump();
+        let result = self.parse_type()?;
+        self.node(checkpoint, SyntaxKind::FunctionType);
+        let span = param.span.to(result.span);
+        Ok(TypeExpr { kind: TypeExprKind::Function { param: Box::new(param), result: Box::new(result) }, span })
+    }
+
+    /// Parses `dyn type` or a type atom followed by `.Name args...` applications and `dyn`
+    fn parse_postfix_type(&mut self) -> PResult<TypeExpr> {
+        let checkpoint = self.checkpoint();
+        if self.is_keyword("dyn") {
+            let start = Span::of(self.bump());
+            let ty = self.parse_postfix_type()?;
+            self.node(checkpoint, SyntaxKind::DynType);
+            let span = start.to(ty.span);
+            return Ok(TypeExpr { kind: TypeExprKind::Dyn(Box::new(ty)), span });
+        }
+
+        let mut ty = self.parse_type_atom()?;
+        loop {
+            let start = ty.span;
+            let kind = if self.eat_keyword("dyn") {
+                self.node(checkpoint, SyntaxKind::DynType);
+                TypeExprKind::Dyn(Box::new(ty))
+            } else if self.is_op(".") {
+                self.bump();
+                let name = self.parse_ident()?;
+                let mut args = vec![ty];
+                while self.at_type_atom() {
+                    args.push(self.parse_type_atom()?);
+                }
+                self.node(checkpoint, SyntaxKind::ApplyType);
+                TypeExprKind::Apply { name, args }
+            } else {
+                return Ok(ty);
+            };
+            ty = TypeExpr { kind, span: self.span_from(start) };
+        }
+    }
+
+    pub(super) fn at_type_atom(&mut self) -> bool {
+        match self.peek().map(|token| &token.ty) {
+            Some(TokenType::Symbol(_) | TokenType::Argument(_) | TokenType::Open) => true,
+            Some(TokenType::Keyword(keyword)) => keyword == "_",
+            _ => false,
+        }
+    }
+
+    pub(super) fn parse_type_atom(&mut self) -> PResult<TypeExpr> {
+        let Some(token) = self.peek() else { return Err(self.error(ParseErrorKind::ExpectedType)) };
+        let kind = match &token.ty {
+            TokenType::Symbol(name) => TypeExprKind::Name(name.clone()),
+            TokenType::Argument(name) => TypeExprKind::Var(name.clone()),
+            TokenType::Keyword(keyword) if keyword == "_" => TypeExprKind::Hole,
+            TokenType::Open => return self.parse_type_group(),
+            _ => return Err(self.error(ParseErrorKind::ExpectedType)),
+        };
+        let checkpoint = self.checkpoint();
+        self.bump();
+        self.node(checkpoint, match kind {
+            TypeExprKind::Name(_) => SyntaxKind::NameType,
+            TypeExprKind::Var(_) => SyntaxKind::VarType,
+            _ => SyntaxKind::HoleType,
+        });
+        Ok(TypeExpr { kind, span: Span::of(token) })
+    }
+
+    /// Parses `()`, `(type)` or a tuple type `(first, name: second...)`
+    fn parse_type_group(&mut self) -> PResult<TypeExpr> {
+        let checkpoint = self.checkpoint();
+        let open = self.bump();
+        let start = Span::of(open);
+        if open.slice != "(" {
+            return Err(ParseError { kind: ParseErrorKind::ExpectedType, span: start });
+        }
+
+        let depth = self.enter_group();
+        let mut fields = vec![];
+        let mut trailing_comma = false;
+        while !self.at_close() && self.peek().is_some() {
+            let field = self.parse_type_field().unwrap_or_else(|error| {
+                let Expr { span, .. } = self.recover(depth, error);
+                TypeField { name: None, ty: TypeExpr { kind: TypeExprKind::Error, span } }
+            });
+            fields.push(field);
+            trailing_comma = self.peek().is_some_and(|token| matches!(token.ty, TokenType::Comma));
+            if !trailing_comma {
+                break;
+            }
+            self.bump();
+        }
+        self.expect_close(open);
+        self.leave_group();
+
+        let (kind, syntax_kind) = match (&fields[..], trailing_comma) {
+            ([], _) => (TypeExprKind::Unit, SyntaxKind::UnitType),
+            ([TypeField { name: None, .. }], false) => (fields.pop().unwrap().ty.kind, SyntaxKind::ParenType),
+            _ => {
+                self.check_fields(fields.iter().filter_map(|field| field.name.as_ref()));
+                // unlike tuple values, tuple types take their positional fields first
+                let first_named = fields.iter().position(|field| field.name.is_some());
+                for field in fields.iter().skip(first_named.unwrap_or(fields.len())) {
+                    if field.name.is_none() {
+                        self.report(ParseError { kind: ParseErrorKind::PositionalAfterNamed, span: field.ty.span });
+                    }
+                }
+                (TypeExprKind::Tuple(fields), SyntaxKind::TupleType)
+            }
+        };
+        self.node(checkpoint, syntax_kind);
+        Ok(TypeExpr { kind, span: self.span_from(start) })
+    }
+
+    /// Parses `name: Type` or `Type`
+    fn parse_type_field(&mut self) -> PResult<TypeField> {
+        let named = matches!(self.peek().map(|token| &token.ty), Some(TokenType::Symbol(_)))
+            && self.peek_next().is_some_and(|next| matches!(next.ty, TokenType::Operator) && next.slice == ":");
+        if !named {
+            return Ok(TypeField { name: None, ty: self.parse_type()? });
+        }
+        let checkpoint = self.checkpoint();
+        let name = self.parse_ident()?;
+        self.bump();
+        let ty = self.parse_type()?;
+        self.node(checkpoint, SyntaxKind::TypeField);
+        Ok(TypeField { name: Some(name), ty })
+    }
+}
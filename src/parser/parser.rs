@@ -1,7 +0,0 @@
-use crate::lexer::Token;
-use crate::parser::AST;
-
-#[allow(unused)]
-fn parse(_seq: &[Token]) -> Result<AST, ()> {
-    todo!()
-}
\ No newline at end of file
@@ -0,0 +1,103 @@
+use crate::ImStr;
+use crate::parser::*;
+use crate::visit::{walk_expr, walk_pattern, walk_stmt, Visit};
+
+/// Assignments to bindings declared without `mut`. Names bound nowhere in `stmts` are left to name resolution
+pub(super) fn immutable_assignments(stmts: &[Stmt]) -> Vec<ParseError> {
+    let mut scope = Scope { bindings: vec![], errors: vec![] };
+    // definitions of a file are visible before them
+    for stmt in stmts {
+        match stmt {
+            Stmt::Let(Let { name, mutable, .. }) => scope.bind(name, *mutable),
+            Stmt::Function(FunctionDef { name, .. }) => scope.bind(name, false),
+            _ => {}
+        }
+    }
+    stmts.iter().for_each(|stmt| scope.visit_stmt(stmt));
+    scope.errors
+}
+
+struct Scope {
+    /// Names bound so far with where and whether they are `mut`, truncated back when leaving a nested scope
+    bindings: Vec<(ImStr, Span, bool)>,
+    errors: Vec<ParseError>,
+}
+
+impl Scope {
+    fn bind(&mut self, name: &Ident, mutable: bool) {
+        self.bindings.push((name.name.clone(), name.span, mutable));
+    }
+
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        let len = self.bindings.len();
+        f(self);
+        self.bindings.truncate(len);
+    }
+
+    fn assign(&mut self, place: &Expr) {
+        let root = match &place.kind {
+            ExprKind::Name(name) => name,
+            ExprKind::Method { receiver: Some(receiver), .. } => return self.assign(receiver),
+            _ => return,
+        };
+        if let Some(&(_, binding, false)) = self.bindings.iter().rev().find(|(name, ..)| name == root) {
+            self.errors.push(ParseError { kind: ParseErrorKind::ImmutableAssignment { binding }, span: place.span });
+        }
+    }
+}
+
+impl Visit for Scope {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Let(let_) => {
+                self.visit_expr(&let_.value);
+                self.bind(&let_.name, let_.mutable);
+            }
+            // functions see themselves
+            Stmt::Function(function) => {
+                self.bind(&function.name, false);
+                self.nested(|scope| {
+                    function.params.iter().for_each(|param| scope.visit_pattern(param));
+                    if let Some(body) = &function.body {
+                        scope.visit_expr(body);
+                    }
+                });
+                for clause in &function.clauses {
+                    self.nested(|scope| scope.visit_clause(clause));
+                }
+            }
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Assign { place, .. } => {
+                self.assign(place);
+                walk_expr(self, expr);
+            }
+            ExprKind::Block(_) | ExprKind::Lambda { .. } => self.nested(|scope| walk_expr(scope, expr)),
+            ExprKind::For { iter, pattern, body } => {
+                self.visit_expr(iter);
+                self.nested(|scope| {
+                    scope.visit_pattern(pattern);
+                    scope.visit_expr(body);
+                });
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.nested(|scope| scope.visit_arm(arm));
+                }
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        if let PatternKind::Bind { name, .. } | PatternKind::Optional { name, .. } = &pattern.kind {
+            self.bind(name, false);
+        }
+        walk_pattern(self, pattern);
+    }
+}
@@ -0,0 +1,28 @@
+use crate::lexer::StringValue;
+use crate::parser::*;
+use crate::visit::{walk_ast_mut, VisitMut};
+
+impl Ast {
+    /// Copy of the tree without positions: spans are zeroed and the lines of multi-line strings are counted
+    /// from 0, so trees parsed from differently laid out sources are equal if they have the same structure
+    pub fn without_spans(&self) -> Ast {
+        let mut ast = self.clone();
+        walk_ast_mut(&mut EraseSpans, &mut ast);
+        ast.definition_site.clear();
+        ast
+    }
+}
+
+struct EraseSpans;
+
+impl VisitMut for EraseSpans {
+    fn visit_literal_mut(&mut self, literal: &mut Literal) {
+        if let Literal::String(StringLiteral { value: StringValue::MultiLine { lines, .. }, .. }) = literal {
+            *lines = 0..lines.len();
+        }
+    }
+
+    fn visit_span_mut(&mut self, span: &mut Span) {
+        *span = Span::default();
+    }
+}
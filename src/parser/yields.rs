@@ -0,0 +1,94 @@
+use crate::parser::*;
+use crate::visit::{walk_expr, walk_stmt, Visit};
+
+/// Yields of a function or lambda body, nested functions and lambdas are generators of their own
+pub(crate) struct Yields {
+    /// Yields the generator stops at
+    pub stops: Vec<Span>,
+    /// Yields inside expressions, where a generator can't stop
+    pub misplaced: Vec<Span>,
+}
+
+pub(crate) fn yields(params: &[Pattern], body: &Expr) -> Yields {
+    let mut yields = Yields { stops: vec![], misplaced: vec![] };
+    params.iter().for_each(|param| yields.visit_pattern(param));
+    yields.statement(body);
+    yields
+}
+
+impl Yields {
+    /// Walks an expression whose value is dropped, so it can stop at the yields in it
+    fn statement(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Yield(value) => {
+                self.stops.push(expr.span);
+                self.visit_expr(value);
+            }
+            ExprKind::Block(stmts) => stmts.iter().for_each(|stmt| self.stmt(stmt)),
+            ExprKind::For { iter, pattern, body } => {
+                self.visit_expr(iter);
+                self.visit_pattern(pattern);
+                self.statement(body);
+            }
+            ExprKind::While { cond, body, otherwise } => {
+                self.visit_expr(cond);
+                for expr in [body, otherwise].into_iter().flatten() {
+                    self.statement(expr);
+                }
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.visit_pattern(&arm.pattern);
+                    if let Some(guard) = &arm.guard {
+                        self.visit_expr(guard);
+                    }
+                    self.statement(&arm.body);
+                }
+            }
+            ExprKind::Annotated { expr, .. } => self.statement(expr),
+            // `place <- yield value` stops like a definition
+            ExprKind::Assign { op, value, .. } if op.name == "<-" && matches!(value.kind, ExprKind::Yield(_)) => {
+                let ExprKind::Yield(resumed) = &value.kind else { unreachable!() };
+                self.stops.push(value.span);
+                self.visit_expr(resumed);
+            }
+            _ => self.visit_expr(expr),
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Let(Let { value: expr @ Expr { kind: ExprKind::Yield(value), .. }, .. }) => {
+                self.stops.push(expr.span);
+                self.visit_expr(value);
+            }
+            Stmt::Let(Let { value, .. }) => self.visit_expr(value),
+            Stmt::Expr(expr) => self.statement(expr),
+            Stmt::Function(_) | Stmt::Mod(_) | Stmt::Use(_) | Stmt::Type(_) | Stmt::Trait(_) | Stmt::Impl(_)
+            | Stmt::Signature(_) => {}
+        }
+    }
+}
+
+/// Walks expressions whose value is used, every yield in them is misplaced
+impl Visit for Yields {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if let Stmt::Let(_) | Stmt::Expr(_) = stmt {
+            walk_stmt(self, stmt);
+        }
+    }
+
+    fn visit_attribute(&mut self, _attr: &Attribute) {}
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Yield(_) => {
+                self.misplaced.push(expr.span);
+                walk_expr(self, expr);
+            }
+            ExprKind::Lambda { .. } => {}
+            _ => walk_expr(self, expr),
+        }
+    }
+}
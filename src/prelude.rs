@@ -0,0 +1,17 @@
+//! The standard prelude, declarations every module sees without `use`.
+//!
+//! It is written in the language itself and parsed like any other module file. `Range` is the type of
+//! range expressions like `0..2`, which are iterated through its `Iterator` implementation
+
+use std::path::PathBuf;
+use crate::loader::{parse_source, Module, EXTENSION};
+
+pub const NAME: &str = "prelude";
+pub const SOURCE: &str = include_str!("prelude.rolang");
+
+/// The prelude as a module without submodules
+pub fn module() -> Module {
+    let (ast, diagnostics) = parse_source(SOURCE);
+    let file = PathBuf::from(format!("{NAME}.{}", EXTENSION));
+    Module { name: NAME.into(), file, ast, diagnostics, children: vec![] }
+}
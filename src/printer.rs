@@ -0,0 +1,722 @@
+//! Printing of syntax trees in the canonical layout.
+//!
+//! Statements go on their own lines, blocks are indented by four spaces, attributes of declarations
+//! are put on the lines before them and brackets are only inserted where the precedence of an operand
+//! requires them. Comments and the original spacing are not kept, parsing the printed source gives back
+//! the same tree up to spans (see [`Ast::without_spans`])
+
+use crate::lexer::StringValue;
+use crate::parser::*;
+use crate::syntax::LineIndex;
+
+/// Levels of expressions binding tighter than binary operators
+const UNARY: u8 = POW + 1;
+const APPLY: u8 = UNARY + 1;
+/// Atom followed by `.method` without spaces
+const POSTFIX: u8 = APPLY + 1;
+const ATOM: u8 = POSTFIX + 1;
+
+/// Levels of types
+const FUNCTION_TYPE: u8 = 0;
+const POSTFIX_TYPE: u8 = 1;
+const ATOM_TYPE: u8 = 2;
+
+/// Prints `ast` in the canonical layout. Tokens skipped by error recovery and multi-line strings
+/// are copied from `source`, the text `ast` was parsed from
+pub fn print(ast: &Ast, source: &str) -> String {
+    let mut printer = Printer { out: String::new(), indent: 0, source, line_index: LineIndex::new(source) };
+    for stmt in &ast.stmts {
+        printer.stmt(stmt);
+        printer.out.push('\n');
+    }
+    printer.out
+}
+
+struct Printer<'s> {
+    out: String,
+    indent: usize,
+    source: &'s str,
+    line_index: LineIndex,
+}
+
+impl Printer<'_> {
+    /// Starts a new line of the current block
+    fn line(&mut self) {
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat_n("    ", self.indent));
+    }
+
+    fn verbatim(&mut self, span: Span) {
+        let range = self.line_index.range(span);
+        self.out.push_str(self.source.get(range).unwrap_or_default());
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) {
+        self.indent += 1;
+        for stmt in stmts {
+            self.line();
+            self.stmt(stmt);
+        }
+        self.indent -= 1;
+    }
+
+    /// Prints what follows `:=`, `::=`, `:` or `=>`
+    fn body(&mut self, body: &Expr) {
+        match &body.kind {
+            ExprKind::Block(stmts) => self.block(stmts),
+            ExprKind::Missing => {}
+            _ => {
+                self.out.push(' ');
+                self.expr(body, OR, true);
+            }
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        for attr in stmt.attrs() {
+            self.out.push('#');
+            self.attribute(attr);
+            self.line();
+        }
+        match stmt {
+            Stmt::Let(Let { vis, name, mutable, ty, value, .. }) => {
+                self.visibility(*vis);
+                if *mutable {
+                    self.out.push_str("mut ");
+                }
+                self.out.push_str(name.name.as_str());
+                self.ascription(ty.as_ref());
+                self.out.push_str(" :=");
+                self.body(value);
+            }
+            Stmt::Function(FunctionDef { vis, name, params, ty, body, clauses, .. }) => {
+                self.visibility(*vis);
+                self.out.push_str(name.name.as_str());
+                self.params(params);
+                self.ascription(ty.as_ref());
+                self.out.push_str(" :=");
+                if let Some(body) = body {
+                    self.body(body);
+                }
+                for clause in clauses {
+                    self.line();
+                    self.out.push_str(name.name.as_str());
+                    self.params(&clause.params);
+                    self.out.push_str(" ::=");
+                    self.body(&clause.body);
+                }
+            }
+            Stmt::Mod(ModDecl { vis, name, .. }) => {
+                self.visibility(*vis);
+                self.out.push_str("mod ");
+                self.out.push_str(name.name.as_str());
+            }
+            Stmt::Use(UseDecl { vis, tree, .. }) => {
+                self.visibility(*vis);
+                self.out.push_str("use ");
+                self.use_tree(tree);
+            }
+            Stmt::Type(TypeDecl { vis, name, params, where_clause, body, .. }) => {
+                self.visibility(*vis);
+                self.out.push_str(if matches!(body, TypeBody::Record(_)) { "class " } else { "type " });
+                self.out.push_str(name.name.as_str());
+                for param in params {
+                    self.out.push_str(" '");
+                    self.out.push_str(param.name.as_str());
+                }
+                self.where_clause(where_clause);
+                match body {
+                    TypeBody::Alias(ty) => {
+                        self.out.push_str(" := ");
+                        self.ty(ty, FUNCTION_TYPE);
+                    }
+                    TypeBody::Variants(variants) => {
+                        self.out.push(':');
+                        self.indent += 1;
+                        for variant in variants {
+                            self.line();
+                            self.out.push_str(variant.name.name.as_str());
+                            for field in &variant.fields {
+                                self.out.push(' ');
+                                self.ty(field, ATOM_TYPE);
+                            }
+                        }
+                        self.indent -= 1;
+                    }
+                    TypeBody::Record(fields) => {
+                        self.out.push(':');
+                        self.indent += 1;
+                        for field in fields {
+                            self.line();
+                            self.visibility(field.vis);
+                            self.out.push_str(field.name.name.as_str());
+                            self.out.push_str(": ");
+                            self.ty(&field.ty, FUNCTION_TYPE);
+                        }
+                        self.indent -= 1;
+                    }
+                }
+            }
+            Stmt::Trait(TraitDecl { vis, name, params, where_clause, items, .. }) => {
+                self.visibility(*vis);
+                self.out.push_str("trait ");
+                self.out.push_str(name.name.as_str());
+                for param in params {
+                    self.out.push_str(" '");
+                    self.out.push_str(param.name.as_str());
+                }
+                self.where_clause(where_clause);
+                self.out.push(':');
+                self.block(items);
+            }
+            Stmt::Impl(ImplDecl { trait_ref, ty, where_clause, items, .. }) => {
+                self.out.push_str("trait ");
+                self.trait_ref(trait_ref);
+                self.out.push_str(" for ");
+                self.ty(ty, FUNCTION_TYPE);
+                self.where_clause(where_clause);
+                self.out.push(':');
+                self.block(items);
+            }
+            Stmt::Signature(Signature { vis, name, params, ty, .. }) => {
+                self.visibility(*vis);
+                self.out.push_str(name.name.as_str());
+                self.params(params);
+                self.ascription(Some(ty));
+            }
+            Stmt::Expr(expr) => self.expr(expr, OR, true),
+        }
+    }
+
+    fn visibility(&mut self, vis: Visibility) {
+        self.out.push_str(match vis {
+            Visibility::Default => "",
+            Visibility::Pub => "pub ",
+            Visibility::Priv => "priv ",
+        });
+    }
+
+    fn params(&mut self, params: &[Pattern]) {
+        for param in params {
+            self.out.push(' ');
+            self.pattern(param, true);
+        }
+    }
+
+    /// Prints ` : Type` of a definition or signature
+    fn ascription(&mut self, ty: Option<&TypeExpr>) {
+        if let Some(ty) = ty {
+            self.out.push_str(" : ");
+            self.ty(ty, FUNCTION_TYPE);
+        }
+    }
+
+    /// Prints `name args...` of an attribute
+    fn attribute(&mut self, attr: &Attribute) {
+        self.out.push_str(attr.name.name.as_str());
+        for arg in &attr.args {
+            self.out.push(' ');
+            self.expr(arg, ATOM, false);
+        }
+    }
+
+    fn use_tree(&mut self, tree: &UseTree) {
+        let path: Vec<_> = tree.path.iter().map(|segment| segment.name.as_str()).collect();
+        self.out.push_str(&path.join("."));
+        match &tree.kind {
+            UseKind::Single { rename: None } => {}
+            UseKind::Single { rename: Some(rename) } => {
+                self.out.push_str(" as ");
+                self.out.push_str(rename.name.as_str());
+            }
+            UseKind::Glob => self.out.push_str(".*"),
+            UseKind::Group(trees) => {
+                if !path.is_empty() {
+                    self.out.push('.');
+                }
+                self.out.push('(');
+                for (i, tree) in trees.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.use_tree(tree);
+                }
+                self.out.push(')');
+            }
+        }
+    }
+
+    fn where_clause(&mut self, predicates: &[Predicate]) {
+        for (i, predicate) in predicates.iter().enumerate() {
+            self.out.push_str(if i == 0 { " where " } else { ", " });
+            self.ty(&predicate.ty, FUNCTION_TYPE);
+            for (i, bound) in predicate.bounds.iter().enumerate() {
+                self.out.push_str(if i == 0 { " is " } else { " + " });
+                self.trait_ref(bound);
+            }
+        }
+    }
+
+    fn trait_ref(&mut self, trait_ref: &TraitRef) {
+        self.out.push_str(trait_ref.name.name.as_str());
+        for arg in &trait_ref.args {
+            self.out.push(' ');
+            self.ty(arg, ATOM_TYPE);
+        }
+    }
+
+    /// Prints `expr` in brackets if its level is below `min`,
+    /// or if it takes the rest of the expression and something follows it, which `tail` tells
+    fn expr(&mut self, expr: &Expr, min: u8, tail: bool) {
+        let (level, open_ended) = level(expr);
+        if level < min || open_ended && !tail {
+            self.out.push('(');
+            self.expr(expr, OR, true);
+            self.out.push(')');
+            return;
+        }
+
+        match &expr.kind {
+            ExprKind::Name(name) => self.out.push_str(name.as_str()),
+            ExprKind::Argument(name) => {
+                self.out.push('\'');
+                self.out.push_str(name.as_str());
+            }
+            ExprKind::Hole => self.out.push('_'),
+            ExprKind::Literal(literal) => self.literal(literal, expr.span),
+            ExprKind::Unit => self.out.push_str("()"),
+            ExprKind::Tuple(fields) => {
+                self.out.push('(');
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    if let Some(name) = &field.name {
+                        self.out.push_str(name.name.as_str());
+                        self.out.push_str(" := ");
+                    }
+                    self.expr(&field.value, OR, true);
+                }
+                if let [Field { name: None, .. }] = &fields[..] {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            ExprKind::List(elements) => {
+                self.out.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.expr(element, OR, true);
+                }
+                self.out.push(']');
+            }
+            ExprKind::Apply { func, args } => {
+                self.expr(func, POSTFIX, false);
+                self.args(args, tail);
+            }
+            ExprKind::Method { receiver, method, args } => {
+                match receiver {
+                    Some(receiver) if level == POSTFIX => {
+                        self.expr(receiver, POSTFIX, false);
+                        self.out.push('.');
+                    }
+                    Some(receiver) => {
+                        self.expr(receiver, CHAIN, false);
+                        self.out.push_str(" .");
+                    }
+                    None => self.out.push('.'),
+                }
+                self.expr(method, POSTFIX, false);
+                self.args(args, tail);
+            }
+            ExprKind::Unary { op, operand } if op.name == "not" => {
+                self.out.push_str("not ");
+                self.expr(operand, NOT, tail);
+            }
+            ExprKind::Unary { op, operand } => {
+                self.out.push_str(op.name.as_str());
+                // an operator or a dot right after `op` would be lexed as a part of it
+                let glued = matches!(operand.kind, ExprKind::Unary { .. } | ExprKind::Method { receiver: None, .. });
+                self.expr(operand, if glued { ATOM } else { UNARY }, tail);
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let (prec, assoc) = precedence(&op.name);
+                let (lhs_min, rhs_min) = match assoc {
+                    Assoc::Left => (prec, prec + 1),
+                    Assoc::Right => (prec + 1, prec),
+                };
+                self.expr(lhs, lhs_min, false);
+                self.out.push(' ');
+                self.out.push_str(op.name.as_str());
+                self.out.push(' ');
+                self.expr(rhs, rhs_min, tail);
+            }
+            ExprKind::Block(stmts) => self.block(stmts),
+            ExprKind::For { iter, pattern, body } => {
+                self.expr(iter, CHAIN, false);
+                self.out.push_str(" for ");
+                match &pattern.kind {
+                    // a lone name before `:` is a binder rather than a constructor
+                    PatternKind::Bind { name, pattern: None, guard: None } => self.out.push_str(name.name.as_str()),
+                    PatternKind::Constructor { args, .. } if args.is_empty() => self.pattern(pattern, true),
+                    _ => self.pattern(pattern, false),
+                }
+                self.out.push(':');
+                self.body(body);
+            }
+            ExprKind::While { cond, body, otherwise } => {
+                let parts = [Some(&**cond), body.as_deref(), otherwise.as_deref()];
+                let split = parts.iter().flatten().any(|part| matches!(part.kind, ExprKind::Block(_)));
+                self.out.push_str("while:");
+                let mut start = self.out.len();
+                self.body(cond);
+                for (keyword, part) in [("then:", body), ("else:", otherwise)] {
+                    let Some(part) = part else { continue };
+                    if split || self.out[start..].contains('\n') {
+                        self.line();
+                    } else {
+                        self.out.push(' ');
+                    }
+                    start = self.out.len();
+                    self.out.push_str(keyword);
+                    self.body(part);
+                }
+            }
+            ExprKind::Cast { expr, ty } => {
+                self.expr(expr, CHAIN, false);
+                self.out.push_str(" as ");
+                self.ty(ty, FUNCTION_TYPE);
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.out.push_str("match ");
+                self.expr(scrutinee, OR, false);
+                self.out.push(':');
+                self.indent += 1;
+                for arm in arms {
+                    self.line();
+                    self.pattern(&arm.pattern, false);
+                    if let Some(guard) = &arm.guard {
+                        self.out.push_str(" if ");
+                        self.expr(guard, OR, true);
+                    }
+                    self.out.push_str(" =>");
+                    self.body(&arm.body);
+                }
+                self.indent -= 1;
+            }
+            ExprKind::Lambda { params, body, .. } => {
+                self.out.push_str("fn");
+                self.params(params);
+                self.out.push(':');
+                self.body(body);
+            }
+            ExprKind::Annotated { attrs, expr } => {
+                self.out.push_str("#[");
+                for (i, attr) in attrs.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.attribute(attr);
+                }
+                self.out.push(']');
+                if !matches!(expr.kind, ExprKind::Missing) {
+                    self.out.push(' ');
+                    // attributes right before the expression would be merged with these
+                    let nested = matches!(expr.kind, ExprKind::Annotated { .. });
+                    self.expr(expr, if nested { ATOM + 1 } else { OR }, true);
+                }
+            }
+            ExprKind::Yield(value) => {
+                self.out.push_str("yield ");
+                self.expr(value, OR, true);
+            }
+            ExprKind::Assign { place, op, value } => {
+                self.expr(place, POSTFIX, false);
+                self.out.push(' ');
+                self.out.push_str(op.name.as_str());
+                self.out.push(' ');
+                // updates are printed sugared, without the place repeated in `value`
+                match &value.kind {
+                    ExprKind::Method { method, args, .. } if op.name == ".<-" => {
+                        self.expr(method, POSTFIX, false);
+                        self.args(args, tail);
+                    }
+                    ExprKind::Binary { rhs, .. } if op.name != "<-" => self.expr(rhs, OR, tail),
+                    _ => self.expr(value, OR, tail),
+                }
+            }
+            ExprKind::Range { start, end, inclusive } => {
+                if let Some(start) = start {
+                    self.expr(start, RANGE, false);
+                }
+                self.out.push_str(if *inclusive { "..=" } else { ".." });
+                if let Some(end) = end {
+                    // only `-` starts the end of a range, other operators and dots continue the expression
+                    let prefixed = match &end.kind {
+                        ExprKind::Unary { op, .. } => op.name != "-",
+                        ExprKind::Method { receiver: None, .. } => true,
+                        _ => false,
+                    };
+                    self.expr(end, if prefixed { ATOM } else { RANGE + 1 }, tail);
+                }
+            }
+            ExprKind::Error => self.verbatim(expr.span),
+            ExprKind::Missing => {}
+        }
+    }
+
+    /// Prints the arguments of an application or method call, a last argument ending in a block follows `:`
+    fn args(&mut self, args: &[Expr], tail: bool) {
+        for (i, arg) in args.iter().enumerate() {
+            let last = i + 1 == args.len();
+            if last && ends_in_block(arg) {
+                self.out.push(':');
+                self.body(arg);
+            } else {
+                self.out.push(' ');
+                self.expr(arg, POSTFIX, last && tail);
+            }
+        }
+    }
+
+    fn literal(&mut self, literal: &Literal, span: Span) {
+        match literal {
+            Literal::Number(number) => self.out.push_str(number.slice.as_str()),
+            Literal::Char('\'') => self.out.push_str("'''"),
+            Literal::Char('\\') => self.out.push_str(r"'\\'"),
+            Literal::Char('\n') => self.out.push_str(r"'\n'"),
+            Literal::Char('\r') => self.out.push_str(r"'\r'"),
+            Literal::Char('\t') => self.out.push_str(r"'\t'"),
+            Literal::Char(char) if char.is_control() => self.out.push_str(&format!(r"'\{}'", *char as u32)),
+            Literal::Char(char) => self.out.push_str(&format!("'{char}'")),
+            Literal::String(StringLiteral { value: StringValue::MultiLine { .. }, .. }) => self.verbatim(span),
+            Literal::String(StringLiteral { prefix, value, suffix }) => {
+                self.out.push_str(prefix.as_str());
+                match value {
+                    StringValue::SingleLine(content) => {
+                        let quotes = "\"".repeat(quote_count(content));
+                        self.out.push_str(&quotes);
+                        self.out.push_str(content.as_str());
+                        self.out.push_str(&quotes);
+                    }
+                    _ => self.out.push_str("\"\""),
+                }
+                self.out.push_str(suffix.as_str());
+            }
+        }
+    }
+
+    /// Prints `pattern`, in brackets if it has to be an `atom` but isn't one
+    fn pattern(&mut self, pattern: &Pattern, atom: bool) {
+        let compound = match &pattern.kind {
+            PatternKind::Bind { pattern, .. } => pattern.is_some(),
+            PatternKind::Constructor { args, .. } => !args.is_empty(),
+            PatternKind::Optional { default, .. } => default.is_some(),
+            _ => false,
+        };
+        if atom && compound {
+            self.out.push('(');
+            self.pattern(pattern, false);
+            self.out.push(')');
+            return;
+        }
+
+        match &pattern.kind {
+            PatternKind::Wildcard => self.out.push('_'),
+            PatternKind::Bind { name, pattern: Some(pattern), .. } => {
+                self.out.push('\'');
+                self.out.push_str(name.name.as_str());
+                self.out.push_str(" = ");
+                self.pattern(pattern, false);
+            }
+            // the guard starts with the binder
+            PatternKind::Bind { guard: Some(guard), .. } => {
+                self.out.push('(');
+                self.expr(guard, OR, true);
+                self.out.push(')');
+            }
+            PatternKind::Bind { name, .. } => {
+                self.out.push('\'');
+                self.out.push_str(name.name.as_str());
+            }
+            PatternKind::Literal { value, negative } => {
+                if *negative {
+                    self.out.push('-');
+                }
+                self.literal(value, pattern.span);
+            }
+            PatternKind::Range { start, end, inclusive } => {
+                if let Some(start) = start {
+                    self.pattern(start, true);
+                }
+                self.out.push_str(if *inclusive { "..=" } else { ".." });
+                if let Some(end) = end {
+                    self.pattern(end, true);
+                }
+            }
+            PatternKind::Constructor { name, args } => {
+                self.out.push_str(name.name.as_str());
+                for arg in args {
+                    self.out.push(' ');
+                    self.pattern(arg, true);
+                }
+            }
+            PatternKind::Unit => self.out.push_str("()"),
+            PatternKind::Tuple(elements) => {
+                self.out.push('(');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.pattern(element, false);
+                }
+                if elements.len() == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            PatternKind::List(elements) => {
+                self.out.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.pattern(element, false);
+                }
+                self.out.push(']');
+            }
+            PatternKind::Optional { name, default } => {
+                self.out.push_str("?'");
+                self.out.push_str(name.name.as_str());
+                if let Some(default) = default {
+                    self.out.push_str(" = ");
+                    self.expr(default, OR, true);
+                }
+            }
+            PatternKind::Error => self.verbatim(pattern.span),
+        }
+    }
+
+    /// Prints `ty` in brackets if its level is below `min`
+    fn ty(&mut self, ty: &TypeExpr, min: u8) {
+        let level = match ty.kind {
+            TypeExprKind::Function { .. } => FUNCTION_TYPE,
+            TypeExprKind::Apply { .. } | TypeExprKind::Dyn(_) => POSTFIX_TYPE,
+            _ => ATOM_TYPE,
+        };
+        if level < min {
+            self.out.push('(');
+            self.ty(ty, FUNCTION_TYPE);
+            self.out.push(')');
+            return;
+        }
+
+        match &ty.kind {
+            TypeExprKind::Name(name) => self.out.push_str(name.as_str()),
+            TypeExprKind::Var(name) => {
+                self.out.push('\'');
+                self.out.push_str(name.as_str());
+            }
+            TypeExprKind::Hole => self.out.push('_'),
+            TypeExprKind::Apply { name, args } => {
+                let (first, rest) = args.split_first().map_or((None, &[][..]), |(first, rest)| (Some(first), rest));
+                if let Some(first) = first {
+                    self.ty(first, POSTFIX_TYPE);
+                    self.out.push(' ');
+                }
+                self.out.push('.');
+                self.out.push_str(name.name.as_str());
+                for arg in rest {
+                    self.out.push(' ');
+                    self.ty(arg, ATOM_TYPE);
+                }
+            }
+            TypeExprKind::Unit => self.out.push_str("()"),
+            TypeExprKind::Tuple(fields) => {
+                self.out.push('(');
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    if let Some(name) = &field.name {
+                        self.out.push_str(name.name.as_str());
+                        self.out.push_str(": ");
+                    }
+                    self.ty(&field.ty, FUNCTION_TYPE);
+                }
+                if let [TypeField { name: None, .. }] = &fields[..] {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            TypeExprKind::Function { param, result } => {
+                self.ty(param, POSTFIX_TYPE);
+                self.out.push_str(" -> ");
+                self.ty(result, FUNCTION_TYPE);
+            }
+            TypeExprKind::Dyn(inner) => {
+                self.ty(inner, POSTFIX_TYPE);
+                self.out.push_str(" dyn");
+            }
+            TypeExprKind::Error => self.verbatim(ty.span),
+        }
+    }
+}
+
+/// Level of `expr` and whether it takes the rest of the expression it is in
+fn level(expr: &Expr) -> (u8, bool) {
+    match &expr.kind {
+        // a dot right after a number would be lexed as a part of it
+        ExprKind::Method { receiver: Some(receiver), method, args }
+        if args.is_empty() && matches!(method.kind, ExprKind::Name(_)) && level(receiver).0 >= POSTFIX
+            && !matches!(receiver.kind, ExprKind::Literal(Literal::Number(_))) => (POSTFIX, false),
+        ExprKind::Method { receiver: Some(_), .. } => (CHAIN, false),
+        ExprKind::Method { receiver: None, .. } => (UNARY, false),
+        ExprKind::Apply { .. } => (APPLY, false),
+        ExprKind::Unary { op, .. } if op.name == "not" => (NOT, false),
+        ExprKind::Unary { .. } => (UNARY, false),
+        ExprKind::Binary { op, .. } => (precedence(&op.name).0, false),
+        // the type after `as` would take a following `.name` or `->`
+        ExprKind::For { .. } | ExprKind::Cast { .. } => (CHAIN, true),
+        ExprKind::Yield(_) => (UNARY, true),
+        ExprKind::Assign { .. } => (OR, true),
+        ExprKind::Range { .. } => (RANGE, false),
+        ExprKind::Block(_) | ExprKind::While { .. } | ExprKind::Match { .. } | ExprKind::Lambda { .. }
+        | ExprKind::Annotated { .. } => (ATOM, true),
+        _ => (ATOM, false),
+    }
+}
+
+/// Whether `expr` ends with a block argument, whose lines brackets around it can't hold
+fn ends_in_block(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Block(_) => true,
+        ExprKind::Apply { args, .. } | ExprKind::Method { args, .. } => args.last().is_some_and(ends_in_block),
+        ExprKind::Unary { operand, .. } => ends_in_block(operand),
+        ExprKind::Binary { rhs, .. } => ends_in_block(rhs),
+        _ => false,
+    }
+}
+
+fn precedence(op: &str) -> (u8, Assoc) {
+    operator_precedence(op).unwrap_or((OR, Assoc::Left))
+}
+
+/// Number of quotes around a single-line string, more than any run of quotes not preceded by `\\` in it.
+/// Two quotes would make an empty string
+fn quote_count(content: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut prev = '\0';
+    for char in content.chars() {
+        run = if char == '"' && prev != '\\' { run + 1 } else { 0 };
+        longest = longest.max(run);
+        prev = char;
+    }
+    match longest {
+        0 => 1,
+        longest => (longest + 1).max(3),
+    }
+}
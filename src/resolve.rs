@@ -0,0 +1,380 @@
+//! Name resolution.
+//!
+//! Every name used in an expression or a pattern is mapped to the definition it refers to. Definitions
+//! of a module and of the prelude are visible in the whole module, local ones from their binding to the
+//! end of their scope: parameters and pattern binders in their function, lambda, clause or arm,
+//! `:=` definitions in the rest of their block and loop variables in the loop body. Names a macro template
+//! doesn't bind only see the module and prelude ones, where the macro is defined.
+//! Method names after a dot naming a local function or `:=` definition refer to it, other ones and types
+//! are left to the type checker, which knows the receiver type
+
+use std::collections::{HashMap, HashSet};
+use crate::ImStr;
+use crate::parser::*;
+use crate::visit::{walk_expr, walk_stmt, Visit};
+
+/// Index of a definition in [`Resolution::defs`]
+pub type DefId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Def {
+    pub name: ImStr,
+    /// Span of the defining name, in the prelude for prelude definitions
+    pub span: Span,
+    pub kind: DefKind,
+    pub scope: DefScope,
+    /// Spans of the names referring to the definition, in source order
+    pub uses: Vec<Span>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    /// `name params := ...`, local functions included
+    Function,
+    /// `name := value` of a module
+    Value,
+    /// Constructor of a `type`
+    Constructor,
+    /// Definition or signature in a trait
+    Method,
+    /// `mod name`
+    Module,
+    /// Name brought in by `use`
+    Import,
+    /// Binder in the parameters of a function, clause or lambda
+    Param,
+    /// `name := value` in a block
+    Local,
+    /// Binder in the pattern of a `for` loop
+    LoopVar,
+    /// Binder in the pattern of a `match` arm
+    Binder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefScope {
+    Prelude,
+    Module,
+    /// Defined in a function or lambda, which captures it if it's in an enclosing one
+    Local,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    pub defs: Vec<Def>,
+    /// Definition referred to by the name at each span
+    pub resolved: HashMap<Span, DefId>,
+    /// Local definitions of enclosing scopes each lambda uses, by the span of the lambda
+    pub captures: HashMap<Span, Vec<DefId>>,
+    /// Errors and warnings in source order
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Resolution {
+    /// Definition the name at `span` refers to
+    pub fn def_at(&self, span: Span) -> Option<&Def> {
+        self.resolved.get(&span).map(|&id| &self.defs[id])
+    }
+}
+
+/// Resolves the names of module `ast`, which sees the definitions of `prelude` unless it redefines them
+pub fn resolve(ast: &Ast, prelude: &Ast) -> Resolution {
+    let mut resolver = Resolver {
+        resolution: Resolution::default(),
+        globals: HashMap::new(),
+        scopes: vec![],
+        glob: false,
+        definition_site: &ast.definition_site,
+    };
+    resolver.declare_all(&prelude.stmts, DefScope::Prelude);
+    resolver.declare_all(&ast.stmts, DefScope::Module);
+    ast.stmts.iter().for_each(|stmt| resolver.visit_stmt(stmt));
+
+    let mut resolution = resolver.resolution;
+    for def in &mut resolution.defs {
+        def.uses.sort_by_key(|span| (span.row, span.num));
+    }
+    resolution.diagnostics.sort_by_key(|diagnostic| (diagnostic.span.row, diagnostic.span.num));
+    resolution
+}
+
+struct Resolver<'a> {
+    resolution: Resolution,
+    /// Prelude and module definitions by name
+    globals: HashMap<ImStr, DefId>,
+    /// Definitions of the local scopes, innermost last
+    scopes: Vec<Vec<DefId>>,
+    /// Whether a glob import can bring in names not known here, which are then not reported
+    glob: bool,
+    /// Names from macro templates, which don't see local definitions
+    definition_site: &'a HashSet<Span>,
+}
+
+impl Resolver<'_> {
+    fn define(&mut self, name: &Ident, kind: DefKind, scope: DefScope) -> DefId {
+        let def = Def { name: name.name.clone(), span: name.span, kind, scope, uses: vec![] };
+        self.resolution.defs.push(def);
+        self.resolution.defs.len() - 1
+    }
+
+    fn report(&mut self, severity: Severity, kind: ParseErrorKind, span: Span) {
+        self.resolution.diagnostics.push(Diagnostic { severity, kind, span });
+    }
+
+    /// Declares the definitions of a module or prelude, visible before them
+    fn declare_all(&mut self, stmts: &[Stmt], scope: DefScope) {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Let(Let { name, .. }) => self.declare(name, DefKind::Value, scope),
+                Stmt::Function(FunctionDef { name, .. }) => self.declare(name, DefKind::Function, scope),
+                Stmt::Mod(ModDecl { name, .. }) => self.declare(name, DefKind::Module, scope),
+                Stmt::Use(UseDecl { tree, .. }) => self.declare_use(tree, scope),
+                Stmt::Type(TypeDecl { body: TypeBody::Variants(variants), .. }) => {
+                    variants.iter().for_each(|variant| self.declare(&variant.name, DefKind::Constructor, scope));
+                }
+                Stmt::Trait(TraitDecl { items, .. }) => {
+                    for item in items {
+                        if let Stmt::Function(FunctionDef { name, .. }) | Stmt::Signature(Signature { name, .. }) = item {
+                            self.declare(name, DefKind::Method, scope);
+                        }
+                    }
+                }
+                Stmt::Type(_) | Stmt::Impl(_) | Stmt::Signature(_) | Stmt::Expr(_) => {}
+            }
+        }
+    }
+
+    fn declare_use(&mut self, tree: &UseTree, scope: DefScope) {
+        match &tree.kind {
+            UseKind::Single { rename } => {
+                if let Some(name) = rename.as_ref().or(tree.path.last()) {
+                    self.declare(name, DefKind::Import, scope);
+                }
+            }
+            UseKind::Glob => self.glob |= scope == DefScope::Module,
+            UseKind::Group(trees) => trees.iter().for_each(|tree| self.declare_use(tree, scope)),
+        }
+    }
+
+    /// Declares a module level definition. Trait methods can share a name with each other and with
+    /// other definitions, which take precedence
+    fn declare(&mut self, name: &Ident, kind: DefKind, scope: DefScope) {
+        if let Some(&first) = self.globals.get(&name.name) && self.resolution.defs[first].scope == scope {
+            let first = &self.resolution.defs[first];
+            match (first.kind, kind) {
+                (_, DefKind::Method) => return,
+                (DefKind::Method, _) => {}
+                _ => {
+                    let kind = ParseErrorKind::DuplicateDefinition { first: first.span };
+                    return self.report(Severity::Error, kind, name.span);
+                }
+            }
+        }
+        let id = self.define(name, kind, scope);
+        self.globals.insert(name.name.clone(), id);
+    }
+
+    /// Innermost local definition of `name`, from scope `depth` outwards
+    fn local(&self, name: &ImStr, depth: usize) -> Option<DefId> {
+        self.scopes[..depth].iter().rev()
+            .flat_map(|scope| scope.iter().rev())
+            .copied()
+            .find(|&id| self.resolution.defs[id].name == *name)
+    }
+
+    /// Binds a local definition in the innermost scope. `intended` tells whether shadowing a definition
+    /// of an enclosing scope was probably intended, it's warned about otherwise
+    fn bind(&mut self, name: &Ident, kind: DefKind, intended: impl FnOnce(&Self, DefId) -> bool) {
+        let depth = self.scopes.len() - 1;
+        let rebound = self.scopes[depth].iter().any(|&id| self.resolution.defs[id].name == name.name);
+        if !rebound && !name.name.starts_with('_') && let Some(shadowed) = self.local(&name.name, depth)
+            && !intended(self, shadowed)
+        {
+            let kind = ParseErrorKind::ShadowedBinding { name: name.name.clone(), shadowed: self.resolution.defs[shadowed].span };
+            self.report(Severity::Warning, kind, name.span);
+        }
+        let id = self.define(name, kind, DefScope::Local);
+        self.scopes[depth].push(id);
+    }
+
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(vec![]);
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn reference(&mut self, name: &ImStr, span: Span) {
+        let depth = if self.definition_site.contains(&span) { 0 } else { self.scopes.len() };
+        let found = self.local(name, depth).or_else(|| self.globals.get(name).copied());
+        match found {
+            Some(id) => {
+                self.resolution.defs[id].uses.push(span);
+                self.resolution.resolved.insert(span, id);
+            }
+            None if self.glob => {}
+            None => {
+                let suggestion = self.suggest(name);
+                self.report(Severity::Error, ParseErrorKind::UnresolvedName { name: name.clone(), suggestion }, span);
+            }
+        }
+    }
+
+    /// Visible name closest to `name`, if close enough to be a typo of it. Locals come before globals
+    fn suggest(&self, name: &str) -> Option<ImStr> {
+        let max = name.chars().count() / 3;
+        let locals = self.scopes.iter().rev().flat_map(|scope| scope.iter().rev()).map(|&id| (0, id));
+        let globals = self.globals.values().map(|&id| (1, id));
+        locals.chain(globals)
+            .map(|(global, id)| (global, &self.resolution.defs[id].name))
+            .map(|(global, candidate)| (edit_distance(name, candidate), global, candidate))
+            .filter(|&(distance, ..)| distance <= max)
+            .min()
+            .map(|(.., candidate)| candidate.clone())
+    }
+
+    /// Binds the names of the parameters of a function, clause or lambda and resolves its body in a new scope
+    fn function(&mut self, params: &[Pattern], body: Option<&Expr>) {
+        self.nested(|resolver| {
+            params.iter().for_each(|param| resolver.pattern(param, DefKind::Param));
+            if let Some(body) = body {
+                resolver.visit_expr(body);
+            }
+        });
+    }
+
+    /// Resolves `pattern` binding its names as `kind`
+    fn pattern(&mut self, pattern: &Pattern, kind: DefKind) {
+        match &pattern.kind {
+            PatternKind::Bind { name, pattern, guard } => {
+                self.bind(name, kind, |_, _| false);
+                if let Some(pattern) = pattern {
+                    self.pattern(pattern, kind);
+                }
+                if let Some(guard) = guard {
+                    self.visit_expr(guard);
+                }
+            }
+            PatternKind::Optional { name, default } => {
+                if let Some(default) = default {
+                    self.visit_expr(default);
+                }
+                self.bind(name, kind, |_, _| false);
+            }
+            PatternKind::Constructor { name, args } => {
+                self.reference(&name.name, name.span);
+                args.iter().for_each(|arg| self.pattern(arg, kind));
+            }
+            PatternKind::Tuple(elements) | PatternKind::List(elements) => {
+                elements.iter().for_each(|element| self.pattern(element, kind));
+            }
+            PatternKind::Wildcard | PatternKind::Literal { .. } | PatternKind::Range { .. } | PatternKind::Unit
+            | PatternKind::Error => {}
+        }
+    }
+}
+
+impl Visit for Resolver<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        let local = !self.scopes.is_empty();
+        match stmt {
+            // `x := x + 1` means to shadow the `x` it uses
+            Stmt::Let(let_) if local => {
+                self.visit_expr(&let_.value);
+                let used = |resolver: &Self, shadowed: DefId| {
+                    resolver.resolution.defs[shadowed].uses.iter().any(|span| let_.value.span.contains(*span))
+                };
+                self.bind(&let_.name, DefKind::Local, used);
+            }
+            Stmt::Let(let_) => self.visit_expr(&let_.value),
+            // functions see themselves
+            Stmt::Function(function) => {
+                if local {
+                    self.bind(&function.name, DefKind::Function, |_, _| false);
+                }
+                self.function(&function.params, function.body.as_ref());
+                for clause in &function.clauses {
+                    self.function(&clause.params, Some(&clause.body));
+                }
+            }
+            // parameters of signatures only name the arguments
+            Stmt::Signature(_) => {}
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Name(name) | ExprKind::Argument(name) => self.reference(name, expr.span),
+            ExprKind::Method { receiver, method, args } => {
+                if let Some(receiver) = receiver {
+                    self.visit_expr(receiver);
+                }
+                match &method.kind {
+                    ExprKind::Name(name) => {
+                        let local = self.local(name, self.scopes.len())
+                            .filter(|&id| matches!(self.resolution.defs[id].kind, DefKind::Function | DefKind::Local));
+                        if let Some(id) = local {
+                            self.resolution.defs[id].uses.push(method.span);
+                            self.resolution.resolved.insert(method.span, id);
+                        }
+                    }
+                    _ => self.visit_expr(method),
+                }
+                args.iter().for_each(|arg| self.visit_expr(arg));
+            }
+            ExprKind::Block(stmts) => self.nested(|resolver| stmts.iter().for_each(|stmt| resolver.visit_stmt(stmt))),
+            ExprKind::For { iter, pattern, body } => {
+                self.visit_expr(iter);
+                self.nested(|resolver| {
+                    resolver.pattern(pattern, DefKind::LoopVar);
+                    resolver.visit_expr(body);
+                });
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.nested(|resolver| {
+                        resolver.pattern(&arm.pattern, DefKind::Binder);
+                        if let Some(guard) = &arm.guard {
+                            resolver.visit_expr(guard);
+                        }
+                        resolver.visit_expr(&arm.body);
+                    });
+                }
+            }
+            ExprKind::Lambda { params, body, captures, .. } => {
+                let outer = self.resolution.defs.len();
+                self.function(params, Some(body));
+                // the lambda's own definitions come after `outer`
+                let captured = captures.iter()
+                    .filter_map(|capture| self.resolution.resolved.get(&capture.span).copied())
+                    .filter(|&id| id < outer && self.resolution.defs[id].scope == DefScope::Local)
+                    .collect();
+                self.resolution.captures.insert(expr.span, captured);
+            }
+            // the type is resolved by the type checker
+            ExprKind::Cast { expr, .. } => self.visit_expr(expr),
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+/// Number of inserted, deleted, replaced and swapped adjacent characters turning `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<_>, Vec<_>) = (a.chars().collect(), b.chars().collect());
+    // distances from prefixes of `a` to the prefixes of `b`, for the current and two previous rows
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut curr = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                curr[j] = curr[j].min(before[j - 2] + 1);
+            }
+        }
+        before = std::mem::replace(&mut prev, curr);
+    }
+    prev[b.len()]
+}
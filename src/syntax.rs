@@ -0,0 +1,257 @@
+use std::ops::Range;
+use crate::lexer::{Token, TokenIterator, TokenType};
+use crate::parser::{Ast, Diagnostic, Span};
+use self::ast::AstNode;
+
+mod build;
+mod green;
+mod red;
+mod reparse;
+pub mod ast;
+
+pub use self::green::{GreenElement, GreenNode, GreenToken};
+pub use self::red::{SyntaxElement, SyntaxNode, SyntaxToken};
+pub use self::reparse::{reparse, Reparse, TextEdit};
+
+/// Kind of a syntax tree node or token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SyntaxKind {
+    // trivia
+    Whitespace,
+    Newline,
+    Comment,
+
+    // tokens, see `TokenType`
+    Open,
+    Close,
+    Operator,
+    Comma,
+    Semicolon,
+    Sharp,
+    Symbol,
+    Keyword,
+    ArgumentToken,
+    Character,
+    String,
+    Number,
+    Other,
+    ErrorToken,
+
+    // nodes
+    Root,
+    /// `name params := body`
+    Definition,
+    /// `name params ::= body`
+    Clause,
+    /// `mod name`
+    Mod,
+    /// `use tree`
+    Use,
+    /// `a.b as c`, `a.*` or `a.(tree, ...)`
+    UseTree,
+    /// `type` or `class` declaration
+    TypeDecl,
+    /// `Name fields...` in a `type` body
+    Variant,
+    /// `name: Type` in a `class` body
+    RecordField,
+    /// `trait Name 'params...:` followed by signatures and definitions
+    TraitDecl,
+    /// `name params : Type` in a trait
+    Signature,
+    /// `trait Name args... for Type:` followed by a block
+    Impl,
+    /// `where` followed by predicates
+    WhereClause,
+    /// `Type is Trait + ...`
+    Predicate,
+    /// `Name args...` naming a trait
+    TraitRef,
+    /// `#name args...`, or `name args...` in a group
+    Attribute,
+    /// `#[attribute, ...]`
+    AttributeGroup,
+    Block,
+    Name,
+    Argument,
+    Hole,
+    Literal,
+    Unit,
+    Paren,
+    Tuple,
+    List,
+    /// `name := value` in a tuple
+    Field,
+    Apply,
+    /// `receiver.method`, `receiver .method args...` or `.method args...`
+    Method,
+    Unary,
+    Binary,
+    For,
+    While,
+    /// `match scrutinee:` followed by arms
+    Match,
+    /// `pattern [if guard] => body`
+    Arm,
+    /// `fn params...: body`
+    Lambda,
+    /// `expr as Type`
+    Cast,
+    /// Attributes followed by the expression they apply to
+    Annotated,
+    /// `yield value`
+    Yield,
+    /// `place <- value` or an update like `place +<- value`
+    Assign,
+    /// `start..end` or `start..=end`, either bound can be absent
+    Range,
+    /// Skipped erroneous tokens
+    Error,
+    /// Expected but absent expression, never holds tokens
+    Missing,
+
+    WildcardPat,
+    BindPat,
+    /// `('name guard...)`
+    GuardPat,
+    LiteralPat,
+    ConstructorPat,
+    OptionalPat,
+    /// Range with literal bounds
+    RangePat,
+    UnitPat,
+    ParenPat,
+    TuplePat,
+    ListPat,
+
+    NameType,
+    VarType,
+    HoleType,
+    /// `first .Name rest...`
+    ApplyType,
+    UnitType,
+    ParenType,
+    TupleType,
+    /// `name: Type` in a tuple type
+    TypeField,
+    FunctionType,
+    DynType,
+}
+
+impl SyntaxKind {
+    pub fn of(token: &Token) -> Self {
+        match token.ty {
+            TokenType::Open => SyntaxKind::Open,
+            TokenType::Close => SyntaxKind::Close,
+            TokenType::Operator => SyntaxKind::Operator,
+            TokenType::Comma => SyntaxKind::Comma,
+            TokenType::Semicolon => SyntaxKind::Semicolon,
+            TokenType::Sharp => SyntaxKind::Sharp,
+            TokenType::Symbol(_) => SyntaxKind::Symbol,
+            TokenType::Keyword(_) => SyntaxKind::Keyword,
+            TokenType::Argument(_) => SyntaxKind::ArgumentToken,
+            TokenType::Character { .. } => SyntaxKind::Character,
+            TokenType::String { .. } => SyntaxKind::String,
+            TokenType::Number { .. } => SyntaxKind::Number,
+            TokenType::Indent => SyntaxKind::Whitespace,
+            TokenType::Comment => SyntaxKind::Comment,
+            TokenType::Other => SyntaxKind::Other,
+            TokenType::Error => SyntaxKind::ErrorToken,
+        }
+    }
+
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::Whitespace | SyntaxKind::Newline | SyntaxKind::Comment)
+    }
+}
+
+/// Converts between byte offsets in the source and token coordinates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Offset of the first byte of every line
+    line_starts: Vec<usize>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        LineIndex { line_starts, len: source.len() }
+    }
+
+    /// Offset of byte `num` of line `row`, lines are prefixed with a space by the lexer
+    pub fn offset(&self, row: usize, num: usize) -> usize {
+        match self.line_starts.get(row) {
+            Some(start) => (start + num.saturating_sub(1)).min(self.len),
+            None => self.len,
+        }
+    }
+
+    /// Token coordinates `(row, num)` of an offset
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let row = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        (row, offset - self.line_starts[row] + 1)
+    }
+
+    pub fn range(&self, span: Span) -> Range<usize> {
+        self.offset(span.row, span.num)..self.offset(span.end_row, span.end_num)
+    }
+
+    pub fn span(&self, range: Range<usize>) -> Span {
+        let (row, num) = self.position(range.start);
+        let (end_row, end_num) = self.position(range.end);
+        Span { row, num, end_row, end_num }
+    }
+}
+
+/// Source parsed into both the AST and the lossless syntax tree
+#[derive(Debug, Clone)]
+pub struct Parse {
+    pub ast: Ast,
+    pub green: GreenNode,
+    pub diagnostics: Vec<Diagnostic>,
+    pub line_index: LineIndex,
+}
+
+impl Parse {
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    pub fn root(&self) -> ast::Root {
+        ast::Root::cast(self.syntax()).unwrap()
+    }
+
+    /// Syntax token of a token produced by `TokenIterator` over the same source
+    pub fn token(&self, token: &Token) -> Option<SyntaxToken> {
+        let range = self.line_index.range(Span::of(token));
+        self.syntax().token_at_offset(range.start)
+            .filter(|found| found.text_range() == range)
+    }
+
+    /// Smallest node covering `span`
+    pub fn covering_node(&self, span: Span) -> SyntaxNode {
+        self.syntax().covering_node(self.line_index.range(span))
+    }
+
+    pub fn span(&self, node: &SyntaxNode) -> Span {
+        self.line_index.span(node.text_range())
+    }
+}
+
+pub fn parse_source(source: &str) -> Parse {
+    let tokens: Vec<_> = TokenIterator::new(source).collect();
+    let (ast, diagnostics, events) = crate::parser::parse_events(&tokens);
+    let line_index = LineIndex::new(source);
+    let green = build::build(source, &line_index, &tokens, &events);
+    Parse { ast, green, diagnostics, line_index }
+}
+
+/// Syntax tree of `source` without the AST
+fn parse_green(source: &str) -> GreenNode {
+    let tokens: Vec<_> = TokenIterator::new(source).collect();
+    let (_, _, events) = crate::parser::parse_events(&tokens);
+    build::build(source, &LineIndex::new(source), &tokens, &events)
+}
@@ -0,0 +1,918 @@
+//! Typed views over syntax nodes
+
+use crate::syntax::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+
+    fn cast(node: SyntaxNode) -> Option<Self>;
+
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+macro_rules! ast_nodes {
+    ($($name:ident),* $(,)?) => {$(
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $name(SyntaxNode);
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                kind == SyntaxKind::$name
+            }
+
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                Self::can_cast(node.kind()).then_some($name(node))
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.0
+            }
+        }
+    )*};
+}
+
+macro_rules! ast_enum {
+    ($enum:ident { $($name:ident),* $(,)? }) => {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum $enum {
+            $($name($name),)*
+        }
+
+        impl AstNode for $enum {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                matches!(kind, $(SyntaxKind::$name)|*)
+            }
+
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                match node.kind() {
+                    $(SyntaxKind::$name => Some($enum::$name($name(node))),)*
+                    _ => None,
+                }
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                match self {
+                    $($enum::$name(node) => node.syntax(),)*
+                }
+            }
+        }
+    };
+}
+
+ast_nodes!(
+    Root, Definition, Clause, Mod, Use, UseTree, TypeDecl, Variant, RecordField, TraitDecl, Signature, Impl, WhereClause,
+    Predicate, TraitRef, Attribute, AttributeGroup, Block, Name, Argument, Hole, Literal, Unit, Paren, Tuple, List, Field, Apply, Method,
+    Unary, Binary, For, While, Match, Arm, Lambda, Cast, Annotated, Yield, Assign, Range, Error, Missing,
+    WildcardPat, BindPat, GuardPat, LiteralPat, ConstructorPat, OptionalPat, RangePat, UnitPat, ParenPat, TuplePat, ListPat,
+    NameType, VarType, HoleType, ApplyType, UnitType, ParenType, TupleType, TypeField, FunctionType, DynType,
+);
+
+ast_enum!(Expr {
+    Block, Name, Argument, Hole, Literal, Unit, Paren, Tuple, List, Apply, Method, Unary, Binary, For, While, Match, Lambda,
+    Cast, Annotated, Yield, Assign, Range, Error, Missing,
+});
+
+ast_enum!(TypeExpr { NameType, VarType, HoleType, ApplyType, UnitType, ParenType, TupleType, FunctionType, DynType, Error });
+
+ast_enum!(Pattern {
+    WildcardPat, BindPat, GuardPat, LiteralPat, ConstructorPat, OptionalPat, RangePat, UnitPat, ParenPat, TuplePat, ListPat, Error,
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stmt {
+    Definition(Definition),
+    Clause(Clause),
+    Mod(Mod),
+    Use(Use),
+    TypeDecl(TypeDecl),
+    TraitDecl(TraitDecl),
+    Impl(Impl),
+    Signature(Signature),
+    Expr(Expr),
+}
+
+impl AstNode for Stmt {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        matches!(
+            kind,
+            SyntaxKind::Definition | SyntaxKind::Clause | SyntaxKind::Mod | SyntaxKind::Use | SyntaxKind::TypeDecl
+                | SyntaxKind::TraitDecl | SyntaxKind::Impl | SyntaxKind::Signature
+        ) || Expr::can_cast(kind)
+    }
+
+    fn cast(node: SyntaxNode) -> Option<Self> {
+        match node.kind() {
+            SyntaxKind::Definition => Some(Stmt::Definition(Definition(node))),
+            SyntaxKind::Clause => Some(Stmt::Clause(Clause(node))),
+            SyntaxKind::Mod => Some(Stmt::Mod(Mod(node))),
+            SyntaxKind::Use => Some(Stmt::Use(Use(node))),
+            SyntaxKind::TypeDecl => Some(Stmt::TypeDecl(TypeDecl(node))),
+            SyntaxKind::TraitDecl => Some(Stmt::TraitDecl(TraitDecl(node))),
+            SyntaxKind::Impl => Some(Stmt::Impl(Impl(node))),
+            SyntaxKind::Signature => Some(Stmt::Signature(Signature(node))),
+            _ => Expr::cast(node).map(Stmt::Expr),
+        }
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Stmt::Definition(node) => node.syntax(),
+            Stmt::Clause(node) => node.syntax(),
+            Stmt::Mod(node) => node.syntax(),
+            Stmt::Use(node) => node.syntax(),
+            Stmt::TypeDecl(node) => node.syntax(),
+            Stmt::TraitDecl(node) => node.syntax(),
+            Stmt::Impl(node) => node.syntax(),
+            Stmt::Signature(node) => node.syntax(),
+            Stmt::Expr(node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TupleField {
+    Named(Field),
+    Positional(Expr),
+}
+
+impl AstNode for TupleField {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        Field::can_cast(kind) || Expr::can_cast(kind)
+    }
+
+    fn cast(node: SyntaxNode) -> Option<Self> {
+        match node.kind() {
+            SyntaxKind::Field => Some(TupleField::Named(Field(node))),
+            _ => Expr::cast(node).map(TupleField::Positional),
+        }
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            TupleField::Named(node) => node.syntax(),
+            TupleField::Positional(node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TupleTypeField {
+    Named(TypeField),
+    Positional(TypeExpr),
+}
+
+impl AstNode for TupleTypeField {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        TypeField::can_cast(kind) || TypeExpr::can_cast(kind)
+    }
+
+    fn cast(node: SyntaxNode) -> Option<Self> {
+        match node.kind() {
+            SyntaxKind::TypeField => Some(TupleTypeField::Named(TypeField(node))),
+            _ => TypeExpr::cast(node).map(TupleTypeField::Positional),
+        }
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            TupleTypeField::Named(node) => node.syntax(),
+            TupleTypeField::Positional(node) => node.syntax(),
+        }
+    }
+}
+
+fn children<'n, N: AstNode + 'n>(node: &'n SyntaxNode) -> impl Iterator<Item = N> + 'n {
+    node.children().filter_map(N::cast)
+}
+
+fn child<N: AstNode>(node: &SyntaxNode) -> Option<N> {
+    children(node).next()
+}
+
+/// First non-trivia child token of `kind`
+fn token(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    node.child_tokens().find(|token| token.kind() == kind)
+}
+
+/// Children of type `N` before the child token `text`
+fn children_before<'n, N: AstNode + 'n>(node: &'n SyntaxNode, text: &'n str) -> impl Iterator<Item = N> + 'n {
+    node.children_with_tokens()
+        .take_while(move |child| !matches!(child, SyntaxElement::Token(token) if token.text() == text))
+        .filter_map(|child| match child {
+            SyntaxElement::Node(node) => N::cast(node),
+            SyntaxElement::Token(_) => None,
+        })
+}
+
+/// Child of type `N` right after the child token `text`
+fn child_after<N: AstNode>(node: &SyntaxNode, text: &str) -> Option<N> {
+    let mut found = false;
+    for child in node.children_with_tokens() {
+        match child {
+            SyntaxElement::Token(token) if token.text() == text => found = true,
+            SyntaxElement::Node(node) if found => return N::cast(node),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Bound of a range before or `after` its operator
+fn range_bound<N: AstNode>(node: &SyntaxNode, after: bool) -> Option<N> {
+    let mut found = false;
+    for child in node.children_with_tokens() {
+        match child {
+            SyntaxElement::Token(token) if token.kind() == SyntaxKind::Operator => found = true,
+            SyntaxElement::Node(node) if found == after => return N::cast(node),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `pub` or `priv` keyword of a declaration
+fn visibility(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.child_tokens().find(|token| token.kind() == SyntaxKind::Keyword && matches!(token.text(), "pub" | "priv"))
+}
+
+/// Attributes of a node, including the ones in `#[...]` groups
+fn attributes(node: &SyntaxNode) -> impl Iterator<Item = Attribute> + '_ {
+    node.children().flat_map(|child| match child.kind() {
+        SyntaxKind::AttributeGroup => children(&child).collect(),
+        _ => Attribute::cast(child).into_iter().collect::<Vec<_>>(),
+    })
+}
+
+/// `'name` parameters of a type or trait declaration
+fn type_params(node: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> + '_ {
+    node.child_tokens().filter(|token| token.kind() == SyntaxKind::ArgumentToken)
+}
+
+/// Single significant token of a leaf node
+fn leaf_token(node: &SyntaxNode) -> SyntaxToken {
+    node.child_tokens().find(|token| !token.kind().is_trivia()).unwrap()
+}
+
+impl Root {
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Block {
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Definition {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<SyntaxToken> {
+        visibility(&self.0)
+    }
+
+    pub fn is_mut(&self) -> bool {
+        self.0.child_tokens().any(|token| token.kind() == SyntaxKind::Keyword && token.text() == "mut")
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = Pattern> + '_ {
+        children_before(&self.0, ":=")
+    }
+
+    /// Type after `:` in `name params : Type :=`
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child_after(&self.0, ":")
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        child_after(&self.0, ":=")
+    }
+}
+
+impl Clause {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = Pattern> + '_ {
+        children_before(&self.0, "::=")
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        child_after(&self.0, "::=")
+    }
+}
+
+impl Mod {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<SyntaxToken> {
+        visibility(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+}
+
+impl Use {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<SyntaxToken> {
+        visibility(&self.0)
+    }
+
+    pub fn tree(&self) -> Option<UseTree> {
+        child(&self.0)
+    }
+}
+
+impl UseTree {
+    /// Path segments, including the imported item
+    pub fn path(&self) -> impl Iterator<Item = SyntaxToken> + '_ {
+        self.0.child_tokens()
+            .take_while(|token| !(token.kind() == SyntaxKind::Keyword && token.text() == "as"))
+            .filter(|token| token.kind() == SyntaxKind::Symbol)
+    }
+
+    pub fn rename(&self) -> Option<SyntaxToken> {
+        let mut tokens = self.0.child_tokens().skip_while(|token| !(token.kind() == SyntaxKind::Keyword && token.text() == "as"));
+        tokens.find(|token| token.kind() == SyntaxKind::Symbol)
+    }
+
+    pub fn is_glob(&self) -> bool {
+        self.0.child_tokens().any(|token| matches!(token.text(), ".*" | "*"))
+    }
+
+    /// Trees of a `path.(tree, ...)` group
+    pub fn trees(&self) -> impl Iterator<Item = UseTree> + '_ {
+        children(&self.0)
+    }
+}
+
+impl TypeDecl {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<SyntaxToken> {
+        visibility(&self.0)
+    }
+
+    /// Whether the type is a record declared with `class`
+    pub fn is_class(&self) -> bool {
+        self.0.child_tokens().any(|token| token.kind() == SyntaxKind::Keyword && token.text() == "class")
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = SyntaxToken> + '_ {
+        type_params(&self.0)
+    }
+
+    pub fn where_clause(&self) -> Option<WhereClause> {
+        child(&self.0)
+    }
+
+    /// Type after `:=` of an alias
+    pub fn alias(&self) -> Option<TypeExpr> {
+        child_after(&self.0, ":=")
+    }
+
+    pub fn variants(&self) -> impl Iterator<Item = Variant> + '_ {
+        children(&self.0)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = RecordField> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Variant {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = TypeExpr> + '_ {
+        children(&self.0)
+    }
+}
+
+impl RecordField {
+    pub fn visibility(&self) -> Option<SyntaxToken> {
+        visibility(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child(&self.0)
+    }
+}
+
+impl TraitDecl {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<SyntaxToken> {
+        visibility(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = SyntaxToken> + '_ {
+        type_params(&self.0)
+    }
+
+    pub fn where_clause(&self) -> Option<WhereClause> {
+        child(&self.0)
+    }
+
+    /// Signatures and definitions of the methods
+    pub fn items(&self) -> impl Iterator<Item = Stmt> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Signature {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<SyntaxToken> {
+        visibility(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = Pattern> + '_ {
+        children_before(&self.0, ":")
+    }
+
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child_after(&self.0, ":")
+    }
+}
+
+impl Impl {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn trait_ref(&self) -> Option<TraitRef> {
+        child(&self.0)
+    }
+
+    /// Type after `for`
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child_after(&self.0, "for")
+    }
+
+    pub fn where_clause(&self) -> Option<WhereClause> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+impl WhereClause {
+    pub fn predicates(&self) -> impl Iterator<Item = Predicate> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Predicate {
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child(&self.0)
+    }
+
+    pub fn bounds(&self) -> impl Iterator<Item = TraitRef> + '_ {
+        children(&self.0)
+    }
+}
+
+impl TraitRef {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = TypeExpr> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Attribute {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Literal> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Annotated {
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        attributes(&self.0)
+    }
+
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Name {
+    pub fn token(&self) -> SyntaxToken {
+        leaf_token(&self.0)
+    }
+}
+
+impl Argument {
+    pub fn token(&self) -> SyntaxToken {
+        leaf_token(&self.0)
+    }
+}
+
+impl Literal {
+    pub fn token(&self) -> SyntaxToken {
+        leaf_token(&self.0)
+    }
+}
+
+impl Paren {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Tuple {
+    pub fn fields(&self) -> impl Iterator<Item = TupleField> + '_ {
+        children(&self.0)
+    }
+}
+
+impl List {
+    pub fn elements(&self) -> impl Iterator<Item = Expr> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Field {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child_after(&self.0, ":=")
+    }
+}
+
+impl Apply {
+    pub fn func(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Expr> + '_ {
+        children(&self.0).skip(1)
+    }
+}
+
+impl Method {
+    /// `None` for a `.method args...` section
+    pub fn receiver(&self) -> Option<Expr> {
+        match self.0.children_with_tokens().find(|child| !child.kind().is_trivia())? {
+            SyntaxElement::Node(node) => Expr::cast(node),
+            SyntaxElement::Token(_) => None,
+        }
+    }
+
+    pub fn method(&self) -> Option<Expr> {
+        child_after(&self.0, ".")
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Expr> + '_ {
+        let skip = if self.receiver().is_some() { 2 } else { 1 };
+        children(&self.0).skip(skip)
+    }
+}
+
+impl Unary {
+    pub fn op(&self) -> Option<SyntaxToken> {
+        self.0.child_tokens().find(|token| !token.kind().is_trivia())
+    }
+
+    pub fn operand(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Binary {
+    pub fn lhs(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn op(&self) -> Option<SyntaxToken> {
+        self.0.child_tokens().find(|token| !token.kind().is_trivia())
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl For {
+    pub fn iter(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn pattern(&self) -> Option<Pattern> {
+        child_after(&self.0, "for")
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        child_after(&self.0, ":")
+    }
+}
+
+impl While {
+    pub fn cond(&self) -> Option<Expr> {
+        self.after_keyword("while")
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        self.after_keyword("then")
+    }
+
+    pub fn otherwise(&self) -> Option<Expr> {
+        self.after_keyword("else")
+    }
+
+    /// Expression after `keyword:`
+    fn after_keyword(&self, keyword: &str) -> Option<Expr> {
+        let mut found = false;
+        for child in self.0.children_with_tokens() {
+            match child {
+                SyntaxElement::Token(token) if token.kind() == SyntaxKind::Keyword => found = token.text() == keyword,
+                SyntaxElement::Node(node) if found => return Expr::cast(node),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl Match {
+    pub fn scrutinee(&self) -> Option<Expr> {
+        child_after(&self.0, "match")
+    }
+
+    pub fn arms(&self) -> impl Iterator<Item = Arm> + '_ {
+        children(&self.0)
+    }
+}
+
+impl Arm {
+    pub fn pattern(&self) -> Option<Pattern> {
+        child(&self.0)
+    }
+
+    pub fn guard(&self) -> Option<Expr> {
+        child_after(&self.0, "if")
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        child_after(&self.0, "=>")
+    }
+}
+
+impl Lambda {
+    pub fn params(&self) -> impl Iterator<Item = Pattern> + '_ {
+        children_before(&self.0, ":")
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        child_after(&self.0, ":")
+    }
+}
+
+impl Cast {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child_after(&self.0, "as")
+    }
+}
+
+impl Yield {
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Assign {
+    pub fn place(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    /// `<-` or an update operator like `+<-`
+    pub fn op(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Operator)
+    }
+
+    /// Value after the operator, the method for `.<-`
+    pub fn value(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl Range {
+    /// `..` or `..=`
+    pub fn op(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Operator)
+    }
+
+    pub fn is_inclusive(&self) -> bool {
+        self.op().is_some_and(|op| op.text() == "..=")
+    }
+
+    pub fn start(&self) -> Option<Expr> {
+        range_bound(&self.0, false)
+    }
+
+    pub fn end(&self) -> Option<Expr> {
+        range_bound(&self.0, true)
+    }
+}
+
+impl BindPat {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.child_tokens().find(|token| matches!(token.kind(), SyntaxKind::ArgumentToken | SyntaxKind::Symbol))
+    }
+
+    /// Pattern after `'name =`
+    pub fn pattern(&self) -> Option<Pattern> {
+        child(&self.0)
+    }
+}
+
+impl GuardPat {
+    pub fn guard(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl LiteralPat {
+    pub fn is_negative(&self) -> bool {
+        token(&self.0, SyntaxKind::Operator).is_some()
+    }
+
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.0.child_tokens().find(|token| matches!(token.kind(), SyntaxKind::Number | SyntaxKind::String | SyntaxKind::Character))
+    }
+}
+
+impl RangePat {
+    /// `..` or `..=`
+    pub fn op(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Operator)
+    }
+
+    pub fn is_inclusive(&self) -> bool {
+        self.op().is_some_and(|op| op.text() == "..=")
+    }
+
+    pub fn start(&self) -> Option<Pattern> {
+        range_bound(&self.0, false)
+    }
+
+    pub fn end(&self) -> Option<Pattern> {
+        range_bound(&self.0, true)
+    }
+}
+
+impl ConstructorPat {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Pattern> + '_ {
+        children(&self.0)
+    }
+}
+
+impl OptionalPat {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::ArgumentToken)
+    }
+
+    pub fn default(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl ParenPat {
+    pub fn pattern(&self) -> Option<Pattern> {
+        child(&self.0)
+    }
+}
+
+impl TuplePat {
+    pub fn elements(&self) -> impl Iterator<Item = Pattern> + '_ {
+        children(&self.0)
+    }
+}
+
+impl ListPat {
+    pub fn elements(&self) -> impl Iterator<Item = Pattern> + '_ {
+        children(&self.0)
+    }
+}
+
+impl NameType {
+    pub fn token(&self) -> SyntaxToken {
+        leaf_token(&self.0)
+    }
+}
+
+impl VarType {
+    pub fn token(&self) -> SyntaxToken {
+        leaf_token(&self.0)
+    }
+}
+
+impl ApplyType {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.child_tokens().find(|token| token.kind() == SyntaxKind::Symbol)
+    }
+
+    /// The type before the dot followed by the types after the name
+    pub fn args(&self) -> impl Iterator<Item = TypeExpr> + '_ {
+        children(&self.0)
+    }
+}
+
+impl ParenType {
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child(&self.0)
+    }
+}
+
+impl TupleType {
+    pub fn fields(&self) -> impl Iterator<Item = TupleTypeField> + '_ {
+        children(&self.0)
+    }
+}
+
+impl TypeField {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child_after(&self.0, ":")
+    }
+}
+
+impl FunctionType {
+    pub fn param(&self) -> Option<TypeExpr> {
+        child(&self.0)
+    }
+
+    pub fn result(&self) -> Option<TypeExpr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl DynType {
+    pub fn ty(&self) -> Option<TypeExpr> {
+        child(&self.0)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::ImStr;
+use crate::lexer::{Token, TokenType};
+use crate::parser::{Event, Span};
+use crate::syntax::{GreenElement, GreenNode, GreenToken, LineIndex, SyntaxKind};
+
+/// Builds the green tree from parser events, the source text between tokens becomes trivia
+pub(super) fn build(source: &str, index: &LineIndex, seq: &[Token], events: &[Event]) -> GreenNode {
+    let mut builder = Builder { source, index, seq, next: 0, offset: 0, stack: vec![] };
+
+    // first token at or after every event, trivia before it stays outside of nodes starting there
+    let mut first_tokens = vec![seq.len(); events.len()];
+    let mut first = seq.len();
+    for (i, event) in events.iter().enumerate().rev() {
+        if let Event::Token(token) = event {
+            first = *token;
+        }
+        first_tokens[i] = first;
+    }
+
+    let mut root = None;
+    for (i, event) in events.iter().enumerate() {
+        match *event {
+            Event::Start(kind) => {
+                // empty nodes stay right after the last token
+                if !builder.stack.is_empty() && events.get(i + 1) != Some(&Event::Finish) {
+                    builder.trivia(first_tokens[i]);
+                }
+                builder.stack.push((kind, vec![]));
+            }
+            Event::Token(token) => {
+                builder.trivia(token);
+                builder.token(token);
+            }
+            Event::Finish => {
+                if builder.stack.len() == 1 {
+                    builder.trivia(seq.len());
+                }
+                let (kind, children) = builder.stack.pop().unwrap();
+                let node = GreenNode::new(kind, children);
+                match builder.stack.last_mut() {
+                    Some((_, parent)) => parent.push(GreenElement::Node(node)),
+                    None => root = Some(node),
+                }
+            }
+        }
+    }
+    root.unwrap_or_else(|| {
+        builder.stack.push((SyntaxKind::Root, vec![]));
+        builder.trivia(seq.len());
+        GreenNode::new(SyntaxKind::Root, builder.stack.pop().unwrap().1)
+    })
+}
+
+struct Builder<'s> {
+    source: &'s str,
+    index: &'s LineIndex,
+    seq: &'s [Token],
+    /// Next token of `seq` to add
+    next: usize,
+    /// Source offset up to which the text is added
+    offset: usize,
+    stack: Vec<(SyntaxKind, Vec<GreenElement>)>,
+}
+
+impl Builder<'_> {
+    fn push(&mut self, kind: SyntaxKind, end: usize) {
+        let text = ImStr::from(&self.source[self.offset..end]);
+        self.stack.last_mut().unwrap().1.push(GreenElement::Token(GreenToken::new(kind, text)));
+        self.offset = end;
+    }
+
+    /// Adds the text and tokens skipped by the parser before token `until`
+    fn trivia(&mut self, until: usize) {
+        while self.next < until {
+            let token = &self.seq[self.next];
+            if !matches!(token.ty, TokenType::Indent) {
+                self.token(self.next);
+            }
+            self.next += 1;
+        }
+        let end = self.seq.get(until)
+            .map_or(self.source.len(), |token| self.index.offset(token.row, token.num));
+        self.gap(end);
+    }
+
+    /// Adds whitespace and line breaks up to `end`
+    fn gap(&mut self, end: usize) {
+        while self.offset < end {
+            let rest = &self.source[self.offset..end];
+            let (kind, len) = if rest.starts_with("\r\n") {
+                (SyntaxKind::Newline, 2)
+            } else if rest.starts_with('\n') {
+                (SyntaxKind::Newline, 1)
+            } else {
+                let len = rest.find(['\r', '\n']).unwrap_or(rest.len()).max(1);
+                let kind = match rest[..len].trim().is_empty() {
+                    true => SyntaxKind::Whitespace,
+                    false => SyntaxKind::Other,
+                };
+                (kind, len)
+            };
+            self.push(kind, self.offset + len);
+        }
+    }
+
+    fn token(&mut self, i: usize) {
+        let token = &self.seq[i];
+        let span = Span::of(token);
+        let start = self.index.offset(span.row, span.num);
+        self.gap(start);
+        let end = self.index.offset(span.end_row, span.end_num).max(self.offset);
+        self.push(SyntaxKind::of(token), end);
+        self.next = i + 1;
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use crate::ImStr;
+use crate::syntax::SyntaxKind;
+
+/// Immutable syntax tree node without a position, identical subtrees can be shared
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenNode(Rc<GreenNodeData>);
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct GreenNodeData {
+    kind: SyntaxKind,
+    text_len: usize,
+    children: Vec<GreenElement>,
+}
+
+/// Immutable token holding its source text
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenToken(Rc<GreenTokenData>);
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct GreenTokenData {
+    kind: SyntaxKind,
+    text: ImStr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(GreenNode),
+    Token(GreenToken),
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let text_len = children.iter().map(GreenElement::text_len).sum();
+        GreenNode(Rc::new(GreenNodeData { kind, text_len, children }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind
+    }
+
+    /// Length of the source text in bytes
+    pub fn text_len(&self) -> usize {
+        self.0.text_len
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.0.children
+    }
+
+    /// Copy of the node with the children in `range` replaced
+    pub fn splice(&self, range: std::ops::Range<usize>, replace_with: impl IntoIterator<Item = GreenElement>) -> Self {
+        let mut children = self.0.children.clone();
+        children.splice(range, replace_with);
+        GreenNode::new(self.kind(), children)
+    }
+
+    /// Whether both nodes are the same shared node
+    pub fn ptr_eq(&self, other: &GreenNode) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Display for GreenNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.children().iter().try_for_each(|child| match child {
+            GreenElement::Node(node) => write!(f, "{node}"),
+            GreenElement::Token(token) => write!(f, "{}", token.text()),
+        })
+    }
+}
+
+impl GreenToken {
+    pub fn new(kind: SyntaxKind, text: ImStr) -> Self {
+        GreenToken(Rc::new(GreenTokenData { kind, text }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    pub fn text_len(&self) -> usize {
+        self.0.text.len()
+    }
+}
+
+impl GreenElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            GreenElement::Node(node) => node.kind(),
+            GreenElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn text_len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.text_len(),
+            GreenElement::Token(token) => token.text_len(),
+        }
+    }
+}
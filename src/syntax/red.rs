@@ -0,0 +1,281 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Range;
+use std::rc::Rc;
+use crate::syntax::{GreenElement, GreenNode, GreenToken, SyntaxKind};
+
+/// Cursor over a green node knowing its parent and position in the source
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<SyntaxNodeData>);
+
+struct SyntaxNodeData {
+    green: GreenNode,
+    parent: Option<SyntaxNode>,
+    /// Index in the parent children
+    index: usize,
+    offset: usize,
+}
+
+/// Cursor over a green token
+#[derive(Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: GreenNode) -> Self {
+        SyntaxNode(Rc::new(SyntaxNodeData { green, parent: None, index: 0, offset: 0 }))
+    }
+
+    fn new_child(green: GreenNode, parent: SyntaxNode, index: usize, offset: usize) -> Self {
+        SyntaxNode(Rc::new(SyntaxNodeData { green, parent: Some(parent), index, offset }))
+    }
+
+    pub fn green(&self) -> &GreenNode {
+        &self.0.green
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind()
+    }
+
+    /// Byte range in the source
+    pub fn text_range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.text_len()
+    }
+
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// Index in the parent children
+    pub fn index(&self) -> usize {
+        self.0.index
+    }
+
+    /// The node itself and then its parents up to the root
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> + use<> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        let mut offset = self.0.offset;
+        self.green().children().iter().enumerate().map(move |(index, child)| {
+            let start = offset;
+            offset += child.text_len();
+            match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode::new_child(green.clone(), self.clone(), index, start)),
+                GreenElement::Token(_) => SyntaxElement::Token(SyntaxToken { parent: self.clone(), index, offset: start }),
+            }
+        })
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Direct child tokens, trivia included
+    pub fn child_tokens(&self) -> impl Iterator<Item = SyntaxToken> + '_ {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Node(_) => None,
+            SyntaxElement::Token(token) => Some(token),
+        })
+    }
+
+    /// The node itself and all nodes below it in preorder
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode> + use<> {
+        let mut stack = vec![self.clone()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let children: Vec<_> = node.children().collect();
+            stack.extend(children.into_iter().rev());
+            Some(node)
+        })
+    }
+
+    /// All tokens below the node in source order
+    pub fn tokens(&self) -> impl Iterator<Item = SyntaxToken> + use<> {
+        let mut stack = vec![SyntaxElement::Node(self.clone())];
+        std::iter::from_fn(move || loop {
+            match stack.pop()? {
+                SyntaxElement::Token(token) => return Some(token),
+                SyntaxElement::Node(node) => {
+                    let children: Vec<_> = node.children_with_tokens().collect();
+                    stack.extend(children.into_iter().rev());
+                }
+            }
+        })
+    }
+
+    pub fn first_token(&self) -> Option<SyntaxToken> {
+        self.tokens().next()
+    }
+
+    /// Token covering `offset`, the last token at the end of the text
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken> {
+        let mut last = None;
+        for child in self.children_with_tokens() {
+            let range = match &child {
+                SyntaxElement::Node(node) => node.text_range(),
+                SyntaxElement::Token(token) => token.text_range(),
+            };
+            if range.is_empty() {
+                continue;
+            }
+            if range.contains(&offset) {
+                return match child {
+                    SyntaxElement::Node(node) => node.token_at_offset(offset),
+                    SyntaxElement::Token(token) => Some(token),
+                };
+            }
+            last = Some(child);
+        }
+        match last? {
+            SyntaxElement::Node(node) if offset >= node.text_range().end => node.token_at_offset(offset),
+            SyntaxElement::Token(token) if offset >= token.text_range().end => Some(token),
+            _ => None,
+        }
+    }
+
+    /// Smallest node containing `range`
+    pub fn covering_node(&self, range: Range<usize>) -> SyntaxNode {
+        let mut node = self.clone();
+        loop {
+            let child = node.children().find(|child| {
+                let child_range = child.text_range();
+                child_range.start <= range.start && range.end <= child_range.end && !child_range.is_empty()
+            });
+            match child {
+                Some(child) => node = child,
+                None => return node,
+            }
+        }
+    }
+
+    pub fn next_sibling_or_token(&self) -> Option<SyntaxElement> {
+        let parent = self.parent()?;
+        parent.children_with_tokens().nth(self.index() + 1)
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.green().ptr_eq(other.green()) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl Debug for SyntaxNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}@{:?}", self.kind(), self.text_range())
+    }
+}
+
+impl Display for SyntaxNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.green())
+    }
+}
+
+impl SyntaxToken {
+    pub fn green(&self) -> &GreenToken {
+        match &self.parent.green().children()[self.index] {
+            GreenElement::Token(token) => token,
+            GreenElement::Node(_) => unreachable!(),
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.green().kind()
+    }
+
+    pub fn text(&self) -> &str {
+        self.green().text()
+    }
+
+    pub fn text_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green().text_len()
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    /// Index in the parent children
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Next token in source order, possibly in another node
+    pub fn next_token(&self) -> Option<SyntaxToken> {
+        let mut node = self.parent();
+        let mut index = self.index;
+        loop {
+            for child in node.children_with_tokens().skip(index + 1) {
+                match child {
+                    SyntaxElement::Token(token) => return Some(token),
+                    SyntaxElement::Node(child) => if let Some(token) = child.first_token() {
+                        return Some(token);
+                    },
+                }
+            }
+            index = node.index();
+            node = node.parent()?;
+        }
+    }
+
+    /// Previous token in source order, possibly in another node
+    pub fn prev_token(&self) -> Option<SyntaxToken> {
+        let mut node = self.parent();
+        let mut index = self.index;
+        loop {
+            let before: Vec<_> = node.children_with_tokens().take(index).collect();
+            for child in before.into_iter().rev() {
+                match child {
+                    SyntaxElement::Token(token) => return Some(token),
+                    SyntaxElement::Node(child) => if let Some(token) = child.tokens().last() {
+                        return Some(token);
+                    },
+                }
+            }
+            index = node.index();
+            node = node.parent()?;
+        }
+    }
+}
+
+impl Debug for SyntaxToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}@{:?} {:?}", self.kind(), self.text_range(), self.text())
+    }
+}
+
+impl Display for SyntaxToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind(),
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use std::ops::Range;
+use crate::lexer::{StringErrorFlags, Token, TokenIterator, TokenType};
+use crate::parser::{parse_events, ParseErrorKind};
+use crate::syntax::build::build;
+use crate::syntax::{parse_green, GreenElement, GreenNode, LineIndex, SyntaxKind, SyntaxNode, SyntaxToken};
+
+/// Replacement of the `delete` range of the source with `insert`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub delete: Range<usize>,
+    pub insert: String,
+}
+
+impl TextEdit {
+    pub fn apply(&self, text: &mut String) {
+        text.replace_range(self.delete.clone(), &self.insert);
+    }
+}
+
+/// Tree after an edit
+#[derive(Debug, Clone)]
+pub struct Reparse {
+    pub green: GreenNode,
+    /// Ranges of the new text covered by rebuilt nodes, the rest of the tree is shared with the old one
+    pub changed: Vec<Range<usize>>,
+}
+
+/// Updates the tree of a source after `edit`.
+/// Only the innermost top-level statement or indentation block holding the edit is lexed and parsed again,
+/// unless the edit can change how the code around it is parsed
+pub fn reparse(green: &GreenNode, edit: &TextEdit) -> Reparse {
+    let root = SyntaxNode::new_root(green.clone());
+    let units = root.covering_node(edit.delete.clone()).ancestors()
+        .filter(|node| node.kind() == SyntaxKind::Block || node.parent().is_some_and(|parent| parent.parent().is_none()));
+    for unit in units {
+        if let Some(reparse) = reparse_unit(&root, &unit, edit) {
+            return reparse;
+        }
+    }
+
+    let mut text = root.text();
+    edit.apply(&mut text);
+    let new = parse_green(&text);
+    let (children, changed) = reuse(green.children(), new.children().to_vec(), 0);
+    Reparse { green: GreenNode::new(SyntaxKind::Root, children), changed: changed.into_iter().collect() }
+}
+
+fn reparse_unit(root: &SyntaxNode, unit: &SyntaxNode, edit: &TextEdit) -> Option<Reparse> {
+    let range = unit.text_range();
+    if edit.delete.start <= range.start || range.end < edit.delete.end {
+        return None;
+    }
+    let parent = unit.parent()?;
+    let index = unit.index();
+    // an empty node or attributes before the unit are a part of the same statement
+    let before = parent.green().children()[..index].iter().rev().find(|child| !child.kind().is_trivia());
+    let attached = |child: &GreenElement| matches!(child.kind(), SyntaxKind::Attribute | SyntaxKind::AttributeGroup);
+    if before.is_some_and(|child| child.text_len() == 0 || attached(child)) {
+        return None;
+    }
+    // code in open brackets before the unit can look into it
+    if open_brackets(root, range.start) {
+        return None;
+    }
+    let prefix = line_prefix(unit)?;
+    // only lines starting at the first column are sure to start top-level statements
+    if unit.kind() != SyntaxKind::Block && !prefix.is_empty() {
+        return None;
+    }
+    let (rest, line_end) = line_rest(unit)?;
+    if let Some((indent, token)) = next_line(line_end.clone())
+        && (indent > prefix.chars().count() || (indent == prefix.chars().count() && is_continuation(token.text())))
+    {
+        return None;
+    }
+
+    // the rest of the line is lexed together with the unit
+    let mut text = unit.text();
+    text.replace_range(edit.delete.start - range.start..edit.delete.end - range.start, &edit.insert);
+    let rest_text: String = rest.iter().map(SyntaxToken::text).collect();
+    let source = prefix.clone() + &text + &rest_text;
+    let tokens: Vec<_> = TokenIterator::new(&source).collect();
+    let (_, diagnostics, events) = parse_events(&tokens);
+    // attributes ending the unit apply to the line after it
+    let open_ended = diagnostics.iter()
+        .any(|diagnostic| matches!(diagnostic.kind, ParseErrorKind::UnclosedBracket | ParseErrorKind::DanglingAttribute));
+    if open_ended || !is_self_contained(&tokens) {
+        return None;
+    }
+
+    let green = build(&source, &LineIndex::new(&source), &tokens, &events);
+    let mut children = green.children().to_vec();
+    if !prefix.is_empty() {
+        children.remove(0);
+    }
+    // empty nodes at the start would stay right after the code before the unit
+    if children.first().is_none_or(|first| first.kind().is_trivia() || first.text_len() == 0) {
+        return None;
+    }
+    let trailing = children.split_off(children.iter().rposition(|child| !child.kind().is_trivia())? + 1);
+    let trailing_len: usize = trailing.iter().map(GreenElement::text_len).sum();
+    if trailing_len < rest_text.len() {
+        return None;
+    }
+    if let (Some(GreenElement::Token(last)), Some(line_end)) = (trailing.last(), &line_end)
+        && last.text() == "\r" && line_end.text().starts_with('\n')
+    {
+        return None;
+    }
+
+    // trivia after the unit replaces the rest of the line where the old tree has it
+    let nodes_end = range.start + text.len() + rest_text.len() - trailing_len;
+    let old_trailing: Vec<_> = rest.iter().map(|token| GreenElement::Token(token.green().clone())).collect();
+    let trailing_changed = (trailing != old_trailing).then_some(nodes_end..nodes_end + trailing_len);
+    let mut green = match rest.first().or(line_end.as_ref()) {
+        Some(after) => {
+            let siblings = after.index()..after.index() + rest.len();
+            splice_at(root.green(), &path(&after.parent()), siblings, trailing)
+        }
+        None => {
+            let len = root.green().children().len();
+            root.green().splice(len..len, trailing)
+        }
+    };
+
+    let changed = if unit.kind() == SyntaxKind::Block {
+        let (children, changed) = reuse(unit.green().children(), children, range.start);
+        let block = GreenElement::Node(GreenNode::new(SyntaxKind::Block, children));
+        green = splice_at(&green, &path(&parent), index..index + 1, [block]);
+        changed
+    } else {
+        let (children, changed) = reuse(&parent.green().children()[index..index + 1], children, range.start);
+        green = splice_at(&green, &path(&parent), index..index + 1, children);
+        changed
+    };
+    Some(Reparse { green, changed: changed.into_iter().chain(trailing_changed).collect() })
+}
+
+/// Whether some brackets opened before `offset` aren't closed there
+fn open_brackets(root: &SyntaxNode, offset: usize) -> bool {
+    let depth = root.tokens()
+        .take_while(|token| token.text_range().end <= offset)
+        .fold(0usize, |depth, token| match token.kind() {
+            SyntaxKind::Open => depth + 1,
+            SyntaxKind::Close => depth.saturating_sub(1),
+            _ => depth,
+        });
+    depth > 0
+}
+
+/// Whitespace between the line start and `node`, `None` if something else is before it
+fn line_prefix(node: &SyntaxNode) -> Option<String> {
+    let Some(before) = node.first_token()?.prev_token() else { return Some(String::new()) };
+    match before.kind() {
+        SyntaxKind::Newline => Some(String::new()),
+        SyntaxKind::Whitespace => match before.prev_token() {
+            None => Some(before.text().to_string()),
+            Some(token) if token.kind() == SyntaxKind::Newline => Some(before.text().to_string()),
+            Some(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Trivia tokens after `node` up to the end of its line and the line break there,
+/// `None` if something else follows the node on the line
+fn line_rest(node: &SyntaxNode) -> Option<(Vec<SyntaxToken>, Option<SyntaxToken>)> {
+    let last = node.tokens().last()?;
+    let mut rest: Vec<SyntaxToken> = vec![];
+    let mut token = last.next_token();
+    while let Some(current) = token.clone().filter(|token| token.kind() != SyntaxKind::Newline) {
+        let consecutive = rest.last().is_none_or(|prev| prev.parent() == current.parent() && prev.index() + 1 == current.index());
+        if !current.kind().is_trivia() || !consecutive {
+            return None;
+        }
+        token = current.next_token();
+        rest.push(current);
+    }
+    Some((rest, token))
+}
+
+/// Indentation width and first token of the next line holding code
+fn next_line(line_end: Option<SyntaxToken>) -> Option<(usize, SyntaxToken)> {
+    let mut indent = 0;
+    let mut token = line_end;
+    while let Some(current) = token {
+        match current.kind() {
+            SyntaxKind::Newline | SyntaxKind::Comment => indent = 0,
+            SyntaxKind::Whitespace => indent = current.text().chars().count(),
+            _ => return Some((indent, current)),
+        }
+        token = current.next_token();
+    }
+    None
+}
+
+fn is_continuation(text: &str) -> bool {
+    matches!(text, "then" | "else")
+}
+
+/// Whether tokens of a reparsed unit can't reach the code around it:
+/// all lines are indented at least as the first one, strings are closed, brackets are balanced
+/// and the first token doesn't continue the code before it
+fn is_self_contained(tokens: &[Token]) -> bool {
+    let mut indents = tokens.iter().filter(|token| matches!(token.ty, TokenType::Indent));
+    let Some(first) = indents.next() else { return false };
+    let width = first.slice.chars().count();
+    if indents.any(|indent| indent.slice.chars().count() < width) {
+        return false;
+    }
+    let first_token = tokens.iter().find(|token| !matches!(token.ty, TokenType::Indent));
+    if first_token.is_some_and(|token| matches!(&token.ty, TokenType::Keyword(keyword) if is_continuation(keyword))) {
+        return false;
+    }
+    // lookahead for definitions goes past unclosed brackets, a stray closing one ends a bracket around it
+    let depth = tokens.iter().try_fold(0usize, |depth, token| match token.ty {
+        TokenType::Open => Some(depth + 1),
+        TokenType::Close => depth.checked_sub(1),
+        _ => Some(depth),
+    });
+    depth == Some(0) && !tokens.iter().any(|token| {
+        matches!(&token.ty, TokenType::String { errors, .. } if errors.contains(StringErrorFlags::UNCLOSED))
+    })
+}
+
+/// Child indices leading from the root to `node`
+fn path(node: &SyntaxNode) -> Vec<usize> {
+    let mut path: Vec<_> = node.ancestors()
+        .take_while(|node| node.parent().is_some())
+        .map(|node| node.index())
+        .collect();
+    path.reverse();
+    path
+}
+
+/// Copy of `root` with children `range` of the node at `path` replaced
+fn splice_at(root: &GreenNode, path: &[usize], range: Range<usize>, replace_with: impl IntoIterator<Item = GreenElement>) -> GreenNode {
+    match path.split_first() {
+        None => root.splice(range, replace_with),
+        Some((&index, rest)) => {
+            let GreenElement::Node(child) = &root.children()[index] else { unreachable!() };
+            let child = splice_at(child, rest, range, replace_with);
+            root.splice(index..index + 1, [GreenElement::Node(child)])
+        }
+    }
+}
+
+/// Replaces `new` elements equal to the `old` ones at the start and the end with the old ones,
+/// returns the range left in between
+fn reuse(old: &[GreenElement], mut new: Vec<GreenElement>, offset: usize) -> (Vec<GreenElement>, Option<Range<usize>>) {
+    let prefix = old.iter().zip(&new).take_while(|(old, new)| old == new).count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old.iter().rev().zip(new.iter().rev()).take(max_suffix).take_while(|(old, new)| old == new).count();
+    if prefix == old.len() && prefix == new.len() {
+        return (old.to_vec(), None);
+    }
+
+    new[..prefix].clone_from_slice(&old[..prefix]);
+    let (new_len, old_len) = (new.len(), old.len());
+    new[new_len - suffix..].clone_from_slice(&old[old_len - suffix..]);
+    let len = |elements: &[GreenElement]| elements.iter().map(GreenElement::text_len).sum::<usize>();
+    let start = offset + len(&new[..prefix]);
+    let end = start + len(&new[prefix..new_len - suffix]);
+    (new, Some(start..end))
+}
@@ -0,0 +1,402 @@
+//! Traversal of syntax trees.
+//!
+//! [`Visit`] walks a tree by reference, [`VisitMut`] by mutable reference and [`Fold`] rebuilds it by value.
+//! Every node type has a method whose default recurses into the children of the node through the function
+//! of the same name: a pass overrides the methods of the nodes it cares about and calls the function
+//! to keep walking below them. Children are visited in source order, spans before the nodes they cover
+
+use crate::parser::*;
+
+mod fold;
+mod visit_mut;
+
+pub use self::fold::*;
+pub use self::visit_mut::*;
+
+pub trait Visit {
+    fn visit_ast(&mut self, ast: &Ast) {
+        walk_ast(self, ast)
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_let(&mut self, let_: &Let) {
+        walk_let(self, let_)
+    }
+
+    fn visit_function(&mut self, function: &FunctionDef) {
+        walk_function(self, function)
+    }
+
+    fn visit_clause(&mut self, clause: &Clause) {
+        walk_clause(self, clause)
+    }
+
+    fn visit_mod(&mut self, module: &ModDecl) {
+        walk_mod(self, module)
+    }
+
+    fn visit_use(&mut self, use_: &UseDecl) {
+        walk_use(self, use_)
+    }
+
+    fn visit_use_tree(&mut self, tree: &UseTree) {
+        walk_use_tree(self, tree)
+    }
+
+    fn visit_type_decl(&mut self, decl: &TypeDecl) {
+        walk_type_decl(self, decl)
+    }
+
+    fn visit_variant(&mut self, variant: &Variant) {
+        walk_variant(self, variant)
+    }
+
+    fn visit_record_field(&mut self, field: &RecordField) {
+        walk_record_field(self, field)
+    }
+
+    fn visit_trait(&mut self, decl: &TraitDecl) {
+        walk_trait(self, decl)
+    }
+
+    fn visit_impl(&mut self, decl: &ImplDecl) {
+        walk_impl(self, decl)
+    }
+
+    fn visit_signature(&mut self, signature: &Signature) {
+        walk_signature(self, signature)
+    }
+
+    fn visit_predicate(&mut self, predicate: &Predicate) {
+        walk_predicate(self, predicate)
+    }
+
+    fn visit_trait_ref(&mut self, trait_ref: &TraitRef) {
+        walk_trait_ref(self, trait_ref)
+    }
+
+    fn visit_attribute(&mut self, attr: &Attribute) {
+        walk_attribute(self, attr)
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        walk_ident(self, ident)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_field(&mut self, field: &Field) {
+        walk_field(self, field)
+    }
+
+    fn visit_arm(&mut self, arm: &Arm) {
+        walk_arm(self, arm)
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_type(&mut self, ty: &TypeExpr) {
+        walk_type(self, ty)
+    }
+
+    fn visit_type_field(&mut self, field: &TypeField) {
+        walk_type_field(self, field)
+    }
+
+    fn visit_literal(&mut self, _literal: &Literal) {}
+
+    fn visit_span(&mut self, _span: Span) {}
+}
+
+pub fn walk_ast<V: Visit + ?Sized>(visitor: &mut V, ast: &Ast) {
+    ast.stmts.iter().for_each(|stmt| visitor.visit_stmt(stmt));
+}
+
+pub fn walk_stmt<V: Visit + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::Let(let_) => visitor.visit_let(let_),
+        Stmt::Function(function) => visitor.visit_function(function),
+        Stmt::Mod(module) => visitor.visit_mod(module),
+        Stmt::Use(use_) => visitor.visit_use(use_),
+        Stmt::Type(decl) => visitor.visit_type_decl(decl),
+        Stmt::Trait(decl) => visitor.visit_trait(decl),
+        Stmt::Impl(decl) => visitor.visit_impl(decl),
+        Stmt::Signature(signature) => visitor.visit_signature(signature),
+        Stmt::Expr(expr) => visitor.visit_expr(expr),
+    }
+}
+
+pub fn walk_let<V: Visit + ?Sized>(visitor: &mut V, let_: &Let) {
+    visitor.visit_span(let_.span);
+    let_.attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+    visitor.visit_ident(&let_.name);
+    if let Some(ty) = &let_.ty {
+        visitor.visit_type(ty);
+    }
+    visitor.visit_expr(&let_.value);
+}
+
+pub fn walk_function<V: Visit + ?Sized>(visitor: &mut V, function: &FunctionDef) {
+    visitor.visit_span(function.span);
+    function.attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+    visitor.visit_ident(&function.name);
+    function.params.iter().for_each(|param| visitor.visit_pattern(param));
+    if let Some(ty) = &function.ty {
+        visitor.visit_type(ty);
+    }
+    if let Some(body) = &function.body {
+        visitor.visit_expr(body);
+    }
+    function.clauses.iter().for_each(|clause| visitor.visit_clause(clause));
+}
+
+pub fn walk_clause<V: Visit + ?Sized>(visitor: &mut V, clause: &Clause) {
+    visitor.visit_span(clause.span);
+    clause.params.iter().for_each(|param| visitor.visit_pattern(param));
+    visitor.visit_expr(&clause.body);
+}
+
+pub fn walk_mod<V: Visit + ?Sized>(visitor: &mut V, module: &ModDecl) {
+    visitor.visit_span(module.span);
+    module.attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+    visitor.visit_ident(&module.name);
+}
+
+pub fn walk_use<V: Visit + ?Sized>(visitor: &mut V, use_: &UseDecl) {
+    visitor.visit_span(use_.span);
+    use_.attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+    visitor.visit_use_tree(&use_.tree);
+}
+
+pub fn walk_use_tree<V: Visit + ?Sized>(visitor: &mut V, tree: &UseTree) {
+    visitor.visit_span(tree.span);
+    tree.path.iter().for_each(|segment| visitor.visit_ident(segment));
+    match &tree.kind {
+        UseKind::Single { rename: Some(rename) } => visitor.visit_ident(rename),
+        UseKind::Single { rename: None } | UseKind::Glob => {}
+        UseKind::Group(trees) => trees.iter().for_each(|tree| visitor.visit_use_tree(tree)),
+    }
+}
+
+pub fn walk_type_decl<V: Visit + ?Sized>(visitor: &mut V, decl: &TypeDecl) {
+    visitor.visit_span(decl.span);
+    decl.attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+    visitor.visit_ident(&decl.name);
+    decl.params.iter().for_each(|param| visitor.visit_ident(param));
+    decl.where_clause.iter().for_each(|predicate| visitor.visit_predicate(predicate));
+    match &decl.body {
+        TypeBody::Alias(ty) => visitor.visit_type(ty),
+        TypeBody::Variants(variants) => variants.iter().for_each(|variant| visitor.visit_variant(variant)),
+        TypeBody::Record(fields) => fields.iter().for_each(|field| visitor.visit_record_field(field)),
+    }
+}
+
+pub fn walk_variant<V: Visit + ?Sized>(visitor: &mut V, variant: &Variant) {
+    visitor.visit_span(variant.span);
+    visitor.visit_ident(&variant.name);
+    variant.fields.iter().for_each(|field| visitor.visit_type(field));
+}
+
+pub fn walk_record_field<V: Visit + ?Sized>(visitor: &mut V, field: &RecordField) {
+    visitor.visit_span(field.span);
+    visitor.visit_ident(&field.name);
+    visitor.visit_type(&field.ty);
+}
+
+pub fn walk_trait<V: Visit + ?Sized>(visitor: &mut V, decl: &TraitDecl) {
+    visitor.visit_span(decl.span);
+    decl.attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+    visitor.visit_ident(&decl.name);
+    decl.params.iter().for_each(|param| visitor.visit_ident(param));
+    decl.where_clause.iter().for_each(|predicate| visitor.visit_predicate(predicate));
+    decl.items.iter().for_each(|item| visitor.visit_stmt(item));
+}
+
+pub fn walk_impl<V: Visit + ?Sized>(visitor: &mut V, decl: &ImplDecl) {
+    visitor.visit_span(decl.span);
+    decl.attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+    visitor.visit_trait_ref(&decl.trait_ref);
+    visitor.visit_type(&decl.ty);
+    decl.where_clause.iter().for_each(|predicate| visitor.visit_predicate(predicate));
+    decl.items.iter().for_each(|item| visitor.visit_stmt(item));
+}
+
+pub fn walk_signature<V: Visit + ?Sized>(visitor: &mut V, signature: &Signature) {
+    visitor.visit_span(signature.span);
+    signature.attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+    visitor.visit_ident(&signature.name);
+    signature.params.iter().for_each(|param| visitor.visit_pattern(param));
+    visitor.visit_type(&signature.ty);
+}
+
+pub fn walk_predicate<V: Visit + ?Sized>(visitor: &mut V, predicate: &Predicate) {
+    visitor.visit_span(predicate.span);
+    visitor.visit_type(&predicate.ty);
+    predicate.bounds.iter().for_each(|bound| visitor.visit_trait_ref(bound));
+}
+
+pub fn walk_trait_ref<V: Visit + ?Sized>(visitor: &mut V, trait_ref: &TraitRef) {
+    visitor.visit_span(trait_ref.span);
+    visitor.visit_ident(&trait_ref.name);
+    trait_ref.args.iter().for_each(|arg| visitor.visit_type(arg));
+}
+
+pub fn walk_attribute<V: Visit + ?Sized>(visitor: &mut V, attr: &Attribute) {
+    visitor.visit_span(attr.span);
+    visitor.visit_ident(&attr.name);
+    attr.args.iter().for_each(|arg| visitor.visit_expr(arg));
+}
+
+pub fn walk_ident<V: Visit + ?Sized>(visitor: &mut V, ident: &Ident) {
+    visitor.visit_span(ident.span);
+}
+
+pub fn walk_expr<V: Visit + ?Sized>(visitor: &mut V, expr: &Expr) {
+    visitor.visit_span(expr.span);
+    match &expr.kind {
+        ExprKind::Literal(literal) => visitor.visit_literal(literal),
+        ExprKind::Tuple(fields) => fields.iter().for_each(|field| visitor.visit_field(field)),
+        ExprKind::List(elements) => elements.iter().for_each(|element| visitor.visit_expr(element)),
+        ExprKind::Apply { func, args } => {
+            visitor.visit_expr(func);
+            args.iter().for_each(|arg| visitor.visit_expr(arg));
+        }
+        ExprKind::Method { receiver, method, args } => {
+            if let Some(receiver) = receiver {
+                visitor.visit_expr(receiver);
+            }
+            visitor.visit_expr(method);
+            args.iter().for_each(|arg| visitor.visit_expr(arg));
+        }
+        ExprKind::Unary { op, operand } => {
+            visitor.visit_ident(op);
+            visitor.visit_expr(operand);
+        }
+        ExprKind::Binary { op, lhs, rhs } => {
+            visitor.visit_expr(lhs);
+            visitor.visit_ident(op);
+            visitor.visit_expr(rhs);
+        }
+        ExprKind::Block(stmts) => stmts.iter().for_each(|stmt| visitor.visit_stmt(stmt)),
+        ExprKind::For { iter, pattern, body } => {
+            visitor.visit_expr(iter);
+            visitor.visit_pattern(pattern);
+            visitor.visit_expr(body);
+        }
+        ExprKind::While { cond, body, otherwise } => {
+            visitor.visit_expr(cond);
+            for expr in [body, otherwise].into_iter().flatten() {
+                visitor.visit_expr(expr);
+            }
+        }
+        ExprKind::Cast { expr, ty } => {
+            visitor.visit_expr(expr);
+            visitor.visit_type(ty);
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+            arms.iter().for_each(|arm| visitor.visit_arm(arm));
+        }
+        ExprKind::Lambda { params, body, captures, .. } => {
+            params.iter().for_each(|param| visitor.visit_pattern(param));
+            visitor.visit_expr(body);
+            captures.iter().for_each(|capture| visitor.visit_ident(capture));
+        }
+        ExprKind::Annotated { attrs, expr } => {
+            attrs.iter().for_each(|attr| visitor.visit_attribute(attr));
+            visitor.visit_expr(expr);
+        }
+        ExprKind::Yield(value) => visitor.visit_expr(value),
+        ExprKind::Assign { place, op, value } => {
+            visitor.visit_expr(place);
+            visitor.visit_ident(op);
+            visitor.visit_expr(value);
+        }
+        ExprKind::Range { start, end, .. } => [start, end].into_iter().flatten().for_each(|bound| visitor.visit_expr(bound)),
+        ExprKind::Name(_) | ExprKind::Argument(_) | ExprKind::Hole | ExprKind::Unit | ExprKind::Error
+        | ExprKind::Missing => {}
+    }
+}
+
+pub fn walk_field<V: Visit + ?Sized>(visitor: &mut V, field: &Field) {
+    if let Some(name) = &field.name {
+        visitor.visit_ident(name);
+    }
+    visitor.visit_expr(&field.value);
+}
+
+pub fn walk_arm<V: Visit + ?Sized>(visitor: &mut V, arm: &Arm) {
+    visitor.visit_span(arm.span);
+    visitor.visit_pattern(&arm.pattern);
+    if let Some(guard) = &arm.guard {
+        visitor.visit_expr(guard);
+    }
+    visitor.visit_expr(&arm.body);
+}
+
+pub fn walk_pattern<V: Visit + ?Sized>(visitor: &mut V, pattern: &Pattern) {
+    visitor.visit_span(pattern.span);
+    match &pattern.kind {
+        PatternKind::Bind { name, pattern, guard } => {
+            visitor.visit_ident(name);
+            if let Some(pattern) = pattern {
+                visitor.visit_pattern(pattern);
+            }
+            if let Some(guard) = guard {
+                visitor.visit_expr(guard);
+            }
+        }
+        PatternKind::Literal { value, .. } => visitor.visit_literal(value),
+        PatternKind::Constructor { name, args } => {
+            visitor.visit_ident(name);
+            args.iter().for_each(|arg| visitor.visit_pattern(arg));
+        }
+        PatternKind::Tuple(elements) | PatternKind::List(elements) => {
+            elements.iter().for_each(|element| visitor.visit_pattern(element));
+        }
+        PatternKind::Optional { name, default } => {
+            visitor.visit_ident(name);
+            if let Some(default) = default {
+                visitor.visit_expr(default);
+            }
+        }
+        PatternKind::Range { start, end, .. } => {
+            [start, end].into_iter().flatten().for_each(|bound| visitor.visit_pattern(bound));
+        }
+        PatternKind::Wildcard | PatternKind::Unit | PatternKind::Error => {}
+    }
+}
+
+pub fn walk_type<V: Visit + ?Sized>(visitor: &mut V, ty: &TypeExpr) {
+    visitor.visit_span(ty.span);
+    match &ty.kind {
+        // `first .Name rest...`
+        TypeExprKind::Apply { name, args } => {
+            args.iter().take(1).for_each(|arg| visitor.visit_type(arg));
+            visitor.visit_ident(name);
+            args.iter().skip(1).for_each(|arg| visitor.visit_type(arg));
+        }
+        TypeExprKind::Tuple(fields) => fields.iter().for_each(|field| visitor.visit_type_field(field)),
+        TypeExprKind::Function { param, result } => {
+            visitor.visit_type(param);
+            visitor.visit_type(result);
+        }
+        TypeExprKind::Dyn(inner) => visitor.visit_type(inner),
+        TypeExprKind::Name(_) | TypeExprKind::Var(_) | TypeExprKind::Hole | TypeExprKind::Unit
+        | TypeExprKind::Error => {}
+    }
+}
+
+pub fn walk_type_field<V: Visit + ?Sized>(visitor: &mut V, field: &TypeField) {
+    if let Some(name) = &field.name {
+        visitor.visit_ident(name);
+    }
+    visitor.visit_type(&field.ty);
+}
@@ -0,0 +1,444 @@
+use crate::parser::*;
+
+pub trait Fold {
+    fn fold_ast(&mut self, ast: Ast) -> Ast {
+        fold_ast(self, ast)
+    }
+
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        fold_stmt(self, stmt)
+    }
+
+    fn fold_let(&mut self, let_: Let) -> Let {
+        fold_let(self, let_)
+    }
+
+    fn fold_function(&mut self, function: FunctionDef) -> FunctionDef {
+        fold_function(self, function)
+    }
+
+    fn fold_clause(&mut self, clause: Clause) -> Clause {
+        fold_clause(self, clause)
+    }
+
+    fn fold_mod(&mut self, module: ModDecl) -> ModDecl {
+        fold_mod(self, module)
+    }
+
+    fn fold_use(&mut self, use_: UseDecl) -> UseDecl {
+        fold_use(self, use_)
+    }
+
+    fn fold_use_tree(&mut self, tree: UseTree) -> UseTree {
+        fold_use_tree(self, tree)
+    }
+
+    fn fold_type_decl(&mut self, decl: TypeDecl) -> TypeDecl {
+        fold_type_decl(self, decl)
+    }
+
+    fn fold_variant(&mut self, variant: Variant) -> Variant {
+        fold_variant(self, variant)
+    }
+
+    fn fold_record_field(&mut self, field: RecordField) -> RecordField {
+        fold_record_field(self, field)
+    }
+
+    fn fold_trait(&mut self, decl: TraitDecl) -> TraitDecl {
+        fold_trait(self, decl)
+    }
+
+    fn fold_impl(&mut self, decl: ImplDecl) -> ImplDecl {
+        fold_impl(self, decl)
+    }
+
+    fn fold_signature(&mut self, signature: Signature) -> Signature {
+        fold_signature(self, signature)
+    }
+
+    fn fold_predicate(&mut self, predicate: Predicate) -> Predicate {
+        fold_predicate(self, predicate)
+    }
+
+    fn fold_trait_ref(&mut self, trait_ref: TraitRef) -> TraitRef {
+        fold_trait_ref(self, trait_ref)
+    }
+
+    fn fold_attribute(&mut self, attr: Attribute) -> Attribute {
+        fold_attribute(self, attr)
+    }
+
+    fn fold_ident(&mut self, ident: Ident) -> Ident {
+        fold_ident(self, ident)
+    }
+
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        fold_expr(self, expr)
+    }
+
+    fn fold_field(&mut self, field: Field) -> Field {
+        fold_field(self, field)
+    }
+
+    fn fold_arm(&mut self, arm: Arm) -> Arm {
+        fold_arm(self, arm)
+    }
+
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        fold_pattern(self, pattern)
+    }
+
+    fn fold_type(&mut self, ty: TypeExpr) -> TypeExpr {
+        fold_type(self, ty)
+    }
+
+    fn fold_type_field(&mut self, field: TypeField) -> TypeField {
+        fold_type_field(self, field)
+    }
+
+    fn fold_literal(&mut self, literal: Literal) -> Literal {
+        literal
+    }
+
+    fn fold_span(&mut self, span: Span) -> Span {
+        span
+    }
+}
+
+fn fold_all<T>(items: Vec<T>, fold: impl FnMut(T) -> T) -> Vec<T> {
+    items.into_iter().map(fold).collect()
+}
+
+/// Folds the content of `item` in place of the old one, keeping the allocation
+fn fold_box<T>(mut item: Box<T>, fold: impl FnOnce(T) -> T) -> Box<T> {
+    *item = fold(*item);
+    item
+}
+
+pub fn fold_ast<F: Fold + ?Sized>(folder: &mut F, ast: Ast) -> Ast {
+    Ast { stmts: fold_all(ast.stmts, |stmt| folder.fold_stmt(stmt)), definition_site: ast.definition_site }
+}
+
+pub fn fold_stmt<F: Fold + ?Sized>(folder: &mut F, stmt: Stmt) -> Stmt {
+    match stmt {
+        Stmt::Let(let_) => Stmt::Let(folder.fold_let(let_)),
+        Stmt::Function(function) => Stmt::Function(folder.fold_function(function)),
+        Stmt::Mod(module) => Stmt::Mod(folder.fold_mod(module)),
+        Stmt::Use(use_) => Stmt::Use(folder.fold_use(use_)),
+        Stmt::Type(decl) => Stmt::Type(folder.fold_type_decl(decl)),
+        Stmt::Trait(decl) => Stmt::Trait(folder.fold_trait(decl)),
+        Stmt::Impl(decl) => Stmt::Impl(folder.fold_impl(decl)),
+        Stmt::Signature(signature) => Stmt::Signature(folder.fold_signature(signature)),
+        Stmt::Expr(expr) => Stmt::Expr(folder.fold_expr(expr)),
+    }
+}
+
+pub fn fold_let<F: Fold + ?Sized>(folder: &mut F, let_: Let) -> Let {
+    let Let { attrs, vis, name, mutable, ty, value, span } = let_;
+    Let {
+        span: folder.fold_span(span),
+        attrs: fold_all(attrs, |attr| folder.fold_attribute(attr)),
+        vis,
+        name: folder.fold_ident(name),
+        mutable,
+        ty: ty.map(|ty| folder.fold_type(ty)),
+        value: folder.fold_expr(value),
+    }
+}
+
+pub fn fold_function<F: Fold + ?Sized>(folder: &mut F, function: FunctionDef) -> FunctionDef {
+    let FunctionDef { attrs, vis, name, params, ty, body, clauses, generator, span } = function;
+    FunctionDef {
+        span: folder.fold_span(span),
+        attrs: fold_all(attrs, |attr| folder.fold_attribute(attr)),
+        vis,
+        name: folder.fold_ident(name),
+        params: fold_all(params, |param| folder.fold_pattern(param)),
+        ty: ty.map(|ty| folder.fold_type(ty)),
+        body: body.map(|body| folder.fold_expr(body)),
+        clauses: fold_all(clauses, |clause| folder.fold_clause(clause)),
+        generator,
+    }
+}
+
+pub fn fold_clause<F: Fold + ?Sized>(folder: &mut F, clause: Clause) -> Clause {
+    Clause {
+        span: folder.fold_span(clause.span),
+        params: fold_all(clause.params, |param| folder.fold_pattern(param)),
+        body: folder.fold_expr(clause.body),
+    }
+}
+
+pub fn fold_mod<F: Fold + ?Sized>(folder: &mut F, module: ModDecl) -> ModDecl {
+    ModDecl {
+        span: folder.fold_span(module.span),
+        attrs: fold_all(module.attrs, |attr| folder.fold_attribute(attr)),
+        vis: module.vis,
+        name: folder.fold_ident(module.name),
+    }
+}
+
+pub fn fold_use<F: Fold + ?Sized>(folder: &mut F, use_: UseDecl) -> UseDecl {
+    UseDecl {
+        span: folder.fold_span(use_.span),
+        attrs: fold_all(use_.attrs, |attr| folder.fold_attribute(attr)),
+        vis: use_.vis,
+        tree: folder.fold_use_tree(use_.tree),
+    }
+}
+
+pub fn fold_use_tree<F: Fold + ?Sized>(folder: &mut F, tree: UseTree) -> UseTree {
+    let span = folder.fold_span(tree.span);
+    let path = fold_all(tree.path, |segment| folder.fold_ident(segment));
+    let kind = match tree.kind {
+        UseKind::Single { rename } => UseKind::Single { rename: rename.map(|rename| folder.fold_ident(rename)) },
+        UseKind::Glob => UseKind::Glob,
+        UseKind::Group(trees) => UseKind::Group(fold_all(trees, |tree| folder.fold_use_tree(tree))),
+    };
+    UseTree { path, kind, span }
+}
+
+pub fn fold_type_decl<F: Fold + ?Sized>(folder: &mut F, decl: TypeDecl) -> TypeDecl {
+    let TypeDecl { attrs, vis, name, params, where_clause, body, span } = decl;
+    TypeDecl {
+        span: folder.fold_span(span),
+        attrs: fold_all(attrs, |attr| folder.fold_attribute(attr)),
+        vis,
+        name: folder.fold_ident(name),
+        params: fold_all(params, |param| folder.fold_ident(param)),
+        where_clause: fold_all(where_clause, |predicate| folder.fold_predicate(predicate)),
+        body: match body {
+            TypeBody::Alias(ty) => TypeBody::Alias(folder.fold_type(ty)),
+            TypeBody::Variants(variants) => TypeBody::Variants(fold_all(variants, |variant| folder.fold_variant(variant))),
+            TypeBody::Record(fields) => TypeBody::Record(fold_all(fields, |field| folder.fold_record_field(field))),
+        },
+    }
+}
+
+pub fn fold_variant<F: Fold + ?Sized>(folder: &mut F, variant: Variant) -> Variant {
+    Variant {
+        span: folder.fold_span(variant.span),
+        name: folder.fold_ident(variant.name),
+        fields: fold_all(variant.fields, |field| folder.fold_type(field)),
+    }
+}
+
+pub fn fold_record_field<F: Fold + ?Sized>(folder: &mut F, field: RecordField) -> RecordField {
+    RecordField {
+        span: folder.fold_span(field.span),
+        vis: field.vis,
+        name: folder.fold_ident(field.name),
+        ty: folder.fold_type(field.ty),
+    }
+}
+
+pub fn fold_trait<F: Fold + ?Sized>(folder: &mut F, decl: TraitDecl) -> TraitDecl {
+    let TraitDecl { attrs, vis, name, params, where_clause, items, span } = decl;
+    TraitDecl {
+        span: folder.fold_span(span),
+        attrs: fold_all(attrs, |attr| folder.fold_attribute(attr)),
+        vis,
+        name: folder.fold_ident(name),
+        params: fold_all(params, |param| folder.fold_ident(param)),
+        where_clause: fold_all(where_clause, |predicate| folder.fold_predicate(predicate)),
+        items: fold_all(items, |item| folder.fold_stmt(item)),
+    }
+}
+
+pub fn fold_impl<F: Fold + ?Sized>(folder: &mut F, decl: ImplDecl) -> ImplDecl {
+    let ImplDecl { attrs, trait_ref, ty, where_clause, items, span } = decl;
+    ImplDecl {
+        span: folder.fold_span(span),
+        attrs: fold_all(attrs, |attr| folder.fold_attribute(attr)),
+        trait_ref: folder.fold_trait_ref(trait_ref),
+        ty: folder.fold_type(ty),
+        where_clause: fold_all(where_clause, |predicate| folder.fold_predicate(predicate)),
+        items: fold_all(items, |item| folder.fold_stmt(item)),
+    }
+}
+
+pub fn fold_signature<F: Fold + ?Sized>(folder: &mut F, signature: Signature) -> Signature {
+    let Signature { attrs, vis, name, params, ty, span } = signature;
+    Signature {
+        span: folder.fold_span(span),
+        attrs: fold_all(attrs, |attr| folder.fold_attribute(attr)),
+        vis,
+        name: folder.fold_ident(name),
+        params: fold_all(params, |param| folder.fold_pattern(param)),
+        ty: folder.fold_type(ty),
+    }
+}
+
+pub fn fold_predicate<F: Fold + ?Sized>(folder: &mut F, predicate: Predicate) -> Predicate {
+    Predicate {
+        span: folder.fold_span(predicate.span),
+        ty: folder.fold_type(predicate.ty),
+        bounds: fold_all(predicate.bounds, |bound| folder.fold_trait_ref(bound)),
+    }
+}
+
+pub fn fold_trait_ref<F: Fold + ?Sized>(folder: &mut F, trait_ref: TraitRef) -> TraitRef {
+    TraitRef {
+        span: folder.fold_span(trait_ref.span),
+        name: folder.fold_ident(trait_ref.name),
+        args: fold_all(trait_ref.args, |arg| folder.fold_type(arg)),
+    }
+}
+
+pub fn fold_attribute<F: Fold + ?Sized>(folder: &mut F, attr: Attribute) -> Attribute {
+    Attribute {
+        span: folder.fold_span(attr.span),
+        name: folder.fold_ident(attr.name),
+        args: fold_all(attr.args, |arg| folder.fold_expr(arg)),
+    }
+}
+
+pub fn fold_ident<F: Fold + ?Sized>(folder: &mut F, ident: Ident) -> Ident {
+    Ident { span: folder.fold_span(ident.span), name: ident.name }
+}
+
+pub fn fold_expr<F: Fold + ?Sized>(folder: &mut F, expr: Expr) -> Expr {
+    let span = folder.fold_span(expr.span);
+    let kind = match expr.kind {
+        ExprKind::Literal(literal) => ExprKind::Literal(folder.fold_literal(literal)),
+        ExprKind::Tuple(fields) => ExprKind::Tuple(fold_all(fields, |field| folder.fold_field(field))),
+        ExprKind::List(elements) => ExprKind::List(fold_all(elements, |element| folder.fold_expr(element))),
+        ExprKind::Apply { func, args } => ExprKind::Apply {
+            func: fold_box(func, |func| folder.fold_expr(func)),
+            args: fold_all(args, |arg| folder.fold_expr(arg)),
+        },
+        ExprKind::Method { receiver, method, args } => ExprKind::Method {
+            receiver: receiver.map(|receiver| fold_box(receiver, |receiver| folder.fold_expr(receiver))),
+            method: fold_box(method, |method| folder.fold_expr(method)),
+            args: fold_all(args, |arg| folder.fold_expr(arg)),
+        },
+        ExprKind::Unary { op, operand } => ExprKind::Unary {
+            op: folder.fold_ident(op),
+            operand: fold_box(operand, |operand| folder.fold_expr(operand)),
+        },
+        ExprKind::Binary { op, lhs, rhs } => {
+            let lhs = fold_box(lhs, |lhs| folder.fold_expr(lhs));
+            let op = folder.fold_ident(op);
+            ExprKind::Binary { op, lhs, rhs: fold_box(rhs, |rhs| folder.fold_expr(rhs)) }
+        }
+        ExprKind::Block(stmts) => ExprKind::Block(fold_all(stmts, |stmt| folder.fold_stmt(stmt))),
+        ExprKind::For { iter, pattern, body } => ExprKind::For {
+            iter: fold_box(iter, |iter| folder.fold_expr(iter)),
+            pattern: fold_box(pattern, |pattern| folder.fold_pattern(pattern)),
+            body: fold_box(body, |body| folder.fold_expr(body)),
+        },
+        ExprKind::While { cond, body, otherwise } => ExprKind::While {
+            cond: fold_box(cond, |cond| folder.fold_expr(cond)),
+            body: body.map(|body| fold_box(body, |body| folder.fold_expr(body))),
+            otherwise: otherwise.map(|otherwise| fold_box(otherwise, |otherwise| folder.fold_expr(otherwise))),
+        },
+        ExprKind::Cast { expr, ty } => ExprKind::Cast {
+            expr: fold_box(expr, |expr| folder.fold_expr(expr)),
+            ty: folder.fold_type(ty),
+        },
+        ExprKind::Match { scrutinee, arms } => ExprKind::Match {
+            scrutinee: fold_box(scrutinee, |scrutinee| folder.fold_expr(scrutinee)),
+            arms: fold_all(arms, |arm| folder.fold_arm(arm)),
+        },
+        ExprKind::Lambda { params, body, captures, generator } => ExprKind::Lambda {
+            params: fold_all(params, |param| folder.fold_pattern(param)),
+            body: fold_box(body, |body| folder.fold_expr(body)),
+            captures: fold_all(captures, |capture| folder.fold_ident(capture)),
+            generator,
+        },
+        ExprKind::Annotated { attrs, expr } => ExprKind::Annotated {
+            attrs: fold_all(attrs, |attr| folder.fold_attribute(attr)),
+            expr: fold_box(expr, |expr| folder.fold_expr(expr)),
+        },
+        ExprKind::Yield(value) => ExprKind::Yield(fold_box(value, |value| folder.fold_expr(value))),
+        ExprKind::Assign { place, op, value } => {
+            let place = fold_box(place, |place| folder.fold_expr(place));
+            let op = folder.fold_ident(op);
+            ExprKind::Assign { place, op, value: fold_box(value, |value| folder.fold_expr(value)) }
+        }
+        ExprKind::Range { start, end, inclusive } => ExprKind::Range {
+            start: start.map(|start| fold_box(start, |start| folder.fold_expr(start))),
+            end: end.map(|end| fold_box(end, |end| folder.fold_expr(end))),
+            inclusive,
+        },
+        kind @ (ExprKind::Name(_) | ExprKind::Argument(_) | ExprKind::Hole | ExprKind::Unit | ExprKind::Error
+        | ExprKind::Missing) => kind,
+    };
+    Expr { kind, span }
+}
+
+pub fn fold_field<F: Fold + ?Sized>(folder: &mut F, field: Field) -> Field {
+    Field {
+        name: field.name.map(|name| folder.fold_ident(name)),
+        value: folder.fold_expr(field.value),
+    }
+}
+
+pub fn fold_arm<F: Fold + ?Sized>(folder: &mut F, arm: Arm) -> Arm {
+    Arm {
+        span: folder.fold_span(arm.span),
+        pattern: folder.fold_pattern(arm.pattern),
+        guard: arm.guard.map(|guard| folder.fold_expr(guard)),
+        body: folder.fold_expr(arm.body),
+    }
+}
+
+pub fn fold_pattern<F: Fold + ?Sized>(folder: &mut F, pattern: Pattern) -> Pattern {
+    let span = folder.fold_span(pattern.span);
+    let kind = match pattern.kind {
+        PatternKind::Bind { name, pattern, guard } => PatternKind::Bind {
+            name: folder.fold_ident(name),
+            pattern: pattern.map(|pattern| fold_box(pattern, |pattern| folder.fold_pattern(pattern))),
+            guard: guard.map(|guard| fold_box(guard, |guard| folder.fold_expr(guard))),
+        },
+        PatternKind::Literal { value, negative } => PatternKind::Literal { value: folder.fold_literal(value), negative },
+        PatternKind::Constructor { name, args } => PatternKind::Constructor {
+            name: folder.fold_ident(name),
+            args: fold_all(args, |arg| folder.fold_pattern(arg)),
+        },
+        PatternKind::Tuple(elements) => PatternKind::Tuple(fold_all(elements, |element| folder.fold_pattern(element))),
+        PatternKind::List(elements) => PatternKind::List(fold_all(elements, |element| folder.fold_pattern(element))),
+        PatternKind::Optional { name, default } => PatternKind::Optional {
+            name: folder.fold_ident(name),
+            default: default.map(|default| fold_box(default, |default| folder.fold_expr(default))),
+        },
+        PatternKind::Range { start, end, inclusive } => PatternKind::Range {
+            start: start.map(|start| fold_box(start, |start| folder.fold_pattern(start))),
+            end: end.map(|end| fold_box(end, |end| folder.fold_pattern(end))),
+            inclusive,
+        },
+        kind @ (PatternKind::Wildcard | PatternKind::Unit | PatternKind::Error) => kind,
+    };
+    Pattern { kind, span }
+}
+
+pub fn fold_type<F: Fold + ?Sized>(folder: &mut F, ty: TypeExpr) -> TypeExpr {
+    let span = folder.fold_span(ty.span);
+    let kind = match ty.kind {
+        // `first .Name rest...`
+        TypeExprKind::Apply { name, args } => {
+            let mut args = args.into_iter();
+            let first = args.next().map(|first| folder.fold_type(first));
+            let name = folder.fold_ident(name);
+            let args = first.into_iter().chain(args.map(|arg| folder.fold_type(arg)).collect::<Vec<_>>()).collect();
+            TypeExprKind::Apply { name, args }
+        }
+        TypeExprKind::Tuple(fields) => TypeExprKind::Tuple(fold_all(fields, |field| folder.fold_type_field(field))),
+        TypeExprKind::Function { param, result } => TypeExprKind::Function {
+            param: fold_box(param, |param| folder.fold_type(param)),
+            result: fold_box(result, |result| folder.fold_type(result)),
+        },
+        TypeExprKind::Dyn(inner) => TypeExprKind::Dyn(fold_box(inner, |inner| folder.fold_type(inner))),
+        kind @ (TypeExprKind::Name(_) | TypeExprKind::Var(_) | TypeExprKind::Hole | TypeExprKind::Unit
+        | TypeExprKind::Error) => kind,
+    };
+    TypeExpr { kind, span }
+}
+
+pub fn fold_type_field<F: Fold + ?Sized>(folder: &mut F, field: TypeField) -> TypeField {
+    TypeField {
+        name: field.name.map(|name| folder.fold_ident(name)),
+        ty: folder.fold_type(field.ty),
+    }
+}
@@ -0,0 +1,391 @@
+use crate::parser::*;
+
+pub trait VisitMut {
+    fn visit_ast_mut(&mut self, ast: &mut Ast) {
+        walk_ast_mut(self, ast)
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt)
+    }
+
+    fn visit_let_mut(&mut self, let_: &mut Let) {
+        walk_let_mut(self, let_)
+    }
+
+    fn visit_function_mut(&mut self, function: &mut FunctionDef) {
+        walk_function_mut(self, function)
+    }
+
+    fn visit_clause_mut(&mut self, clause: &mut Clause) {
+        walk_clause_mut(self, clause)
+    }
+
+    fn visit_mod_mut(&mut self, module: &mut ModDecl) {
+        walk_mod_mut(self, module)
+    }
+
+    fn visit_use_mut(&mut self, use_: &mut UseDecl) {
+        walk_use_mut(self, use_)
+    }
+
+    fn visit_use_tree_mut(&mut self, tree: &mut UseTree) {
+        walk_use_tree_mut(self, tree)
+    }
+
+    fn visit_type_decl_mut(&mut self, decl: &mut TypeDecl) {
+        walk_type_decl_mut(self, decl)
+    }
+
+    fn visit_variant_mut(&mut self, variant: &mut Variant) {
+        walk_variant_mut(self, variant)
+    }
+
+    fn visit_record_field_mut(&mut self, field: &mut RecordField) {
+        walk_record_field_mut(self, field)
+    }
+
+    fn visit_trait_mut(&mut self, decl: &mut TraitDecl) {
+        walk_trait_mut(self, decl)
+    }
+
+    fn visit_impl_mut(&mut self, decl: &mut ImplDecl) {
+        walk_impl_mut(self, decl)
+    }
+
+    fn visit_signature_mut(&mut self, signature: &mut Signature) {
+        walk_signature_mut(self, signature)
+    }
+
+    fn visit_predicate_mut(&mut self, predicate: &mut Predicate) {
+        walk_predicate_mut(self, predicate)
+    }
+
+    fn visit_trait_ref_mut(&mut self, trait_ref: &mut TraitRef) {
+        walk_trait_ref_mut(self, trait_ref)
+    }
+
+    fn visit_attribute_mut(&mut self, attr: &mut Attribute) {
+        walk_attribute_mut(self, attr)
+    }
+
+    fn visit_ident_mut(&mut self, ident: &mut Ident) {
+        walk_ident_mut(self, ident)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr)
+    }
+
+    fn visit_field_mut(&mut self, field: &mut Field) {
+        walk_field_mut(self, field)
+    }
+
+    fn visit_arm_mut(&mut self, arm: &mut Arm) {
+        walk_arm_mut(self, arm)
+    }
+
+    fn visit_pattern_mut(&mut self, pattern: &mut Pattern) {
+        walk_pattern_mut(self, pattern)
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut TypeExpr) {
+        walk_type_mut(self, ty)
+    }
+
+    fn visit_type_field_mut(&mut self, field: &mut TypeField) {
+        walk_type_field_mut(self, field)
+    }
+
+    fn visit_literal_mut(&mut self, _literal: &mut Literal) {}
+
+    fn visit_span_mut(&mut self, _span: &mut Span) {}
+}
+
+pub fn walk_ast_mut<V: VisitMut + ?Sized>(visitor: &mut V, ast: &mut Ast) {
+    ast.stmts.iter_mut().for_each(|stmt| visitor.visit_stmt_mut(stmt));
+}
+
+pub fn walk_stmt_mut<V: VisitMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::Let(let_) => visitor.visit_let_mut(let_),
+        Stmt::Function(function) => visitor.visit_function_mut(function),
+        Stmt::Mod(module) => visitor.visit_mod_mut(module),
+        Stmt::Use(use_) => visitor.visit_use_mut(use_),
+        Stmt::Type(decl) => visitor.visit_type_decl_mut(decl),
+        Stmt::Trait(decl) => visitor.visit_trait_mut(decl),
+        Stmt::Impl(decl) => visitor.visit_impl_mut(decl),
+        Stmt::Signature(signature) => visitor.visit_signature_mut(signature),
+        Stmt::Expr(expr) => visitor.visit_expr_mut(expr),
+    }
+}
+
+pub fn walk_let_mut<V: VisitMut + ?Sized>(visitor: &mut V, let_: &mut Let) {
+    visitor.visit_span_mut(&mut let_.span);
+    let_.attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+    visitor.visit_ident_mut(&mut let_.name);
+    if let Some(ty) = &mut let_.ty {
+        visitor.visit_type_mut(ty);
+    }
+    visitor.visit_expr_mut(&mut let_.value);
+}
+
+pub fn walk_function_mut<V: VisitMut + ?Sized>(visitor: &mut V, function: &mut FunctionDef) {
+    visitor.visit_span_mut(&mut function.span);
+    function.attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+    visitor.visit_ident_mut(&mut function.name);
+    function.params.iter_mut().for_each(|param| visitor.visit_pattern_mut(param));
+    if let Some(ty) = &mut function.ty {
+        visitor.visit_type_mut(ty);
+    }
+    if let Some(body) = &mut function.body {
+        visitor.visit_expr_mut(body);
+    }
+    function.clauses.iter_mut().for_each(|clause| visitor.visit_clause_mut(clause));
+}
+
+pub fn walk_clause_mut<V: VisitMut + ?Sized>(visitor: &mut V, clause: &mut Clause) {
+    visitor.visit_span_mut(&mut clause.span);
+    clause.params.iter_mut().for_each(|param| visitor.visit_pattern_mut(param));
+    visitor.visit_expr_mut(&mut clause.body);
+}
+
+pub fn walk_mod_mut<V: VisitMut + ?Sized>(visitor: &mut V, module: &mut ModDecl) {
+    visitor.visit_span_mut(&mut module.span);
+    module.attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+    visitor.visit_ident_mut(&mut module.name);
+}
+
+pub fn walk_use_mut<V: VisitMut + ?Sized>(visitor: &mut V, use_: &mut UseDecl) {
+    visitor.visit_span_mut(&mut use_.span);
+    use_.attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+    visitor.visit_use_tree_mut(&mut use_.tree);
+}
+
+pub fn walk_use_tree_mut<V: VisitMut + ?Sized>(visitor: &mut V, tree: &mut UseTree) {
+    visitor.visit_span_mut(&mut tree.span);
+    tree.path.iter_mut().for_each(|segment| visitor.visit_ident_mut(segment));
+    match &mut tree.kind {
+        UseKind::Single { rename: Some(rename) } => visitor.visit_ident_mut(rename),
+        UseKind::Single { rename: None } | UseKind::Glob => {}
+        UseKind::Group(trees) => trees.iter_mut().for_each(|tree| visitor.visit_use_tree_mut(tree)),
+    }
+}
+
+pub fn walk_type_decl_mut<V: VisitMut + ?Sized>(visitor: &mut V, decl: &mut TypeDecl) {
+    visitor.visit_span_mut(&mut decl.span);
+    decl.attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+    visitor.visit_ident_mut(&mut decl.name);
+    decl.params.iter_mut().for_each(|param| visitor.visit_ident_mut(param));
+    decl.where_clause.iter_mut().for_each(|predicate| visitor.visit_predicate_mut(predicate));
+    match &mut decl.body {
+        TypeBody::Alias(ty) => visitor.visit_type_mut(ty),
+        TypeBody::Variants(variants) => variants.iter_mut().for_each(|variant| visitor.visit_variant_mut(variant)),
+        TypeBody::Record(fields) => fields.iter_mut().for_each(|field| visitor.visit_record_field_mut(field)),
+    }
+}
+
+pub fn walk_variant_mut<V: VisitMut + ?Sized>(visitor: &mut V, variant: &mut Variant) {
+    visitor.visit_span_mut(&mut variant.span);
+    visitor.visit_ident_mut(&mut variant.name);
+    variant.fields.iter_mut().for_each(|field| visitor.visit_type_mut(field));
+}
+
+pub fn walk_record_field_mut<V: VisitMut + ?Sized>(visitor: &mut V, field: &mut RecordField) {
+    visitor.visit_span_mut(&mut field.span);
+    visitor.visit_ident_mut(&mut field.name);
+    visitor.visit_type_mut(&mut field.ty);
+}
+
+pub fn walk_trait_mut<V: VisitMut + ?Sized>(visitor: &mut V, decl: &mut TraitDecl) {
+    visitor.visit_span_mut(&mut decl.span);
+    decl.attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+    visitor.visit_ident_mut(&mut decl.name);
+    decl.params.iter_mut().for_each(|param| visitor.visit_ident_mut(param));
+    decl.where_clause.iter_mut().for_each(|predicate| visitor.visit_predicate_mut(predicate));
+    decl.items.iter_mut().for_each(|item| visitor.visit_stmt_mut(item));
+}
+
+pub fn walk_impl_mut<V: VisitMut + ?Sized>(visitor: &mut V, decl: &mut ImplDecl) {
+    visitor.visit_span_mut(&mut decl.span);
+    decl.attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+    visitor.visit_trait_ref_mut(&mut decl.trait_ref);
+    visitor.visit_type_mut(&mut decl.ty);
+    decl.where_clause.iter_mut().for_each(|predicate| visitor.visit_predicate_mut(predicate));
+    decl.items.iter_mut().for_each(|item| visitor.visit_stmt_mut(item));
+}
+
+pub fn walk_signature_mut<V: VisitMut + ?Sized>(visitor: &mut V, signature: &mut Signature) {
+    visitor.visit_span_mut(&mut signature.span);
+    signature.attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+    visitor.visit_ident_mut(&mut signature.name);
+    signature.params.iter_mut().for_each(|param| visitor.visit_pattern_mut(param));
+    visitor.visit_type_mut(&mut signature.ty);
+}
+
+pub fn walk_predicate_mut<V: VisitMut + ?Sized>(visitor: &mut V, predicate: &mut Predicate) {
+    visitor.visit_span_mut(&mut predicate.span);
+    visitor.visit_type_mut(&mut predicate.ty);
+    predicate.bounds.iter_mut().for_each(|bound| visitor.visit_trait_ref_mut(bound));
+}
+
+pub fn walk_trait_ref_mut<V: VisitMut + ?Sized>(visitor: &mut V, trait_ref: &mut TraitRef) {
+    visitor.visit_span_mut(&mut trait_ref.span);
+    visitor.visit_ident_mut(&mut trait_ref.name);
+    trait_ref.args.iter_mut().for_each(|arg| visitor.visit_type_mut(arg));
+}
+
+pub fn walk_attribute_mut<V: VisitMut + ?Sized>(visitor: &mut V, attr: &mut Attribute) {
+    visitor.visit_span_mut(&mut attr.span);
+    visitor.visit_ident_mut(&mut attr.name);
+    attr.args.iter_mut().for_each(|arg| visitor.visit_expr_mut(arg));
+}
+
+pub fn walk_ident_mut<V: VisitMut + ?Sized>(visitor: &mut V, ident: &mut Ident) {
+    visitor.visit_span_mut(&mut ident.span);
+}
+
+pub fn walk_expr_mut<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    visitor.visit_span_mut(&mut expr.span);
+    match &mut expr.kind {
+        ExprKind::Literal(literal) => visitor.visit_literal_mut(literal),
+        ExprKind::Tuple(fields) => fields.iter_mut().for_each(|field| visitor.visit_field_mut(field)),
+        ExprKind::List(elements) => elements.iter_mut().for_each(|element| visitor.visit_expr_mut(element)),
+        ExprKind::Apply { func, args } => {
+            visitor.visit_expr_mut(func);
+            args.iter_mut().for_each(|arg| visitor.visit_expr_mut(arg));
+        }
+        ExprKind::Method { receiver, method, args } => {
+            if let Some(receiver) = receiver {
+                visitor.visit_expr_mut(receiver);
+            }
+            visitor.visit_expr_mut(method);
+            args.iter_mut().for_each(|arg| visitor.visit_expr_mut(arg));
+        }
+        ExprKind::Unary { op, operand } => {
+            visitor.visit_ident_mut(op);
+            visitor.visit_expr_mut(operand);
+        }
+        ExprKind::Binary { op, lhs, rhs } => {
+            visitor.visit_expr_mut(lhs);
+            visitor.visit_ident_mut(op);
+            visitor.visit_expr_mut(rhs);
+        }
+        ExprKind::Block(stmts) => stmts.iter_mut().for_each(|stmt| visitor.visit_stmt_mut(stmt)),
+        ExprKind::For { iter, pattern, body } => {
+            visitor.visit_expr_mut(iter);
+            visitor.visit_pattern_mut(pattern);
+            visitor.visit_expr_mut(body);
+        }
+        ExprKind::While { cond, body, otherwise } => {
+            visitor.visit_expr_mut(cond);
+            for expr in [body, otherwise].into_iter().flatten() {
+                visitor.visit_expr_mut(expr);
+            }
+        }
+        ExprKind::Cast { expr, ty } => {
+            visitor.visit_expr_mut(expr);
+            visitor.visit_type_mut(ty);
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr_mut(scrutinee);
+            arms.iter_mut().for_each(|arm| visitor.visit_arm_mut(arm));
+        }
+        ExprKind::Lambda { params, body, captures, .. } => {
+            params.iter_mut().for_each(|param| visitor.visit_pattern_mut(param));
+            visitor.visit_expr_mut(body);
+            captures.iter_mut().for_each(|capture| visitor.visit_ident_mut(capture));
+        }
+        ExprKind::Annotated { attrs, expr } => {
+            attrs.iter_mut().for_each(|attr| visitor.visit_attribute_mut(attr));
+            visitor.visit_expr_mut(expr);
+        }
+        ExprKind::Yield(value) => visitor.visit_expr_mut(value),
+        ExprKind::Assign { place, op, value } => {
+            visitor.visit_expr_mut(place);
+            visitor.visit_ident_mut(op);
+            visitor.visit_expr_mut(value);
+        }
+        ExprKind::Range { start, end, .. } => {
+            [start, end].into_iter().flatten().for_each(|bound| visitor.visit_expr_mut(bound));
+        }
+        ExprKind::Name(_) | ExprKind::Argument(_) | ExprKind::Hole | ExprKind::Unit | ExprKind::Error
+        | ExprKind::Missing => {}
+    }
+}
+
+pub fn walk_field_mut<V: VisitMut + ?Sized>(visitor: &mut V, field: &mut Field) {
+    if let Some(name) = &mut field.name {
+        visitor.visit_ident_mut(name);
+    }
+    visitor.visit_expr_mut(&mut field.value);
+}
+
+pub fn walk_arm_mut<V: VisitMut + ?Sized>(visitor: &mut V, arm: &mut Arm) {
+    visitor.visit_span_mut(&mut arm.span);
+    visitor.visit_pattern_mut(&mut arm.pattern);
+    if let Some(guard) = &mut arm.guard {
+        visitor.visit_expr_mut(guard);
+    }
+    visitor.visit_expr_mut(&mut arm.body);
+}
+
+pub fn walk_pattern_mut<V: VisitMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    visitor.visit_span_mut(&mut pattern.span);
+    match &mut pattern.kind {
+        PatternKind::Bind { name, pattern, guard } => {
+            visitor.visit_ident_mut(name);
+            if let Some(pattern) = pattern {
+                visitor.visit_pattern_mut(pattern);
+            }
+            if let Some(guard) = guard {
+                visitor.visit_expr_mut(guard);
+            }
+        }
+        PatternKind::Literal { value, .. } => visitor.visit_literal_mut(value),
+        PatternKind::Constructor { name, args } => {
+            visitor.visit_ident_mut(name);
+            args.iter_mut().for_each(|arg| visitor.visit_pattern_mut(arg));
+        }
+        PatternKind::Tuple(elements) | PatternKind::List(elements) => {
+            elements.iter_mut().for_each(|element| visitor.visit_pattern_mut(element));
+        }
+        PatternKind::Optional { name, default } => {
+            visitor.visit_ident_mut(name);
+            if let Some(default) = default {
+                visitor.visit_expr_mut(default);
+            }
+        }
+        PatternKind::Range { start, end, .. } => {
+            [start, end].into_iter().flatten().for_each(|bound| visitor.visit_pattern_mut(bound));
+        }
+        PatternKind::Wildcard | PatternKind::Unit | PatternKind::Error => {}
+    }
+}
+
+pub fn walk_type_mut<V: VisitMut + ?Sized>(visitor: &mut V, ty: &mut TypeExpr) {
+    visitor.visit_span_mut(&mut ty.span);
+    match &mut ty.kind {
+        // `first .Name rest...`
+        TypeExprKind::Apply { name, args } => {
+            args.iter_mut().take(1).for_each(|arg| visitor.visit_type_mut(arg));
+            visitor.visit_ident_mut(name);
+            args.iter_mut().skip(1).for_each(|arg| visitor.visit_type_mut(arg));
+        }
+        TypeExprKind::Tuple(fields) => fields.iter_mut().for_each(|field| visitor.visit_type_field_mut(field)),
+        TypeExprKind::Function { param, result } => {
+            visitor.visit_type_mut(param);
+            visitor.visit_type_mut(result);
+        }
+        TypeExprKind::Dyn(inner) => visitor.visit_type_mut(inner),
+        TypeExprKind::Name(_) | TypeExprKind::Var(_) | TypeExprKind::Hole | TypeExprKind::Unit
+        | TypeExprKind::Error => {}
+    }
+}
+
+pub fn walk_type_field_mut<V: VisitMut + ?Sized>(visitor: &mut V, field: &mut TypeField) {
+    if let Some(name) = &mut field.name {
+        visitor.visit_ident_mut(name);
+    }
+    visitor.visit_type_mut(&mut field.ty);
+}
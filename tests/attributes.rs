@@ -0,0 +1,110 @@
+use rolang::attributes::*;
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn parse_all(src: &str) -> (Ast, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    dbg!(&ast, &diagnostics);
+    (ast, diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect())
+}
+
+fn check(src: &str) -> Vec<(Severity, ParseErrorKind)> {
+    let (ast, errors) = parse_all(src);
+    assert_eq!(errors, []);
+    let res: Vec<_> = AttributeRegistry::builtin().check(&ast).into_iter()
+        .map(|diagnostic| (diagnostic.severity, diagnostic.kind))
+        .collect();
+    dbg!(&res);
+    res
+}
+
+fn names(attrs: &[Attribute]) -> Vec<&str> {
+    attrs.iter().map(|attr| attr.name.name.as_str()).collect()
+}
+
+#[test]
+fn attached_to_statements() {
+    let (ast, errors) = parse_all("\
+#inline
+#deprecated \"use g\"
+f 'x := x
+#[test, extern \"C\"] pub g () := f 1
+#test
+#[cold]
+main () :=
+    #unchecked
+    x := 1 + 2
+    #[likely] x
+");
+    assert_eq!(errors, []);
+    let [f, g, main] = &ast.stmts[..] else { panic!() };
+    assert_eq!(names(f.attrs()), ["inline", "deprecated"]);
+    assert!(matches!(&f.attrs()[1].args[..], [Expr { kind: ExprKind::Literal(Literal::String(_)), .. }]));
+    assert_eq!(names(g.attrs()), ["test", "extern"]);
+    assert!(matches!(g, Stmt::Function(FunctionDef { vis: Visibility::Pub, .. })));
+    assert_eq!(names(main.attrs()), ["test", "cold"]);
+
+    let Stmt::Function(FunctionDef { body: Some(Expr { kind: ExprKind::Block(stmts), .. }), .. }) = main else { panic!() };
+    let [Stmt::Let(x), Stmt::Expr(Expr { kind: ExprKind::Annotated { attrs, expr }, .. })] = &stmts[..] else { panic!() };
+    assert_eq!(names(&x.attrs), ["unchecked"]);
+    assert_eq!(names(attrs), ["likely"]);
+    assert!(matches!(expr.kind, ExprKind::Name(_)));
+}
+
+#[test]
+fn annotated_expressions() {
+    let (ast, errors) = parse_all("x := f #[unroll \"4\"] y + 1\n");
+    assert_eq!(errors, []);
+    let [Stmt::Let(Let { value: Expr { kind: ExprKind::Apply { args, .. }, .. }, .. })] = &ast.stmts[..] else { panic!() };
+    // attributes in argument position take the rest of the expression like `fn`
+    let [Expr { kind: ExprKind::Annotated { attrs, expr }, .. }] = &args[..] else { panic!() };
+    assert_eq!(names(attrs), ["unroll"]);
+    assert!(matches!(expr.kind, ExprKind::Binary { .. }));
+
+    let (ast, errors) = parse_all("f := 1\n#inline\n");
+    assert_eq!(errors, [ParseErrorKind::DanglingAttribute]);
+    assert!(matches!(&ast.stmts[1], Stmt::Expr(Expr { kind: ExprKind::Annotated { .. }, .. })));
+}
+
+#[test]
+fn declarations_and_items() {
+    let (ast, errors) = parse_all("\
+#deprecated
+type Old := New
+trait Show 't:
+    #deprecated \"use fmt\"
+    show 't : str
+#inline
+f _ :=
+#inline
+f 0 ::= 1
+");
+    assert_eq!(errors, [ParseErrorKind::MisplacedAttribute]);
+    let [Stmt::Type(old), Stmt::Trait(show), Stmt::Function(f)] = &ast.stmts[..] else { panic!() };
+    assert_eq!(names(&old.attrs), ["deprecated"]);
+    assert!(matches!(&show.items[..], [Stmt::Signature(Signature { attrs, .. })] if attrs.len() == 1));
+    assert_eq!((names(&f.attrs), f.clauses.len()), (vec!["inline"], 1));
+}
+
+#[test]
+fn registry() {
+    assert_eq!(check("#inline\nf 'x := x\n#test\nt := 1\n#deprecated\nmod util\nx := #[unknown] 1"), [
+        (Severity::Warning, ParseErrorKind::UnknownAttribute { name: "unknown".into() }),
+    ]);
+    assert_eq!(check("#inline \"always\"\nf 'x := x\n#extern\ng 'x := x\n#extern 1\nh 'x := x"), [
+        (Severity::Error, ParseErrorKind::AttributeArgs { min: 0, max: 0 }),
+        (Severity::Error, ParseErrorKind::AttributeArgs { min: 1, max: 1 }),
+        (Severity::Error, ParseErrorKind::AttributeArgs { min: 1, max: 1 }),
+    ]);
+    assert_eq!(check("#inline\nx := 1\ny := #[test] 2\nf 'x :=\n    #[wat] z := x"), [
+        (Severity::Error, ParseErrorKind::MisplacedAttribute),
+        (Severity::Error, ParseErrorKind::MisplacedAttribute),
+        (Severity::Warning, ParseErrorKind::UnknownAttribute { name: "wat".into() }),
+    ]);
+
+    let mut registry = AttributeRegistry::builtin();
+    registry.register("wat", 0..=2, AttributeTargets::VALUE);
+    let (ast, _) = parse_all("f 'x :=\n    #[wat \"a\" \"b\"] z := x");
+    assert_eq!(registry.check(&ast), []);
+}
@@ -0,0 +1,147 @@
+use rolang::coverage::check;
+use rolang::loader::parse_source;
+use rolang::parser::*;
+use rolang::prelude;
+use rolang::resolve::resolve;
+
+fn check_all(src: &str) -> Vec<Diagnostic> {
+    let (ast, diagnostics) = parse_source(src);
+    assert_eq!(diagnostics, []);
+    let prelude = prelude::module().ast;
+    let resolution = resolve(&ast, &prelude);
+    let diagnostics = check(&ast, &prelude, &resolution);
+    dbg!(&diagnostics);
+    diagnostics
+}
+
+fn messages(src: &str) -> Vec<String> {
+    check_all(src).iter().map(|diagnostic| diagnostic.to_string()).collect()
+}
+
+#[test]
+fn example() {
+    let diagnostics = check_all(include_str!("../examples/factorial/.rolang"));
+    let messages: Vec<_> = diagnostics.iter().map(|diagnostic| diagnostic.to_string()).collect();
+    assert_eq!(messages, [
+        "3:1: warning: clauses of `fact_impl` don't cover `_ ..=-1`",
+        "14:5: error: no clause of `fact_impl` matches the arguments `1 (-1)`",
+    ]);
+    assert_eq!(diagnostics[1].span, Span { row: 13, num: 5, end_row: 13, end_num: 14 });
+}
+
+#[test]
+fn guards() {
+    assert_eq!(messages("\
+sign _ :=
+sign ('n < 0) ::= -1
+sign 0 ::= 0
+sign ('n >= 1) ::= 1
+digit _ :=
+digit ('c >= '0' and 'c <= '9') ::= true
+digit ('c < '0' or 'c > '9') ::= false
+small _ :=
+small ('n > 0 and 10 > 'n and 'n != 5) ::= true
+small ('n >= 10 or not 'n > 0) ::= false
+limit := 100
+below _ :=
+below ('n < limit) ::= true
+below ('n >= limit) ::= false
+unknown _ :=
+unknown ('n .is_even) ::= true
+unknown ('n > 0) ::= false
+"), [
+        "8:1: warning: clauses of `small` don't cover `5`",
+        "15:1: warning: clauses of `unknown` don't cover `..=0`",
+    ]);
+}
+
+#[test]
+fn constructors_and_tuples() {
+    assert_eq!(messages("\
+type Color:
+    Red
+    Green
+    Blue
+name _ :=
+name Red ::= \"red\"
+name Green ::= \"green\"
+both _ _ :=
+both (Some 'a) (Some 'b) ::= a + b
+both None _ ::= 0
+first _ :=
+first (0, _) ::= 0
+first (_, 'b) ::= b
+first ('a, 'b) ::= a
+nested _ :=
+nested (Some (Some 0)) ::= 0
+nested None ::= 1
+"), [
+        "5:1: warning: clauses of `name` don't cover `Blue`",
+        "8:1: warning: clauses of `both` don't cover `(Some _) None`",
+        "14:1: warning: clause is never reached, the ones before match its arguments",
+        "15:1: warning: clauses of `nested` don't cover `(Some None)`",
+    ]);
+}
+
+#[test]
+fn literals_and_ranges() {
+    assert_eq!(messages("\
+grade _ :=
+grade 0..50 ::= 'F'
+grade 50..=100 ::= 'P'
+grade 100 ::= 'A'
+grade 101.. ::= '?'
+kind _ :=
+kind 'a'..='z' ::= 1
+kind 'A'..='Z' ::= 2
+kind '_' ::= 3
+word _ :=
+word \"yes\" ::= true
+word \"no\" ::= false
+word _ ::= false
+word \"maybe\" ::= false
+list _ :=
+list [] ::= 0
+list [_] ::= 1
+list _ ::= 2
+"), [
+        "1:1: warning: clauses of `grade` don't cover `..=-1`",
+        "4:1: warning: clause is never reached, the ones before match its arguments",
+        "6:1: warning: clauses of `kind` don't cover `..='@'`",
+        "14:1: warning: clause is never reached, the ones before match its arguments",
+    ]);
+}
+
+#[test]
+fn calls() {
+    assert_eq!(messages("\
+pred _ :=
+pred ('n > 0) ::= n - 1
+zero := 0
+start := pred
+main () :=
+    pred 1
+    pred 0
+    pred zero
+    start (-3)
+    5 .pred
+    (-5).pred
+    x := 0
+    pred x
+    mut y := 0
+    pred y
+    pred (1 - 2)
+is_some _ :=
+is_some (Some _) ::= true
+check := is_some None
+"), [
+        "1:1: warning: clauses of `pred` don't cover `..=0`",
+        "7:5: error: no clause of `pred` matches the arguments `0`",
+        "8:5: error: no clause of `pred` matches the arguments `0`",
+        "9:5: error: no clause of `pred` matches the arguments `(-3)`",
+        "11:5: error: no clause of `pred` matches the arguments `(-5)`",
+        "13:5: error: no clause of `pred` matches the arguments `0`",
+        "17:1: warning: clauses of `is_some` don't cover `None`",
+        "19:10: error: no clause of `is_some` matches the arguments `None`",
+    ]);
+}
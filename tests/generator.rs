@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use rolang::generator::*;
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn parse_all(src: &str) -> (Ast, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    dbg!(&ast, &diagnostics);
+    (ast, diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect())
+}
+
+fn parse_function(src: &str) -> FunctionDef {
+    let (ast, errors) = parse_all(src);
+    assert_eq!(errors, []);
+    let Some(Stmt::Function(function)) = ast.stmts.into_iter().next() else { panic!() };
+    function
+}
+
+/// Runs a machine over integers like a backend would, every yield is resumed with the value it yielded
+struct Run<'m> {
+    machine: &'m StateMachine,
+    state: Option<StateId>,
+    scopes: Vec<HashMap<String, i64>>,
+    iterators: Vec<std::vec::IntoIter<i64>>,
+}
+
+impl<'m> Run<'m> {
+    fn new(machine: &'m StateMachine, args: &[i64]) -> Self {
+        let mut run = Run { machine, state: Some(0), scopes: vec![HashMap::new()], iterators: vec![] };
+        for (param, &arg) in machine.params.iter().zip(args) {
+            assert!(run.bind(param, arg));
+        }
+        run.iterators.resize_with(machine.iterators, Default::default);
+        run
+    }
+
+    fn bind(&mut self, pattern: &Pattern, value: i64) -> bool {
+        match &pattern.kind {
+            PatternKind::Bind { name, pattern: None, guard: None } => {
+                self.scopes.last_mut().unwrap().insert(name.name.to_string(), value);
+                true
+            }
+            PatternKind::Literal { value: Literal::Number(number), negative: false } => number.slice.parse() == Ok(value),
+            PatternKind::Wildcard => true,
+            kind => panic!("{kind:?}"),
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> i64 {
+        match &expr.kind {
+            ExprKind::Literal(Literal::Number(number)) => number.slice.parse().unwrap(),
+            ExprKind::Name(name) => *self.scopes.iter().rev().find_map(|scope| scope.get(name.as_str())).unwrap(),
+            ExprKind::Unit => 0,
+            ExprKind::Binary { op, lhs, rhs } => {
+                let (lhs, rhs) = (self.eval(lhs), self.eval(rhs));
+                match op.name.as_str() {
+                    "+" => lhs + rhs,
+                    "*" => lhs * rhs,
+                    "%" => lhs % rhs,
+                    "<" => (lhs < rhs) as i64,
+                    op => panic!("{op}"),
+                }
+            }
+            kind => panic!("{kind:?}"),
+        }
+    }
+}
+
+impl Iterator for Run<'_> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        loop {
+            let machine = self.machine;
+            let state = &machine.states[self.state?];
+            for step in &state.steps {
+                match step {
+                    Step::Stmt(stmt) => match &**stmt {
+                        Stmt::Let(Let { name, value, .. }) => {
+                            let value = self.eval(value);
+                            self.scopes.last_mut().unwrap().insert(name.name.to_string(), value);
+                        }
+                        Stmt::Expr(expr) => _ = self.eval(expr),
+                        stmt => panic!("{stmt:?}"),
+                    },
+                    Step::Enter => self.scopes.push(HashMap::new()),
+                    Step::Leave => _ = self.scopes.pop(),
+                    Step::Iter { slot, iter } => {
+                        let ExprKind::List(elements) = &iter.kind else { panic!() };
+                        self.iterators[*slot] = elements.iter().map(|element| self.eval(element)).collect::<Vec<_>>().into_iter();
+                    }
+                }
+            }
+            self.state = match &state.exit {
+                Exit::Yield { value, bind, resume } => {
+                    let value = self.eval(value);
+                    self.state = Some(*resume);
+                    if let Some(bind) = bind {
+                        self.scopes.last_mut().unwrap().insert(bind.name.name.to_string(), value);
+                    }
+                    return Some(value);
+                }
+                Exit::Goto(state) => Some(*state),
+                Exit::Branch { cond, then, otherwise } => Some(if self.eval(cond) != 0 { *then } else { *otherwise }),
+                Exit::Next { slot, pattern, body, done } => match self.iterators[*slot].next() {
+                    Some(element) => {
+                        self.scopes.push(HashMap::new());
+                        assert!(self.bind(pattern, element));
+                        Some(*body)
+                    }
+                    None => Some(*done),
+                },
+                Exit::Match { scrutinee, arms } => {
+                    let value = self.eval(scrutinee);
+                    arms.iter().find_map(|arm| {
+                        self.scopes.push(HashMap::new());
+                        let matches = self.bind(&arm.pattern, value) && arm.guard.as_ref().is_none_or(|guard| self.eval(guard) != 0);
+                        if !matches {
+                            self.scopes.pop();
+                        }
+                        matches.then_some(arm.target)
+                    })
+                }
+                Exit::Done => None,
+            };
+        }
+    }
+}
+
+#[test]
+fn marked_generators() {
+    let (ast, errors) = parse_all("\
+count n :=
+    yield n
+    x := yield n + 1
+    f := fn: yield x
+    g () := yield 0
+fact _ :=
+fact 0 ::= 1
+fact 'n ::= n * fact (n - 1)
+digits _ :=
+digits 0 ::= ()
+digits 'n ::=
+    digits (n / 10) for d: yield d
+    yield n % 10
+");
+    assert_eq!(errors, []);
+    let generators: Vec<_> = ast.stmts.iter()
+        .map(|stmt| match stmt {
+            Stmt::Function(FunctionDef { generator, .. }) => *generator,
+            _ => panic!(),
+        })
+        .collect();
+    assert_eq!(generators, [true, false, true]);
+
+    let Stmt::Function(FunctionDef { body: Some(Expr { kind: ExprKind::Block(stmts), .. }), .. }) = &ast.stmts[0] else { panic!() };
+    let [_, Stmt::Let(x), Stmt::Let(f), Stmt::Function(g)] = &stmts[..] else { panic!() };
+    assert!(matches!(&x.value.kind, ExprKind::Yield(value) if matches!(value.kind, ExprKind::Binary { .. })));
+    assert!(matches!(f.value.kind, ExprKind::Lambda { generator: true, .. }));
+    assert!(g.generator);
+}
+
+#[test]
+fn misplaced_yields() {
+    let (_, errors) = parse_all("\
+f xs :=
+    print (yield 1)
+    xs for x: yield x
+    while: yield 2
+yield 3
+");
+    assert_eq!(errors, [ParseErrorKind::MisplacedYield, ParseErrorKind::MisplacedYield, ParseErrorKind::YieldOutsideFunction]);
+}
+
+#[test]
+fn state_machines() {
+    let function = parse_function("\
+gen () :=
+    [1, 2, 3, 4] for x:
+        match x % 2:
+            0 => yield x
+            _ => ()
+    r := yield 100
+    yield r + 1
+    while: r < 0
+    then: yield 0
+    else: yield 7
+");
+    let [machine] = &StateMachine::of_function(&function)[..] else { panic!() };
+    assert_eq!(machine.iterators, 1);
+    let run: Vec<_> = Run::new(machine, &[]).collect();
+    assert_eq!(run, [2, 4, 100, 101, 7]);
+
+    // the clause is picked before the generator starts, so each one gets a machine
+    let function = parse_function("\
+countdown _ :=
+countdown 0 ::= yield 0
+countdown 'n ::=
+    yield n
+    yield n + 100
+");
+    let machines = StateMachine::of_function(&function);
+    let states: Vec<_> = machines.iter().map(|machine| machine.states.len()).collect();
+    assert_eq!(states, [2, 3]);
+    let [zero, n] = &machines[..] else { panic!() };
+    assert!(matches!(&zero.states[0].exit, Exit::Yield { bind: None, resume: 1, .. }));
+    assert_eq!(Run::new(n, &[5]).collect::<Vec<_>>(), [5, 105]);
+
+    // the definition the resumed value is bound by keeps its mutability and type
+    let function = parse_function("\
+gen () :=
+    #inline
+    mut x : s32 := yield 1
+    x
+");
+    let [machine] = &StateMachine::of_function(&function)[..] else { panic!() };
+    let Exit::Yield { bind: Some(bind), .. } = &machine.states[0].exit else { panic!() };
+    assert_eq!((bind.name.name.as_str(), bind.mutable, bind.attrs.len()), ("x", true, 1));
+    assert!(matches!(&bind.ty, Some(TypeExpr { kind: TypeExprKind::Name(name), .. }) if name == "s32"));
+}
@@ -0,0 +1,234 @@
+use rolang::infer::*;
+use rolang::loader::parse_source;
+use rolang::parser::*;
+use rolang::prelude;
+use rolang::resolve::{resolve, DefScope};
+
+fn infer_all(src: &str) -> Vec<(String, String)> {
+    let (typing, resolution) = infer_source(src);
+    let mut schemes: Vec<_> = resolution.defs.iter().enumerate()
+        .filter(|(_, def)| def.scope == DefScope::Module)
+        .filter_map(|(id, def)| Some((def.name.to_string(), typing.schemes.get(&id)?.to_string())))
+        .collect();
+    schemes.sort();
+    dbg!(&schemes);
+    assert_eq!(typing.diagnostics, []);
+    schemes
+}
+
+fn infer_source(src: &str) -> (Typing, rolang::resolve::Resolution) {
+    let (ast, diagnostics) = parse_source(src);
+    assert_eq!(diagnostics, []);
+    let prelude = prelude::module().ast;
+    let resolution = resolve(&ast, &prelude);
+    assert_eq!(resolution.diagnostics, []);
+    let typing = infer(&ast, &prelude, &resolution);
+    dbg!(&typing.diagnostics);
+    (typing, resolution)
+}
+
+fn messages(src: &str) -> Vec<String> {
+    infer_source(src).0.diagnostics.iter().map(|diagnostic| diagnostic.to_string()).collect()
+}
+
+fn scheme<'s>(schemes: &'s [(String, String)], name: &str) -> &'s str {
+    &schemes.iter().find(|(other, _)| other == name).unwrap().1
+}
+
+#[test]
+fn example() {
+    let schemes = infer_all(include_str!("../examples/factorial/.rolang"));
+    assert_eq!(scheme(&schemes, "fact_impl"), "'a -> 'a -> 'a where 'a is {integer}");
+}
+
+#[test]
+fn prelude() {
+    let prelude = prelude::module().ast;
+    let empty = Ast::default();
+    let resolution = resolve(&prelude, &empty);
+    let typing = infer(&prelude, &empty, &resolution);
+    assert_eq!(typing.diagnostics, []);
+}
+
+#[test]
+fn generic_functions() {
+    let schemes = infer_all("\
+id 'x := x
+first 'x _ := x
+compose 'f 'g 'x := f (g x)
+apply 'f := f 1
+pair := (id 1u8, id \"a\", id)
+");
+    assert_eq!(scheme(&schemes, "id"), "'a -> 'a");
+    assert_eq!(scheme(&schemes, "first"), "'a -> 'b -> 'a");
+    assert_eq!(scheme(&schemes, "compose"), "('a -> 'b) -> ('c -> 'a) -> 'c -> 'b");
+    assert_eq!(scheme(&schemes, "apply"), "('a -> 'b) -> 'b where 'a is {integer}");
+    assert_eq!(scheme(&schemes, "pair"), "(u8, String, 'a -> 'a)");
+}
+
+#[test]
+fn let_polymorphism() {
+    let schemes = infer_all("\
+main () :=
+    twice := fn 'f 'x: f (f x)
+    a := twice (fn 'n: n + 1u16) 0
+    b := twice (fn 's: s) 'c'
+    (a, b)
+is_even 'n := n == 0 or is_odd (n - 1)
+is_odd 'n := n != 0 and is_even (n - 1)
+");
+    assert_eq!(scheme(&schemes, "main"), "() -> (u16, char)");
+    assert_eq!(scheme(&schemes, "is_even"), "'a -> bool where 'a is {integer}");
+}
+
+#[test]
+fn data_types() {
+    let schemes = infer_all("\
+type Tree 't:
+    Leaf
+    Node ('t .Tree) 't ('t .Tree)
+size _ :=
+size Leaf ::= 0
+size (Node 'l _ 'r) ::= size l + 1 + size r
+values := [Some 1.5, None]
+first _ :=
+first [] ::= None
+first ['x, _] ::= Some x
+");
+    assert_eq!(scheme(&schemes, "size"), "'a .Tree -> 'b where 'b is {integer}");
+    assert_eq!(scheme(&schemes, "values"), "'a .Option .Array where 'a is {float}");
+    assert_eq!(scheme(&schemes, "first"), "'a .Array -> 'a .Option");
+}
+
+#[test]
+fn mismatches() {
+    assert_eq!(messages("\
+check := while: 1
+main () :=
+    x := 1u8 + 2u16
+    [1, 'a']
+    fn 'f: f f
+bad := 1 + \"one\"
+f 'x := x x
+type A := B
+type B := A
+type C := C .Option
+y : A := 1
+"), [
+        "1:17: error: expected `bool`, found `{integer}`",
+        "3:16: error: expected `u8`, found `u16`, expected because of 3:10",
+        "4:9: error: expected `{integer}`, found `char`, expected because of 4:6",
+        "5:12: error: infinite type: `_` would have to be `_ -> _`, which holds it",
+        "6:12: error: expected `{integer}`, found `String`, expected because of 6:8",
+        "7:9: error: infinite type: `_` would have to be `_ -> _`, which holds it",
+        "8:6: error: alias `A` expands to itself",
+        "9:6: error: alias `B` expands to itself",
+        "10:6: error: alias `C` expands to itself",
+    ]);
+}
+
+#[test]
+fn number_literals() {
+    let src = "\
+main () :=
+    a := 1
+    b := 2.5
+    c := 3u8 + 4
+    d := 5 +% 6s16
+    (a, b, c, d)
+";
+    let (typing, _) = infer_source(src);
+    assert_eq!(typing.diagnostics, []);
+    let literal = |row, num, end_num| typing.types[&Span { row, num, end_row: row, end_num }].to_string();
+    assert_eq!(literal(1, 10, 11), "int");
+    assert_eq!(literal(2, 10, 13), "f64");
+    assert_eq!(literal(3, 16, 17), "u8");
+    assert_eq!(literal(4, 10, 11), "s16");
+    assert_eq!(literal(5, 5, 17), "(int, f64, u8, s16)");
+}
+
+#[test]
+fn number_errors() {
+    assert_eq!(messages("\
+a := 256u8
+b := -128s8
+c := -129s8
+d := 1.5u8
+e := 1u7
+f := 0x1'0000 + 1u16
+g := 1e39f32
+h := 'c' +| 'd'
+main () :=
+    x : u8 := 300
+    match x:
+        1000 => 1
+        _ => 0
+    n := 500
+    w : u8 := n
+big := 300
+y : u8 := big
+scale 'x := x * 1000
+z := scale 2u8
+"), [
+        "1:6: error: literal `256` doesn't fit in `u8`",
+        "3:6: error: literal `-129` doesn't fit in `s8`",
+        "4:6: error: `u8` isn't a type this number can have",
+        "5:6: error: `u7` isn't a type this number can have",
+        "6:6: error: literal `0x1'0000` doesn't fit in `u16`",
+        "7:6: error: literal `1e39` doesn't fit in `f32`",
+        "8:6: error: expected `{integer}`, found `char`, expected because of 8:10",
+        "10:15: error: literal `300` doesn't fit in `u8`",
+        "12:9: error: literal `1000` doesn't fit in `u8`",
+        "14:10: error: literal `500` doesn't fit in `u8`",
+        "16:8: error: literal `300` doesn't fit in `u8`",
+        "18:17: error: literal `1000` doesn't fit in `u8`",
+    ]);
+}
+
+#[test]
+fn tuple_map() {
+    let schemes = infer_all("\
+fact := fact_impl 1
+fact_impl _ _ :=
+fact_impl 'prod 0 ::= prod
+fact_impl 'prod ('n > 0) ::= fact_impl (n * prod) (n - 1)
+pair := (1u32, 2u64) |> .map fact
+options := (1u8, \"a\", 'c') .map Some
+nested := (1.5f32, (2s8, 3u16)) .map (fn 'x: (x, x))
+");
+    assert_eq!(scheme(&schemes, "pair"), "(u32, u64)");
+    assert_eq!(scheme(&schemes, "options"), "(u8 .Option, String .Option, char .Option)");
+    assert_eq!(scheme(&schemes, "nested"), "((f32, f32), ((s8, u16), (s8, u16)))");
+}
+
+#[test]
+fn tuple_map_errors() {
+    assert_eq!(messages("\
+half 'x := x / 2
+halves := (1, \"a\") |> .map half
+both 'f := (1, 'c') |> .map f
+"), [
+        "2:28: error: element 1 of the tuple is a `String`, which a `{integer} -> {integer}` can't take",
+        "3:29: error: element 1 of the tuple is a `char`, which a `{integer} -> _` can't take",
+    ]);
+}
+
+#[test]
+fn constant_arithmetic() {
+    assert_eq!(messages("\
+a := 200u8 + 56u8
+b := 200u8 +% 56u8
+c := 100s8 * 2
+d := 7 / 0
+e := (250u8 + 5) + 1
+f := 1.5 / 0.0
+g := 255u8 +| 1
+h := 127s8 + 1 - 1
+"), [
+        "1:6: error: this gives `256`, which overflows `u8`",
+        "3:6: error: this gives `200`, which overflows `s8`",
+        "4:6: error: division by zero",
+        "5:6: error: this gives `256`, which overflows `u8`",
+        "8:6: error: this gives `128`, which overflows `s8`",
+    ]);
+}
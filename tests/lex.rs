@@ -1,3 +1,5 @@
+#![allow(clippy::unneeded_struct_pattern)]
+
 use rolang::lexer::*;
 
 fn lex(src: &str) -> Vec<TokenType> {
@@ -94,6 +96,16 @@ fn test_numbers_2() {
     assert!(matches!(t, TokenType::Number{ errors, .. } if errors.contains(NumberErrorFlags::BAD_SUFFIX)))
 }
 
+#[test]
+fn test_ranges() {
+    let tokens = lex("0..2 1..=3 1.5..2. ..=-1");
+    let numbers = |tokens: &[TokenType]| tokens.iter().map(|token| matches!(token, TokenType::Number { .. })).collect::<Vec<_>>();
+    assert_eq!(numbers(&tokens), [true, false, true, true, false, true, true, false, true, false, false, true]);
+    assert!(matches!(lex_one("..-"), TokenType::Operator));
+    assert!(matches!(tokens[0], TokenType::Number { dot_pos: None, errors, .. } if errors.is_empty()));
+    assert!(matches!(tokens[6], TokenType::Number { dot_pos: Some(_), .. }));
+}
+
 #[test]
 fn test_operators() {
     assert!(matches!(lex_one("+"), TokenType::Operator));
@@ -111,6 +123,21 @@ fn test_characters() {
 #[test]
 fn test_strings_2() {
     assert!(matches!(lex_one("\"hello\""), TokenType::String{ value: StringValue::SingleLine(s), .. } if s.as_str()=="hello"));
+    assert!(matches!(lex_one("_\"hello\"s"), TokenType::String{ value: StringValue::SingleLine(s), prefix_len: 1, suffix_len: 1, .. } if s.as_str()=="hello"));
     assert!(matches!(lex_one("\"\"\"bad multi\nline\"\"\""), TokenType::String{ value: StringValue::SingleLine{..}, errors, .. } if errors.contains(StringErrorFlags::UNCLOSED)));
     assert!(matches!(lex_one("\"\"\"\nmulti\nline\"\"\""), TokenType::String{ value: StringValue::MultiLine{..}, .. }));
-}
\ No newline at end of file
+}
+#[test]
+fn test_comments() {
+    let tokens = lex("x ## comment \"\"\"\n# other\n#attr .(y)");
+    assert!(matches!(tokens[..], [
+        TokenType::Symbol(_), TokenType::Comment, TokenType::Indent, TokenType::Comment,
+        TokenType::Indent, TokenType::Sharp, TokenType::Symbol(_), TokenType::Operator, TokenType::Open, TokenType::Symbol(_), TokenType::Close,
+    ]));
+}
+
+#[test]
+fn test_zero() {
+    assert!(matches!(lex_one("0"), TokenType::Number{ errors, .. } if errors.is_empty()));
+    assert!(matches!(lex_one("0x"), TokenType::Number{ errors, .. } if errors.contains(NumberErrorFlags::NO_START_DIGITS)));
+}
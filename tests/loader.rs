@@ -0,0 +1,106 @@
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use rolang::loader::*;
+
+/// Temporary directory, removed when dropped
+struct Package(PathBuf);
+
+impl Deref for Package {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Package {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Fresh directory holding `files` given as relative path and contents
+fn package(name: &str, files: &[(&str, &str)]) -> Package {
+    let dir = std::env::temp_dir().join(format!("rolang-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for (path, source) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+    Package(dir)
+}
+
+fn load(dir: &Path) -> (Option<Module>, Vec<LoadError>) {
+    let res = load_package(dir);
+    for error in &res.1 {
+        println!("{error}");
+    }
+    dbg!(&res.0.as_ref().map(|module| &module.children));
+    res
+}
+
+#[test]
+fn example_package() {
+    let (module, errors) = load(Path::new("examples/factorial"));
+    assert_eq!(errors, []);
+    let module = module.unwrap();
+    assert_eq!(module.name, "factorial");
+    assert_eq!(module.diagnostics, []);
+    assert!(module.children.is_empty());
+}
+
+#[test]
+fn directories_and_siblings() {
+    let dir = package("tree", &[
+        (".rolang", "mod math\nmod util\nmain () := math.fact 3"),
+        ("math/.rolang", "pub mod fact"),
+        ("math/fact.rolang", "mod helper\npub fact := 1"),
+        ("math/helper.rolang", "h := 2"),
+        ("util.rolang", "u := 3"),
+    ]);
+    let (module, errors) = load(&dir);
+    assert_eq!(errors, []);
+    let module = module.unwrap();
+    let names: Vec<_> = module.children.iter().map(|child| child.name.as_str()).collect();
+    assert_eq!(names, ["math", "util"]);
+    let fact = &module.children[0].children[0];
+    assert_eq!(fact.file, dir.join("math/fact.rolang"));
+    assert_eq!(fact.children[0].file, dir.join("math/helper.rolang"));
+}
+
+#[test]
+fn missing_and_ambiguous() {
+    let dir = package("missing", &[
+        (".rolang", "x := 1\nmod gone\nmod both"),
+        ("both.rolang", ""),
+        ("both/.rolang", ""),
+    ]);
+    let (module, errors) = load(&dir);
+    assert!(module.unwrap().children.is_empty());
+    let [missing, ambiguous] = &errors[..] else { panic!() };
+    assert!(matches!(&missing.kind, LoadErrorKind::MissingFile { candidates } if candidates[0] == dir.join("gone.rolang")));
+    assert_eq!((missing.span.row, missing.span.num, missing.span.end_num), (1, 1, 9));
+    assert_eq!(missing.file, dir.join(ENTRY_FILE));
+    assert!(matches!(&ambiguous.kind, LoadErrorKind::AmbiguousFile { candidates } if candidates.len() == 2));
+
+    let (module, errors) = load(&dir.join("nothing"));
+    assert!(module.is_none());
+    assert!(matches!(errors[..], [LoadError { kind: LoadErrorKind::MissingFile { .. }, .. }]));
+}
+
+#[test]
+fn cyclic_mods() {
+    let dir = package("cycle", &[
+        (".rolang", "mod a"),
+        ("a.rolang", "mod b"),
+        ("b.rolang", "y := 1\nmod a"),
+    ]);
+    let (module, errors) = load(&dir);
+    let [LoadError { kind: LoadErrorKind::CyclicMod { cycle }, file, span }] = &errors[..] else { panic!() };
+    assert_eq!(cycle, &[dir.join("a.rolang"), dir.join("b.rolang")]);
+    assert_eq!((file, span.row), (&dir.join("b.rolang"), 1));
+    // the module declaring the cycle is still loaded
+    assert_eq!(module.unwrap().children[0].children[0].name, "b");
+}
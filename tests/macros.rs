@@ -0,0 +1,225 @@
+use rolang::lexer::*;
+use rolang::macros::*;
+use rolang::parser::*;
+use rolang::printer::print;
+use rolang::loader::parse_source;
+use rolang::prelude;
+use rolang::resolve::{resolve, DefScope};
+
+fn expand_all(src: &str) -> (Expanded, Ast, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let expanded = expand(&tokens);
+    let (ast, diagnostics) = parse_with_diagnostics(&expanded.tokens);
+    dbg!(&expanded.expansions, &ast, &expanded.diagnostics, &diagnostics);
+    let errors = expanded.diagnostics.iter().chain(&diagnostics).map(|diagnostic| diagnostic.kind.clone()).collect();
+    (expanded, ast, errors)
+}
+
+/// Expands and parses `src`, giving the printed result
+fn expand_print(src: &str) -> String {
+    let (_, ast, errors) = expand_all(src);
+    assert_eq!(errors, []);
+    let printed = print(&ast, src);
+    dbg!(&printed);
+    printed
+}
+
+#[test]
+fn expression_macros() {
+    let src = "\
+macro square:
+    ($x) => ($x * $x)
+macro max:
+    ($a, $b) => (if_else ($a > $b) $a $b)
+    ($a, $b, $rest ...) => #max((#max($a, $b)), $rest ...)
+#inline
+y := #square((a + 1)) + #max(1, 2, 3)
+z := #inline (x)
+";
+    assert_eq!(expand_print(src), "\
+#inline
+y := (a + 1) * (a + 1) + if_else (if_else (1 > 2) 1 2 > 3) (if_else (1 > 2) 1 2) 3
+z := #[inline] x
+");
+}
+
+#[test]
+fn hygiene() {
+    let src = "\
+macro swap:
+    ($a, $b) => {t := $a; $a .<- $b; $b .<- t}
+macro apply:
+    ($f $arg) => ((fn 'x: $f x) $arg)
+main () :=
+    mut t := 1
+    mut u := 2
+    #swap(t, u)
+    #apply(g x)
+";
+    assert_eq!(expand_print(src), "\
+main () :=
+    mut t := 1
+    mut u := 2
+    t_0 := t
+    t .<- u
+    u .<- t_0
+    (fn 'x_1: g x_1) x
+");
+}
+
+#[test]
+fn hygiene_fresh_names() {
+    let src = "\
+macro swap:
+    ($a, $b) => {t := $a; $a .<- $b; $b .<- t}
+main () :=
+    mut t_0 := 1
+    mut u := 2
+    #swap(t_0, u)
+";
+    let printed = expand_print(src);
+    assert_eq!(printed, "\
+main () :=
+    mut t_0 := 1
+    mut u := 2
+    t_0_2 := t_0
+    t_0 .<- u
+    u .<- t_0_2
+");
+    // renamed names are plain names, the expansion parses again to the same tree
+    let (_, ast, _) = expand_all(src);
+    let (reparsed, errors) = parse_source(&printed);
+    assert_eq!(errors, []);
+    assert_eq!(reparsed.without_spans(), ast.without_spans());
+}
+
+#[test]
+fn hygiene_definition_site() {
+    let src = "\
+helper 'x := x
+macro call:
+    ($e) => (helper $e)
+main () :=
+    helper := fn 'y: y + 1
+    #call(1)
+    helper 2
+";
+    let (ast, errors) = parse_source(src);
+    assert_eq!(errors, []);
+    let resolution = resolve(&ast, &prelude::module().ast);
+    assert_eq!(resolution.diagnostics, []);
+    let scope_at = |row, num| resolution.resolved.iter()
+        .find(|(span, _)| (span.row, span.num) == (row, num))
+        .map(|(_, &id)| resolution.defs[id].scope);
+    // the template's `helper` is the module one, the call site's its local
+    assert_eq!(scope_at(2, 14), Some(DefScope::Module));
+    assert_eq!(scope_at(6, 5), Some(DefScope::Local));
+}
+
+#[test]
+fn nested_calls() {
+    let src = "\
+macro double:
+    ($x) => ($x * 2)
+y := #double(#double(1))
+z := #double((#double(a) + 1))
+";
+    let (expanded, ast, errors) = expand_all(src);
+    assert_eq!(errors, []);
+    assert_eq!(print(&ast, src), "\
+y := 1 * 2 * 2
+z := (a * 2 + 1) * 2
+");
+    let parents: Vec<_> = expanded.expansions.iter().map(|expansion| expansion.parent).collect();
+    assert_eq!(parents, [None, Some(0), None, Some(2)]);
+}
+
+#[test]
+fn nested_and_multiline() {
+    let src = "\
+macro list:
+    () => []
+    ($x $rest ...) => (Cons $x #list($rest ...))
+macro twice:
+    ($body ...) => {
+
+        $body ...
+        $body ...
+    }
+f () :=
+    xs := #list(1 2 3)
+    #twice(print xs)
+";
+    let (expanded, ast, errors) = expand_all(src);
+    assert_eq!(errors, []);
+    assert_eq!(print(&ast, src), "\
+f () :=
+    xs := Cons 1 (Cons 2 (Cons 3 []))
+    print xs
+    print xs
+");
+    let parents: Vec<_> = expanded.expansions.iter().map(|expansion| expansion.parent).collect();
+    assert_eq!(parents, [None, Some(0), Some(1), Some(2), None]);
+    assert_eq!(expanded.backtrace(3).len(), 4);
+}
+
+#[test]
+fn spans() {
+    let src = "\
+macro square:
+    ($x) => ($x * $x)
+y := #square(a)
+";
+    let (expanded, ast, errors) = expand_all(src);
+    assert_eq!(errors, []);
+    let Stmt::Let(Let { value, .. }) = &ast.stmts[0] else { panic!() };
+    let ExprKind::Binary { op, lhs, .. } = &value.kind else { panic!() };
+    // the operator comes from the definition, the operand from the call
+    assert_eq!((op.span.row, op.span.num), (1, 17));
+    assert_eq!((lhs.span.row, lhs.span.num), (2, 14));
+    let call = Span { row: 2, num: 6, end_row: 2, end_num: 16 };
+    assert_eq!(expanded.expansions, [Expansion {
+        name: "square".into(),
+        call,
+        definition: Span { row: 0, num: 1, end_row: 0, end_num: 13 },
+        parent: None,
+    }]);
+    assert_eq!(expanded.backtrace_at(op.span), [call]);
+    assert_eq!(expanded.backtrace_at(Span { row: 2, num: 1, end_row: 2, end_num: 2 }), []);
+}
+
+#[test]
+fn errors() {
+    let src = "\
+macro outer:
+    ($x) => (#inner($x))
+macro inner:
+    (1) => 1
+macro forever:
+    ($x) => #forever($x)
+macro bad:
+    $x => $x
+    ($x) => $y
+a := #outer(2)
+b := #missing(1)
+c := #forever(1)
+d := #bad(1)
+";
+    let (expanded, _, errors) = expand_all(src);
+    let outer = Span { row: 9, num: 6, end_row: 9, end_num: 15 };
+    let forever = Span { row: 11, num: 6, end_row: 11, end_num: 17 };
+    // the innermost calls are in the definitions
+    let ParseErrorKind::MacroRecursion { backtrace, .. } = &errors[1] else { panic!() };
+    assert_eq!((backtrace.len(), backtrace.last()), (RECURSION_LIMIT, Some(&forever)));
+    assert_eq!(errors, [
+        ParseErrorKind::NoMacroRule { name: "inner".into(), backtrace: vec![outer] },
+        ParseErrorKind::MacroRecursion { name: "forever".into(), backtrace: backtrace.clone() },
+        ParseErrorKind::ExpectedMacroRule,
+        ParseErrorKind::UnknownMacroVar { name: "y".into() },
+        ParseErrorKind::UnknownMacro { name: "missing".into(), backtrace: vec![] },
+        // the rules with errors are left out
+        ParseErrorKind::NoMacroRule { name: "bad".into(), backtrace: vec![] },
+    ]);
+    assert_eq!(expanded.diagnostics[0].to_string(),
+        "2:14: error: no rule of macro `inner` matches the arguments\n  in expansion at 10:6");
+}
@@ -0,0 +1,71 @@
+use num_bigint::BigInt;
+use rolang::numeric::*;
+
+fn int(value: i128) -> Number {
+    Number::Int(BigInt::from(value))
+}
+
+fn apply(op: &str, ty: &str, a: i128, b: i128, debug: bool) -> Result<Number, ArithmeticError> {
+    let (op, overflow) = arithmetic_operator(op, debug).unwrap();
+    let result = arithmetic(op, overflow, NumberType::from_name(ty).unwrap(), &int(a), &int(b));
+    dbg!(&result);
+    result
+}
+
+#[test]
+fn types() {
+    assert_eq!(NumberType::from_name("u32"), Some(NumberType::Unsigned(32)));
+    assert_eq!(NumberType::from_name("s128"), Some(NumberType::Signed(128)));
+    assert_eq!(NumberType::from_name("int"), Some(NumberType::Int));
+    assert_eq!(NumberType::from_name("f64"), Some(NumberType::Float(64)));
+    for name in ["u7", "s08", "f16", "i32", "u", "uint"] {
+        assert_eq!(NumberType::from_name(name), None, "{name}");
+    }
+    assert_eq!(NumberType::Signed(8).bounds(), Some((BigInt::from(-128), BigInt::from(127))));
+    assert_eq!(NumberType::Unsigned(128).bounds().unwrap().1, BigInt::from(u128::MAX));
+    assert!(NumberType::Int.fits(&int(i128::MAX)));
+    assert!(!NumberType::Unsigned(8).fits(&int(-1)));
+    assert!(!NumberType::Signed(32).fits(&Number::Float(1.0)));
+    assert!(NumberType::Float(32).fits(&int(1 << 100)));
+    assert!(!NumberType::Float(32).fits(&Number::Float(1e39)));
+}
+
+#[test]
+fn operators() {
+    assert_eq!(arithmetic_operator("+", true), Some((ArithmeticOp::Add, Overflow::Checked)));
+    assert_eq!(arithmetic_operator("+", false), Some((ArithmeticOp::Add, Overflow::Wrapping)));
+    assert_eq!(arithmetic_operator("-%", true), Some((ArithmeticOp::Sub, Overflow::Wrapping)));
+    assert_eq!(arithmetic_operator("*|", false), Some((ArithmeticOp::Mul, Overflow::Saturating)));
+    assert_eq!(arithmetic_operator("%", true), Some((ArithmeticOp::Rem, Overflow::Checked)));
+    assert_eq!(arithmetic_operator("/%", true), None);
+    assert_eq!(arithmetic_operator("**", true), None);
+    assert!(is_bounded_operator("+|"));
+    assert!(!is_bounded_operator("+"));
+}
+
+#[test]
+fn overflow() {
+    assert_eq!(apply("+", "u8", 200, 55, true), Ok(int(255)));
+    assert_eq!(apply("+", "u8", 200, 56, true), Err(ArithmeticError::Overflow { ty: NumberType::Unsigned(8), result: BigInt::from(256) }));
+    assert_eq!(apply("+", "u8", 200, 56, false), Ok(int(0)));
+    assert_eq!(apply("+%", "s8", 127, 1, true), Ok(int(-128)));
+    assert_eq!(apply("-%", "u16", 0, 1, true), Ok(int(65535)));
+    assert_eq!(apply("*%", "s16", 300, 300, true), Ok(int(90000 - 65536)));
+    assert_eq!(apply("-|", "u8", 3, 5, true), Ok(int(0)));
+    assert_eq!(apply("*|", "s8", -100, 2, true), Ok(int(-128)));
+    assert_eq!(apply("+|", "s64", i64::MAX as i128, 1, true), Ok(int(i64::MAX as i128)));
+    assert_eq!(apply("*", "int", i128::MAX, 4, true), Ok(Number::Int(BigInt::from(i128::MAX) * 4)));
+    assert_eq!(apply("/", "s32", -7, 2, true), Ok(int(-3)));
+    assert_eq!(apply("%", "s32", -7, 2, true), Ok(int(-1)));
+    assert_eq!(apply("/", "u8", 1, 0, true), Err(ArithmeticError::DivisionByZero));
+    assert_eq!(apply("/", "s8", -128, -1, true).unwrap_err().to_string(), "`128` overflows `s8`");
+}
+
+#[test]
+fn floats() {
+    let (op, overflow) = arithmetic_operator("/", true).unwrap();
+    let third = arithmetic(op, overflow, NumberType::Float(32), &int(1), &Number::Float(3.0));
+    assert_eq!(third, Ok(Number::Float((1.0f32 / 3.0) as f64)));
+    let (op, overflow) = arithmetic_operator("*", true).unwrap();
+    assert_eq!(arithmetic(op, overflow, NumberType::Float(64), &Number::Float(1e308), &Number::Float(10.0)), Ok(Number::Float(f64::INFINITY)));
+}
@@ -0,0 +1,96 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn parse_src(src: &str) -> Result<Ast, Vec<ParseError>> {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let res = parse(&tokens);
+    dbg!(&res);
+    res
+}
+
+fn parse_errors(src: &str) -> Vec<ParseErrorKind> {
+    parse_src(src).unwrap_err().into_iter().map(|err| err.kind).collect()
+}
+
+#[test]
+fn clauses_grouped() {
+    let ast = parse_src("\
+fact := fact_impl 1
+
+fact_impl _ _ :=
+
+fact_impl 'prod 0 ::= prod
+fact_impl 'prod ('n > 0) ::= fact_impl (n * prod) (n - 1)
+").unwrap();
+
+    assert_eq!(ast.stmts.len(), 2);
+    assert!(matches!(&ast.stmts[0], Stmt::Let(Let { name, mutable: false, .. }) if name.name == "fact"));
+    let Stmt::Function(function) = &ast.stmts[1] else { panic!() };
+    assert_eq!(function.name.name, "fact_impl");
+    assert_eq!(function.params.len(), 2);
+    assert!(function.body.is_none());
+    assert_eq!(function.clauses.len(), 2);
+    assert!(matches!(function.clauses[0].body.kind, ExprKind::Name(ref name) if name == "prod"));
+    assert!(matches!(function.clauses[1].body.kind, ExprKind::Apply { ref args, .. } if args.len() == 2));
+    assert_eq!(function.clauses[1].span.row, 5);
+}
+
+#[test]
+fn arity_mismatch() {
+    let errors = parse_errors("\
+f _ _ :=
+f 0 ::= 1
+f 'a 'b ::= a
+");
+    assert_eq!(errors, [ParseErrorKind::ArityMismatch { expected: 2, found: 1 }]);
+}
+
+#[test]
+fn separated_clause() {
+    let errors = parse_errors("\
+f _ :=
+f 0 ::= 1
+x := 2
+f 'n ::= n
+");
+    assert!(matches!(errors[..], [ParseErrorKind::SeparatedClause { signature }] if signature.row == 0));
+}
+
+#[test]
+fn clause_without_signature() {
+    assert_eq!(parse_errors("g 0 ::= 1"), [ParseErrorKind::ClauseWithoutSignature]);
+    assert!(matches!(parse_errors("f _ := 0\nf 0 ::= 1")[..], [ParseErrorKind::ClauseAfterBody { .. }]));
+}
+
+#[test]
+fn function_block_body() {
+    let ast = parse_src("\
+main () :=
+    ## 6
+    fact 3
+      |> println \"{}\"
+
+    mut x := 3
+    fact (-1) .(println \"{}\")
+").unwrap();
+
+    let [Stmt::Function(main)] = &ast.stmts[..] else { panic!() };
+    assert!(matches!(main.params[..], [Pattern { kind: PatternKind::Unit, .. }]));
+    let Some(Expr { kind: ExprKind::Block(stmts), .. }) = &main.body else { panic!() };
+    assert_eq!(stmts.len(), 3);
+    assert!(matches!(&stmts[0], Stmt::Expr(Expr { kind: ExprKind::Binary { op, .. }, .. }) if op.name == "|>"));
+    assert!(matches!(&stmts[1], Stmt::Let(Let { mutable: true, .. })));
+    let Stmt::Expr(Expr { kind: ExprKind::Method { receiver: Some(receiver), args, .. }, .. }) = &stmts[2] else { panic!() };
+    assert!(matches!(receiver.kind, ExprKind::Apply { .. }));
+    assert!(args.is_empty());
+}
+
+#[test]
+fn precedence() {
+    let ast = parse_src("a + b * c |> f x.g").unwrap();
+    let [Stmt::Expr(Expr { kind: ExprKind::Binary { op, lhs, rhs }, .. })] = &ast.stmts[..] else { panic!() };
+    assert_eq!(op.name, "|>");
+    assert!(matches!(&lhs.kind, ExprKind::Binary { op, rhs, .. } if op.name == "+" && matches!(rhs.kind, ExprKind::Binary { .. })));
+    let ExprKind::Apply { args, .. } = &rhs.kind else { panic!() };
+    assert!(matches!(args[..], [Expr { kind: ExprKind::Method { receiver: Some(_), .. }, .. }]));
+}
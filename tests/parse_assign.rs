@@ -0,0 +1,95 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+use rolang::printer::print;
+
+fn parse_all(src: &str) -> (Ast, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    dbg!(&ast, &diagnostics);
+    (ast, diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect())
+}
+
+/// Value of the last statement of the body of `main`
+fn last_expr(src: &str) -> Expr {
+    let (ast, errors) = parse_all(src);
+    assert_eq!(errors, []);
+    let Some(Stmt::Function(FunctionDef { body: Some(Expr { kind: ExprKind::Block(stmts), .. }), .. })) = ast.stmts.last() else {
+        panic!()
+    };
+    let Some(Stmt::Expr(expr)) = stmts.last() else { panic!() };
+    expr.clone()
+}
+
+fn name(expr: &Expr) -> &str {
+    match &expr.kind {
+        ExprKind::Name(name) => name.as_str(),
+        _ => panic!("not a name: {expr:?}"),
+    }
+}
+
+#[test]
+fn update_operators() {
+    let expr = last_expr("main () :=\n    mut x := 3\n    x %<- 7 + 1");
+    let ExprKind::Assign { place, op, value } = expr.kind else { panic!() };
+    assert_eq!((name(&place), op.name.as_str()), ("x", "%<-"));
+    // `x % (7 + 1)`, the update takes the rest of the expression
+    let ExprKind::Binary { op, lhs, rhs } = value.kind else { panic!() };
+    assert_eq!((op.name.as_str(), name(&lhs)), ("%", "x"));
+    assert!(matches!(rhs.kind, ExprKind::Binary { .. }));
+
+    for op in ["+<-", "**<-", "|><-", "<-"] {
+        let expr = last_expr(&format!("main () :=\n    mut x := 3\n    x {op} y"));
+        assert!(matches!(&expr.kind, ExprKind::Assign { op: ident, .. } if ident.name == op));
+    }
+}
+
+#[test]
+fn method_update() {
+    let expr = last_expr("main () :=\n    mut x := 3\n    x .<-fact");
+    let ExprKind::Assign { place, op, value } = expr.kind else { panic!() };
+    assert_eq!((name(&place), op.name.as_str()), ("x", ".<-"));
+    let ExprKind::Method { receiver: Some(receiver), method, args } = value.kind else { panic!() };
+    assert_eq!((name(&receiver), name(&method), args.len()), ("x", "fact", 0));
+
+    let expr = last_expr("main () :=\n    mut p := 3\n    p.count .<- max 0 (f 1)");
+    let ExprKind::Assign { place, value, .. } = expr.kind else { panic!() };
+    assert!(matches!(place.kind, ExprKind::Method { .. }));
+    assert!(matches!(value.kind, ExprKind::Method { ref args, .. } if args.len() == 2));
+}
+
+#[test]
+fn in_expressions() {
+    let expr = last_expr("main () :=\n    mut x := 3\n    println \"{}\" x (x %<- 7) x");
+    let ExprKind::Apply { args, .. } = expr.kind else { panic!() };
+    assert!(matches!(args[2].kind, ExprKind::Assign { .. }));
+
+    // assignments are right associative
+    let expr = last_expr("main () :=\n    mut x := 3\n    mut y := 3\n    x <- y +<- 1");
+    let ExprKind::Assign { value, .. } = expr.kind else { panic!() };
+    assert!(matches!(value.kind, ExprKind::Assign { .. }));
+}
+
+#[test]
+fn errors() {
+    let (_, errors) = parse_all("main () :=\n    x := 3\n    x .<-fact");
+    assert_eq!(errors, [ParseErrorKind::ImmutableAssignment { binding: Span { row: 1, num: 5, end_row: 1, end_num: 6 } }]);
+
+    let (_, errors) = parse_all("f 'n :=\n    n +<- 1\n    p := (a := 1)\n    p.a <- 2");
+    assert!(matches!(errors[..], [ParseErrorKind::ImmutableAssignment { .. }, ParseErrorKind::ImmutableAssignment { .. }]));
+
+    let (_, errors) = parse_all("main () :=\n    mut x := 3\n    x + 1 <- 2\n    (f x) %<- 7\n    [1] for 'x: x <- 2");
+    assert_eq!(errors, [ParseErrorKind::NotAPlace, ParseErrorKind::NotAPlace, ParseErrorKind::ImmutableAssignment {
+        binding: Span { row: 4, num: 13, end_row: 4, end_num: 15 },
+    }]);
+
+    // shadowed by a `mut` binding, and names bound nowhere are left to name resolution
+    let (_, errors) = parse_all("main () :=\n    x := 3\n    f := fn:\n        mut x := 1\n        x <- 2\n    y <- 1");
+    assert_eq!(errors, []);
+}
+
+#[test]
+fn printing() {
+    let src = "main () :=\n    mut x := 3\n    x %<-7\n    x .<-fact\n    f (x <- 1) (x .<- g 2)\n";
+    let (ast, _) = parse_all(src);
+    assert_eq!(print(&ast, src), "main () :=\n    mut x := 3\n    x %<- 7\n    x .<- fact\n    f (x <- 1) (x .<- g 2)\n");
+}
@@ -0,0 +1,106 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn parse_stmts(src: &str) -> Vec<Stmt> {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let res = parse(&tokens).unwrap().stmts;
+    dbg!(&res);
+    res
+}
+
+fn parse_error(src: &str) -> ParseErrorKind {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let res = parse(&tokens).unwrap_err().remove(0).kind;
+    dbg!(&res);
+    res
+}
+
+fn block_len(expr: &Expr) -> usize {
+    match &expr.kind {
+        ExprKind::Block(stmts) => stmts.len(),
+        _ => 0,
+    }
+}
+
+#[test]
+fn for_block() {
+    let stmts = parse_stmts("\
+xs for n:
+    res := n.fact
+    print \"{}, \" res
+println \"\"
+");
+    assert_eq!(stmts.len(), 2);
+    let Stmt::Expr(Expr { kind: ExprKind::For { iter, pattern, body }, .. }) = &stmts[0] else { panic!() };
+    assert!(matches!(iter.kind, ExprKind::Name(_)));
+    assert!(matches!(&pattern.kind, PatternKind::Bind { name, .. } if name.name == "n"));
+    assert_eq!(block_len(body), 2);
+}
+
+#[test]
+fn for_inline() {
+    let stmts = parse_stmts("xs for n: print n");
+    let [Stmt::Expr(Expr { kind: ExprKind::For { body, .. }, .. })] = &stmts[..] else { panic!() };
+    assert!(matches!(body.kind, ExprKind::Apply { .. }));
+}
+
+#[test]
+fn while_then_else() {
+    let stmts = parse_stmts("\
+main () :=
+    println while: x < 720
+    then:
+        x .<-fact
+    else:
+        x
+    done
+");
+    let [Stmt::Function(FunctionDef { body: Some(body), .. })] = &stmts[..] else { panic!() };
+    let ExprKind::Block(stmts) = &body.kind else { panic!() };
+    assert_eq!(stmts.len(), 2);
+    let Stmt::Expr(Expr { kind: ExprKind::Apply { args, .. }, .. }) = &stmts[0] else { panic!() };
+    let ExprKind::While { cond, body: Some(body), otherwise: Some(otherwise) } = &args[0].kind else { panic!() };
+    assert!(matches!(cond.kind, ExprKind::Binary { .. }));
+    assert_eq!(block_len(body), 1);
+    assert_eq!(block_len(otherwise), 1);
+}
+
+#[test]
+fn while_inline() {
+    let stmts = parse_stmts("while: a then: b else: c");
+    let [Stmt::Expr(Expr { kind: ExprKind::While { body: Some(_), otherwise: Some(_), .. }, .. })] = &stmts[..] else { panic!() };
+}
+
+#[test]
+fn nested_blocks() {
+    let stmts = parse_stmts("\
+xs for x:
+    ys for y:
+        while:
+            x < y
+        then:
+            f x
+              y
+    g x
+h:
+    1
+");
+    assert_eq!(stmts.len(), 2);
+    let Stmt::Expr(Expr { kind: ExprKind::For { body, .. }, .. }) = &stmts[0] else { panic!() };
+    let ExprKind::Block(inner) = &body.kind else { panic!() };
+    assert_eq!(inner.len(), 2);
+    let Stmt::Expr(Expr { kind: ExprKind::For { body, .. }, .. }) = &inner[0] else { panic!() };
+    let ExprKind::Block(inner) = &body.kind else { panic!() };
+    let [Stmt::Expr(Expr { kind: ExprKind::While { cond, body: Some(body), otherwise: None }, .. })] = &inner[..] else { panic!() };
+    assert_eq!(block_len(cond), 1);
+    let ExprKind::Block(then) = &body.kind else { panic!() };
+    assert!(matches!(&then[..], [Stmt::Expr(Expr { kind: ExprKind::Apply { args, .. }, .. })] if args.len() == 2));
+    assert!(matches!(&stmts[1], Stmt::Expr(Expr { kind: ExprKind::Apply { args, .. }, .. }) if block_len(&args[0]) == 1));
+}
+
+#[test]
+fn block_errors() {
+    assert_eq!(parse_error("xs for n:\nfoo"), ParseErrorKind::ExpectedBlock);
+    assert_eq!(parse_error("xs for n print"), ParseErrorKind::ExpectedColon);
+    assert_eq!(parse_error("f x\nelse: y"), ParseErrorKind::UnexpectedContinuation);
+}
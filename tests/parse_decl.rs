@@ -0,0 +1,112 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn parse_all(src: &str) -> (Vec<Stmt>, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    dbg!(&ast, &diagnostics);
+    (ast.stmts, diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect())
+}
+
+fn decls(src: &str) -> Vec<Stmt> {
+    let (stmts, errors) = parse_all(src);
+    assert_eq!(errors, []);
+    stmts
+}
+
+fn names(idents: &[Ident]) -> Vec<&str> {
+    idents.iter().map(|ident| ident.name.as_str()).collect()
+}
+
+#[test]
+fn types() {
+    let stmts = decls("\
+type Meters := f64
+pub type Result 't 'e:
+    Ok 't
+    Err 'e
+type List 't where 't is Eq + Into u64:
+    Cons 't ('t .List); Nil
+class Point:
+    pub x: f64
+    y: f64 .Array
+");
+    let [Stmt::Type(meters), Stmt::Type(result), Stmt::Type(list), Stmt::Type(point)] = &stmts[..] else { panic!() };
+    assert!(matches!(&meters.body, TypeBody::Alias(TypeExpr { kind: TypeExprKind::Name(name), .. }) if name == "f64"));
+
+    assert_eq!((result.vis, names(&result.params)), (Visibility::Pub, vec!["t", "e"]));
+    let TypeBody::Variants(variants) = &result.body else { panic!() };
+    assert_eq!(variants.iter().map(|variant| variant.name.name.as_str()).collect::<Vec<_>>(), ["Ok", "Err"]);
+    assert!(matches!(variants[1].fields[..], [TypeExpr { kind: TypeExprKind::Var(_), .. }]));
+
+    let [Predicate { ty, bounds, .. }] = &list.where_clause[..] else { panic!() };
+    assert!(matches!(ty.kind, TypeExprKind::Var(_)));
+    assert_eq!(bounds.iter().map(|bound| (bound.name.name.as_str(), bound.args.len())).collect::<Vec<_>>(), [("Eq", 0), ("Into", 1)]);
+    let TypeBody::Variants(variants) = &list.body else { panic!() };
+    assert!(matches!(&variants[..], [Variant { fields, .. }, Variant { .. }] if matches!(fields[1].kind, TypeExprKind::Apply { .. })));
+
+    let TypeBody::Record(fields) = &point.body else { panic!() };
+    assert_eq!(fields.iter().map(|field| (field.name.name.as_str(), field.vis)).collect::<Vec<_>>(), [("x", Visibility::Pub), ("y", Visibility::Default)]);
+    assert!(matches!(fields[1].ty.kind, TypeExprKind::Apply { .. }));
+}
+
+#[test]
+fn traits_and_impls() {
+    let stmts = decls("\
+pub trait Iterator 'iter 'item:
+    next 'iter : 'item .Option
+    count 'iter : int :=
+        mut n := 0
+        n
+    nth 'iter 'n : 'item .Option :=
+    nth 'it 0 ::= next it
+    nth 'it 'n ::= nth it (n - 1)
+trait Show for 'a .List where 'a is Show:
+    show 'xs := xs .map show
+trait Into u64 for s32:
+    into 'x := x as u64
+");
+    let [Stmt::Trait(iterator), Stmt::Impl(show), Stmt::Impl(into)] = &stmts[..] else { panic!() };
+    assert_eq!((iterator.vis, names(&iterator.params)), (Visibility::Pub, vec!["iter", "item"]));
+    let [Stmt::Signature(next), Stmt::Function(count), Stmt::Function(nth)] = &iterator.items[..] else { panic!() };
+    assert_eq!((next.name.name.as_str(), next.params.len()), ("next", 1));
+    assert!(matches!(&next.ty.kind, TypeExprKind::Apply { name, .. } if name.name == "Option"));
+    assert!(count.body.is_some());
+    assert_eq!(nth.clauses.len(), 2);
+
+    assert_eq!(show.trait_ref.name.name, "Show");
+    assert!(matches!(&show.ty.kind, TypeExprKind::Apply { name, .. } if name.name == "List"));
+    assert_eq!(show.where_clause.len(), 1);
+    assert!(matches!(show.items[..], [Stmt::Function(_)]));
+
+    assert!(matches!(into.trait_ref.args[..], [TypeExpr { kind: TypeExprKind::Name(_), .. }]));
+    assert!(into.where_clause.is_empty());
+}
+
+#[test]
+fn errors() {
+    let (stmts, errors) = parse_all("\
+type Option t:
+    None
+class Point:
+    x: f64
+    x f64
+    x: s32
+pub trait Show for s32:
+    show 'x := x
+trait Eq 't:
+    eq 'a 'b
+type Empty:
+x := 1
+");
+    assert!(matches!(errors[..], [
+        ParseErrorKind::ExpectedTypeParam,
+        ParseErrorKind::ExpectedColon,
+        ParseErrorKind::DuplicateField { .. },
+        ParseErrorKind::ImplVisibility,
+        ParseErrorKind::ExpectedColon,
+        ParseErrorKind::ExpectedBlock,
+    ]));
+    assert!(matches!(stmts[1], Stmt::Type(TypeDecl { body: TypeBody::Record(ref fields), .. }) if fields.len() == 2));
+    assert!(matches!(stmts.last(), Some(Stmt::Let(_))));
+}
@@ -0,0 +1,52 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn lambda(src: &str) -> (Vec<Pattern>, Expr, Vec<String>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let ast = parse(&tokens).unwrap();
+    let Stmt::Let(Let { value, .. }) = &ast.stmts[0] else { panic!() };
+    dbg!(&value);
+    let ExprKind::Lambda { params, body, captures, .. } = &value.kind else { panic!() };
+    let captures = captures.iter().map(|capture| capture.name.to_string()).collect();
+    (params.clone(), *body.clone(), captures)
+}
+
+#[test]
+fn tuple_parameter() {
+    let (params, body, captures) = lambda("\
+u := fn ('a = Some 'n, 'b = Some, 'c = Some 'm, None, ?'e ) :
+    n - m + (e or 0)
+");
+    let [Pattern { kind: PatternKind::Tuple(elements), .. }] = &params[..] else { panic!() };
+    assert_eq!(elements.len(), 5);
+    assert!(matches!(&elements[0].kind, PatternKind::Bind { name, pattern: Some(_), .. } if name.name == "a"));
+    assert!(matches!(&elements[3].kind, PatternKind::Constructor { name, .. } if name.name == "None"));
+    assert!(matches!(&elements[4].kind, PatternKind::Optional { name, default: None } if name.name == "e"));
+    assert!(matches!(body.kind, ExprKind::Block(_)));
+    assert!(captures.is_empty());
+}
+
+#[test]
+fn inline_body() {
+    let (params, body, captures) = lambda("add := fn 'x (?'y = 1) : x + y + z");
+    assert_eq!(params.len(), 2);
+    assert!(matches!(body.kind, ExprKind::Binary { .. }));
+    assert_eq!(captures, ["z"]);
+
+    let tokens: Vec<_> = TokenIterator::new("f := map (fn 'x: x * 2) xs").collect();
+    let ast = parse(&tokens).unwrap();
+    let Stmt::Let(Let { value: Expr { kind: ExprKind::Apply { args, .. }, .. }, .. }) = &ast.stmts[0] else { panic!() };
+    assert!(matches!(args[0].kind, ExprKind::Lambda { .. }));
+}
+
+#[test]
+fn captures() {
+    let (_, _, captures) = lambda("\
+f := fn 'x :
+    y := x + a
+    xs for 'n: print (n + y)
+    g 'k := k + b + g (k - 1)
+    fn 'z : z + x + c
+");
+    assert_eq!(captures, ["a", "xs", "print", "b", "c"]);
+}
@@ -0,0 +1,88 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn parse_all(src: &str) -> (Vec<Stmt>, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    dbg!(&ast, &diagnostics);
+    (ast.stmts, diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect())
+}
+
+fn arms(src: &str) -> (Expr, Vec<Arm>) {
+    let (stmts, errors) = parse_all(src);
+    assert_eq!(errors, []);
+    let Stmt::Let(Let { value: Expr { kind: ExprKind::Match { scrutinee, arms }, .. }, .. }) = &stmts[0] else { panic!() };
+    (*scrutinee.clone(), arms.clone())
+}
+
+fn constructor(pattern: &Pattern) -> (&str, &[Pattern]) {
+    let PatternKind::Constructor { name, args } = &pattern.kind else { panic!("{pattern:?}") };
+    (&name.name, args)
+}
+
+#[test]
+fn arms_and_guards() {
+    let (scrutinee, arms) = arms("\
+x := match f y:
+    0 => zero
+    'n if n > 0 => n * 2
+    -1 => 1; _ =>
+        print \"other\"
+        0
+");
+    assert!(matches!(scrutinee.kind, ExprKind::Apply { .. }));
+    assert_eq!(arms.len(), 4);
+    assert!(matches!(arms[0].pattern.kind, PatternKind::Literal { negative: false, .. }));
+    assert!(matches!(arms[1].guard, Some(Expr { kind: ExprKind::Binary { .. }, .. })));
+    assert!(matches!(arms[2].pattern.kind, PatternKind::Literal { negative: true, .. }));
+    assert!(matches!(arms[3].pattern.kind, PatternKind::Wildcard));
+    assert!(matches!(&arms[3].body.kind, ExprKind::Block(stmts) if stmts.len() == 2));
+    assert_eq!((arms[1].span.row, arms[1].span.num, arms[1].span.end_num), (2, 5, 25));
+}
+
+#[test]
+fn nested_constructors() {
+    let (_, arms) = arms("\
+x := match list:
+    Cons 'a (Cons 'b Nil) => a + b
+    Cons (Some 'a) 'rest if a > 0 => a
+    'all = Cons _ (Cons _ _) => all
+    (Nil, [Some _, None]) => 0
+");
+    let (name, args) = constructor(&arms[0].pattern);
+    assert_eq!(name, "Cons");
+    let (inner, inner_args) = constructor(&args[1]);
+    assert_eq!(inner, "Cons");
+    assert!(matches!(inner_args[..], [Pattern { kind: PatternKind::Bind { .. }, .. }, Pattern { kind: PatternKind::Constructor { .. }, .. }]));
+
+    let (_, args) = constructor(&arms[1].pattern);
+    assert_eq!(constructor(&args[0]).0, "Some");
+    assert!(arms[1].guard.is_some());
+
+    let PatternKind::Bind { name, pattern: Some(pattern), .. } = &arms[2].pattern.kind else { panic!() };
+    assert_eq!(name.name, "all");
+    assert_eq!(constructor(pattern).1.len(), 2);
+    assert!(matches!(&arms[3].pattern.kind, PatternKind::Tuple(elements) if matches!(elements[1].kind, PatternKind::List(_))));
+}
+
+#[test]
+fn scrutinee_keeps_colon() {
+    // the colon ends the scrutinee instead of passing a block to `f`
+    let (_, arms) = arms("x := match f (g: 1):\n    _ => 0\n");
+    assert_eq!(arms.len(), 1);
+}
+
+#[test]
+fn errors() {
+    let (stmts, errors) = parse_all("\
+x := match y:
+    0 -> 1
+    1 => 2
+z := match y
+");
+    assert_eq!(errors, [ParseErrorKind::ExpectedArrow, ParseErrorKind::ExpectedColon]);
+    let Stmt::Let(Let { value: Expr { kind: ExprKind::Match { arms, .. }, .. }, .. }) = &stmts[0] else { panic!() };
+    assert!(matches!(arms[..], [Arm { pattern: Pattern { kind: PatternKind::Error, .. }, .. }, Arm { guard: None, .. }]));
+    assert_eq!(stmts.len(), 2);
+    assert!(matches!(parse_all("m := match y:\nn := 1\n").1[..], [ParseErrorKind::ExpectedBlock]));
+}
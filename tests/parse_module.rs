@@ -0,0 +1,64 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn parse_src(src: &str) -> Result<Ast, Vec<ParseError>> {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let res = parse(&tokens);
+    dbg!(&res);
+    res
+}
+
+fn use_tree(src: &str) -> UseTree {
+    let ast = parse_src(src).unwrap();
+    let [Stmt::Use(UseDecl { tree, .. })] = &ast.stmts[..] else { panic!() };
+    tree.clone()
+}
+
+fn path(tree: &UseTree) -> Vec<&str> {
+    tree.path.iter().map(|segment| segment.name.as_str()).collect()
+}
+
+#[test]
+fn declarations_and_visibility() {
+    let ast = parse_src("\
+mod math
+pub mod io
+pub use math.fact
+priv x : s32 := 1
+pub mut y := 2
+pub f 'n :=
+f 0 ::= 1
+").unwrap();
+    let [Stmt::Mod(math), Stmt::Mod(io), Stmt::Use(fact), Stmt::Let(x), Stmt::Let(y), Stmt::Function(f)] = &ast.stmts[..] else { panic!() };
+    assert_eq!((math.name.name.as_str(), math.vis), ("math", Visibility::Default));
+    assert_eq!(io.vis, Visibility::Pub);
+    assert_eq!((fact.vis, path(&fact.tree)), (Visibility::Pub, vec!["math", "fact"]));
+    assert_eq!(x.vis, Visibility::Priv);
+    assert!(x.ty.is_some());
+    assert!(y.vis == Visibility::Pub && y.mutable);
+    assert_eq!((f.vis, f.clauses.len()), (Visibility::Pub, 1));
+}
+
+#[test]
+fn use_trees() {
+    let tree = use_tree("use std.io.*");
+    assert_eq!((path(&tree), &tree.kind), (vec!["std", "io"], &UseKind::Glob));
+    assert_eq!(use_tree("use a . *").kind, UseKind::Glob);
+
+    let tree = use_tree("use std.fmt.Display as Show");
+    assert!(matches!(&tree.kind, UseKind::Single { rename: Some(name) } if name.name == "Show"));
+
+    let tree = use_tree("use std.(io, fmt.(Display as Show, Debug), collections.*,)");
+    assert_eq!(path(&tree), ["std"]);
+    let UseKind::Group(trees) = &tree.kind else { panic!() };
+    assert_eq!(trees.iter().map(path).collect::<Vec<_>>(), [vec!["io"], vec!["fmt"], vec!["collections"]]);
+    assert!(matches!(&trees[1].kind, UseKind::Group(inner) if inner.len() == 2));
+    assert_eq!(trees[2].kind, UseKind::Glob);
+}
+
+#[test]
+fn errors() {
+    let errors = parse_src("f _ :=\npub f 0 ::= 1\nuse a.(b, 1)\nmod\n").unwrap_err();
+    let kinds: Vec<_> = errors.iter().map(|error| error.kind.clone()).collect();
+    assert_eq!(kinds, [ParseErrorKind::ClauseVisibility, ParseErrorKind::ExpectedName, ParseErrorKind::ExpectedName]);
+}
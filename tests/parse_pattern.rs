@@ -0,0 +1,75 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn params(src: &str) -> Vec<PatternKind> {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let ast = parse(&tokens).unwrap();
+    let Stmt::Function(function) = &ast.stmts[0] else { panic!() };
+    let res = function.params.iter().map(|param| param.kind.clone()).collect();
+    dbg!(&res);
+    res
+}
+
+fn error(src: &str) -> ParseError {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let res = parse(&tokens).unwrap_err().remove(0);
+    dbg!(&res);
+    res
+}
+
+#[test]
+fn binders_and_literals() {
+    let params = params("f 'prod 0 _ -1 \"s\" None := prod");
+    assert!(matches!(&params[0], PatternKind::Bind { name, pattern: None, guard: None } if name.name == "prod"));
+    assert!(matches!(&params[1], PatternKind::Literal { value: Literal::Number(number), negative: false } if number.int_value() == Some(0)));
+    assert!(matches!(params[2], PatternKind::Wildcard));
+    assert!(matches!(params[3], PatternKind::Literal { negative: true, .. }));
+    assert!(matches!(params[4], PatternKind::Literal { value: Literal::String(_), .. }));
+    assert!(matches!(&params[5], PatternKind::Constructor { name, args } if name.name == "None" && args.is_empty()));
+}
+
+#[test]
+fn guard() {
+    let params = params("f ('n > 0) := n");
+    let PatternKind::Bind { name, pattern: None, guard: Some(guard) } = &params[0] else { panic!() };
+    assert_eq!(name.name, "n");
+    let ExprKind::Binary { op, lhs, .. } = &guard.kind else { panic!() };
+    assert_eq!(op.name, ">");
+    assert!(matches!(&lhs.kind, ExprKind::Argument(name) if name == "n"));
+}
+
+#[test]
+fn lambda_like_parameters() {
+    let params = params("u ('a = Some 'n, 'b = Some, None, ?'e, ?'d = 0) := n");
+    let [PatternKind::Tuple(elements)] = &params[..] else { panic!() };
+    assert_eq!(elements.len(), 5);
+    let PatternKind::Bind { name, pattern: Some(pattern), guard: None } = &elements[0].kind else { panic!() };
+    assert_eq!(name.name, "a");
+    assert!(matches!(&pattern.kind, PatternKind::Constructor { args, .. } if matches!(args[..], [Pattern { kind: PatternKind::Bind { .. }, .. }])));
+    assert!(matches!(&elements[1].kind, PatternKind::Bind { pattern: Some(pattern), .. } if matches!(pattern.kind, PatternKind::Constructor { .. })));
+    assert!(matches!(&elements[3].kind, PatternKind::Optional { name, default: None } if name.name == "e"));
+    assert!(matches!(&elements[4].kind, PatternKind::Optional { default: Some(_), .. }));
+}
+
+#[test]
+fn groups() {
+    let params = params("f () ('x) ('x,) [] ['x, _] (Some ('n, 'm)) := 0");
+    assert!(matches!(params[0], PatternKind::Unit));
+    assert!(matches!(params[1], PatternKind::Bind { .. }));
+    assert!(matches!(&params[2], PatternKind::Tuple(elements) if elements.len() == 1));
+    assert!(matches!(&params[3], PatternKind::List(elements) if elements.is_empty()));
+    assert!(matches!(&params[4], PatternKind::List(elements) if elements.len() == 2));
+    let PatternKind::Constructor { args, .. } = &params[5] else { panic!() };
+    assert!(matches!(&args[..], [Pattern { kind: PatternKind::Tuple(_), .. }]));
+}
+
+#[test]
+fn errors() {
+    let err = error("f ?x := 0");
+    assert_eq!(err.kind, ParseErrorKind::ExpectedBinder);
+    assert_eq!((err.span.row, err.span.num), (0, 4));
+
+    let err = error("f (Some 'n, +) := 0");
+    assert_eq!(err.kind, ParseErrorKind::ExpectedPattern);
+    assert_eq!((err.span.num, err.span.end_num), (13, 14));
+}
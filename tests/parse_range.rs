@@ -0,0 +1,149 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+use rolang::prelude;
+use rolang::printer::print;
+
+fn parse_all(src: &str) -> (Ast, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    dbg!(&ast, &diagnostics);
+    (ast, diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect())
+}
+
+/// Value of the definition `x := ...`
+fn value(src: &str) -> Expr {
+    let (ast, errors) = parse_all(&format!("x := {src}"));
+    assert_eq!(errors, []);
+    let Some(Stmt::Let(Let { value, .. })) = ast.stmts.into_iter().next() else { panic!() };
+    value
+}
+
+/// Bounds of a range and whether it is inclusive
+fn range(expr: &Expr) -> (Option<&Expr>, Option<&Expr>, bool) {
+    match &expr.kind {
+        ExprKind::Range { start, end, inclusive } => (start.as_deref(), end.as_deref(), *inclusive),
+        _ => panic!("not a range: {expr:?}"),
+    }
+}
+
+fn int(expr: Option<&Expr>) -> u128 {
+    match expr.map(|expr| &expr.kind) {
+        Some(ExprKind::Literal(Literal::Number(number))) => number.int_value().unwrap(),
+        _ => panic!("not an integer: {expr:?}"),
+    }
+}
+
+#[test]
+fn forms() {
+    let expr = value("0..2");
+    let (start, end, inclusive) = range(&expr);
+    assert_eq!((int(start), int(end), inclusive), (0, 2, false));
+    assert_eq!(expr.span, Span { row: 0, num: 6, end_row: 0, end_num: 10 });
+
+    let expr = value("1..=n");
+    assert!(matches!(range(&expr), (Some(_), Some(Expr { kind: ExprKind::Name(_), .. }), true)));
+    assert!(matches!(range(&value("a..")), (Some(_), None, false)));
+    assert!(matches!(range(&value("..=10")), (None, Some(_), true)));
+    assert!(matches!(range(&value("..")), (None, None, false)));
+    assert!(matches!(range(&value("-5..-1")), (Some(Expr { kind: ExprKind::Unary { .. }, .. }), Some(_), false)));
+}
+
+#[test]
+fn precedence() {
+    // the range is iterated, not `2 .iter`
+    let ExprKind::Method { receiver: Some(receiver), method, .. } = value("0..2 .iter .map f").kind else { panic!() };
+    assert!(matches!(method.kind, ExprKind::Name(ref name) if name == "map"));
+    let ExprKind::Method { receiver: Some(iterated), .. } = receiver.kind else { panic!() };
+    let (start, end, _) = range(&iterated);
+    assert_eq!((int(start), int(end)), (0, 2));
+
+    let expr = value("a + 1..b * 2");
+    let (Some(start), Some(end), _) = range(&expr) else { panic!() };
+    assert!(matches!((&start.kind, &end.kind), (ExprKind::Binary { .. }, ExprKind::Binary { .. })));
+
+    assert!(matches!(value("x == 0..2").kind, ExprKind::Binary { ref op, .. } if op.name == "=="));
+    assert!(matches!(value("(..n) .rev").kind, ExprKind::Method { .. }));
+    // an open range ends before a method chain
+    assert!(matches!(value("0.. .take 3").kind, ExprKind::Method { .. }));
+}
+
+#[test]
+fn patterns() {
+    let (ast, errors) = parse_all("\
+fact_impl _ _ :=
+fact_impl 'prod 0..1 ::= prod
+fact_impl 'prod (..=-1) ::= prod
+fact_impl 'prod 'n ::= fact_impl (n * prod) (n - 1)
+letter 'c := match c:
+    'a'..='z' => true
+    100.. => false
+    _ => false
+");
+    assert_eq!(errors, []);
+    let Stmt::Function(fact) = &ast.stmts[0] else { panic!() };
+    let PatternKind::Range { start: Some(start), end: Some(end), inclusive: false } = &fact.clauses[0].params[1].kind else {
+        panic!()
+    };
+    assert!(matches!(start.kind, PatternKind::Literal { negative: false, .. }));
+    assert!(matches!(end.kind, PatternKind::Literal { .. }));
+    let PatternKind::Range { start: None, end: Some(end), inclusive: true } = &fact.clauses[1].params[1].kind else { panic!() };
+    assert!(matches!(end.kind, PatternKind::Literal { negative: true, .. }));
+
+    let Stmt::Function(FunctionDef { body: Some(Expr { kind: ExprKind::Match { arms, .. }, .. }), .. }) = &ast.stmts[1] else {
+        panic!()
+    };
+    assert!(matches!(arms[0].pattern.kind, PatternKind::Range { start: Some(_), end: Some(_), inclusive: true }));
+    assert!(matches!(arms[1].pattern.kind, PatternKind::Range { start: Some(_), end: None, .. }));
+}
+
+#[test]
+fn bound_types() {
+    let (_, errors) = parse_all("a := 0..2.5\nb := 'a'..\"z\"\nc := 0u8..=255\nd := 1..10u64\ne := 1u8..n\nf := 'a'..5");
+    assert_eq!(errors, [
+        ParseErrorKind::RangeBoundMismatch { start: "integer".into(), end: "float".into() },
+        ParseErrorKind::InvalidRangeBound { ty: "string".into() },
+        ParseErrorKind::RangeBoundMismatch { start: "char".into(), end: "integer".into() },
+    ]);
+
+    let (_, errors) = parse_all("f 0u8..2s32 := 1\nf 5..1 := 2\nf 1..1 := 3\nf 1..=1 := 4\nf -1..=-2 := 5\nf 0..= := 6");
+    assert_eq!(errors, [
+        ParseErrorKind::RangeBoundMismatch { start: "u8".into(), end: "s32".into() },
+        ParseErrorKind::EmptyRangePattern,
+        ParseErrorKind::EmptyRangePattern,
+        ParseErrorKind::EmptyRangePattern,
+        ParseErrorKind::InclusiveRangeWithoutEnd,
+    ]);
+}
+
+#[test]
+fn printing() {
+    let src = "\
+x := (0..2 .iter) (a + 1..=b) (-1.. -2 .abs) (1.5..)
+y := (..) (0..) (0.. .x) (0..(.x)) (0..(!x))
+f 'n 0..10 (..=-1) 'a'..'z' := n
+";
+    let (ast, errors) = parse_all(src);
+    assert_eq!(errors, []);
+    let printed = print(&ast, src);
+    assert_eq!(printed, "\
+x := (0..2 .iter) (a + 1..=b) (-1..-2 .abs) (1.5..)
+y := (..) (0..) (0.. .x) (0..(.x)) (0..(!x))
+f 'n 0..10 ..=-1 'a'..'z' := n
+");
+    let (reparsed, _) = parse_all(&printed);
+    assert_eq!(reparsed.without_spans(), ast.without_spans());
+}
+
+#[test]
+fn standard_range() {
+    let prelude = prelude::module();
+    assert_eq!(prelude.diagnostics, []);
+    let range = prelude.ast.stmts.iter().find_map(|stmt| match stmt {
+        Stmt::Type(decl) if decl.name.name == "Range" => Some(decl),
+        _ => None,
+    });
+    let TypeBody::Record(fields) = &range.unwrap().body else { panic!() };
+    let names: Vec<_> = fields.iter().map(|field| field.name.name.as_str()).collect();
+    assert_eq!(names, ["start", "end", "inclusive"]);
+    assert!(prelude.ast.stmts.iter().any(|stmt| matches!(stmt, Stmt::Impl(impl_) if impl_.trait_ref.name.name == "Iterator")));
+}
@@ -0,0 +1,94 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn parse_all(src: &str) -> (Ast, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    for diagnostic in &diagnostics {
+        println!("{diagnostic}");
+    }
+    dbg!(&ast);
+    (ast, diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect())
+}
+
+fn let_value(stmt: &Stmt) -> &ExprKind {
+    let Stmt::Let(Let { value, .. }) = stmt else { panic!() };
+    &value.kind
+}
+
+#[test]
+fn every_statement_kept() {
+    let (ast, errors) = parse_all("\
+a := 1 +
+b := )
+c := f (x + ) y
+d := 4
+");
+    assert_eq!(ast.stmts.len(), 5);
+    assert!(matches!(let_value(&ast.stmts[0]), ExprKind::Binary { rhs, .. } if rhs.kind == ExprKind::Missing));
+    assert_eq!(let_value(&ast.stmts[1]), &ExprKind::Missing);
+    assert!(matches!(&ast.stmts[2], Stmt::Expr(Expr { kind: ExprKind::Error, .. })));
+    assert!(matches!(let_value(&ast.stmts[3]), ExprKind::Apply { args, .. } if args.len() == 2));
+    assert!(matches!(let_value(&ast.stmts[4]), ExprKind::Literal(_)));
+    assert_eq!(errors, [
+        ParseErrorKind::ExpectedExpression,
+        ParseErrorKind::ExpectedExpression,
+        ParseErrorKind::UnexpectedToken,
+        ParseErrorKind::ExpectedExpression,
+    ]);
+}
+
+#[test]
+fn block_resync() {
+    let (ast, errors) = parse_all("\
+main () :=
+    x := 1
+    then y
+    f (a ]
+    z
+");
+    assert_eq!(errors, [ParseErrorKind::UnexpectedContinuation, ParseErrorKind::MismatchedBracket]);
+    let [Stmt::Function(FunctionDef { body: Some(body), .. })] = &ast.stmts[..] else { panic!() };
+    let ExprKind::Block(stmts) = &body.kind else { panic!() };
+    assert_eq!(stmts.len(), 4);
+    assert!(matches!(&stmts[1], Stmt::Expr(Expr { kind: ExprKind::Error, span })
+        if (span.num, span.end_num) == (5, 11)));
+}
+
+#[test]
+fn unclosed_bracket() {
+    let (ast, errors) = parse_all("\
+main () :=
+    f (a b
+    g
+x := 1
+");
+    assert_eq!(errors, [ParseErrorKind::UnclosedBracket]);
+    assert_eq!(ast.stmts.len(), 2);
+    assert!(matches!(&ast.stmts[1], Stmt::Let(_)));
+}
+
+#[test]
+fn bracket_resync() {
+    let (ast, errors) = parse_all("x := g (a ::=) (b)");
+    assert_eq!(errors, [ParseErrorKind::UnexpectedToken]);
+    let ExprKind::Apply { args, .. } = let_value(&ast.stmts[0]) else { panic!() };
+    assert!(matches!(args[..], [
+        Expr { kind: ExprKind::Name(_), .. },
+        Expr { kind: ExprKind::Name(_), .. },
+    ]));
+}
+
+#[test]
+fn lexer_errors() {
+    let (ast, errors) = parse_all("x := 0x + '\\q' + \"abc\ny := \u{0663}");
+    assert!(matches!(errors[..], [
+        ParseErrorKind::InvalidNumber(number),
+        ParseErrorKind::InvalidChar(char),
+        ParseErrorKind::InvalidString(string),
+        ParseErrorKind::InvalidToken,
+    ] if number.contains(NumberErrorFlags::NO_START_DIGITS)
+        && char.contains(CharErrorFlags::BAD_ESC_SEQUENCE)
+        && string.contains(StringErrorFlags::UNCLOSED)));
+    assert_eq!(let_value(&ast.stmts[1]), &ExprKind::Error);
+}
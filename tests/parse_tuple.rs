@@ -0,0 +1,59 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn value(src: &str) -> ExprKind {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let ast = parse(&tokens).unwrap();
+    let Stmt::Let(Let { value, .. }) = &ast.stmts[0] else { panic!() };
+    dbg!(&value.kind);
+    value.kind.clone()
+}
+
+fn parse_errors(src: &str) -> Vec<ParseError> {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let res = parse(&tokens).unwrap_err();
+    dbg!(&res);
+    res
+}
+
+fn names(fields: &[Field]) -> Vec<Option<&str>> {
+    fields.iter().map(|field| field.name.as_ref().map(|name| name.name.as_str())).collect()
+}
+
+#[test]
+fn grouping_and_tuples() {
+    assert!(matches!(value("x := ()"), ExprKind::Unit));
+    assert!(matches!(value("x := (1 + 2)"), ExprKind::Binary { .. }));
+    assert!(matches!(value("x := (1,)"), ExprKind::Tuple(fields) if fields.len() == 1));
+    assert!(matches!(value("x := (1, f 2)"), ExprKind::Tuple(fields) if fields.len() == 2));
+    assert!(matches!(value("x := Some()"), ExprKind::Apply { args, .. } if matches!(args[0].kind, ExprKind::Unit)));
+}
+
+#[test]
+fn named_fields() {
+    let ExprKind::Tuple(fields) = value("x := (b := 1, 2, a := 3,)") else { panic!() };
+    assert_eq!(names(&fields), [Some("b"), None, Some("a")]);
+    assert!(matches!(&fields[2].value.kind, ExprKind::Literal(Literal::Number(number)) if number.int_value() == Some(3)));
+
+    // a single named field makes a tuple too
+    let ExprKind::Tuple(fields) = value("x := (a := f 1)") else { panic!() };
+    assert_eq!(names(&fields), [Some("a")]);
+}
+
+#[test]
+fn lists() {
+    assert!(matches!(value("x := []"), ExprKind::List(elements) if elements.is_empty()));
+    let ExprKind::List(elements) = value("x := [4, 3,\n  2, 1]") else { panic!() };
+    assert_eq!(elements.len(), 4);
+    assert!(matches!(value("x := [(1, 2)]"), ExprKind::List(elements) if matches!(elements[0].kind, ExprKind::Tuple(_))));
+}
+
+#[test]
+fn field_errors() {
+    let errors = parse_errors("x := (a := 1, b := 2, a := 3)");
+    let [ParseError { kind: ParseErrorKind::DuplicateField { first }, span }] = &errors[..] else { panic!() };
+    assert_eq!((first.num, span.num), (7, 23));
+
+    let errors = parse_errors("x := [a := 1, 2]");
+    assert!(matches!(errors[..], [ParseError { kind: ParseErrorKind::NamedListElement, .. }]));
+}
@@ -0,0 +1,89 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+
+fn ascription(src: &str) -> TypeExpr {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let ast = parse(&tokens).unwrap();
+    let ty = match &ast.stmts[0] {
+        Stmt::Let(Let { ty, .. }) | Stmt::Function(FunctionDef { ty, .. }) => ty.clone().unwrap(),
+        _ => panic!(),
+    };
+    dbg!(&ty);
+    ty
+}
+
+fn parse_errors(src: &str) -> Vec<ParseErrorKind> {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let res: Vec<_> = parse(&tokens).unwrap_err().into_iter().map(|error| error.kind).collect();
+    dbg!(&res);
+    res
+}
+
+fn name(ty: &TypeExpr) -> &str {
+    match &ty.kind {
+        TypeExprKind::Name(name) => name,
+        TypeExprKind::Var(name) => name,
+        _ => panic!("{ty:?}"),
+    }
+}
+
+#[test]
+fn postfix_application() {
+    let ty = ascription("xs : s32 .Array dyn .Box := f 1");
+    let TypeExprKind::Apply { name: boxed, args } = &ty.kind else { panic!() };
+    assert_eq!(boxed.name, "Box");
+    let [TypeExpr { kind: TypeExprKind::Dyn(array), .. }] = &args[..] else { panic!() };
+    let TypeExprKind::Apply { name: array_name, args } = &array.kind else { panic!() };
+    assert_eq!(array_name.name, "Array");
+    assert_eq!(name(&args[0]), "s32");
+    assert_eq!((ty.span.num, ty.span.end_num), (6, 25));
+
+    let ty = ascription("m : str .Map 'v := empty");
+    let TypeExprKind::Apply { args, .. } = &ty.kind else { panic!() };
+    assert_eq!(args.iter().map(name).collect::<Vec<_>>(), ["str", "v"]);
+    assert!(matches!(ascription("d : dyn Show .Box := x").kind, TypeExprKind::Dyn(_)));
+}
+
+#[test]
+fn tuples_and_functions() {
+    let ty = ascription("f 'x 'y : (s32, s32) -> s32 -> () := x");
+    let TypeExprKind::Function { param, result } = &ty.kind else { panic!() };
+    assert!(matches!(&param.kind, TypeExprKind::Tuple(fields) if fields.len() == 2));
+    let TypeExprKind::Function { result, .. } = &result.kind else { panic!() };
+    assert!(matches!(result.kind, TypeExprKind::Unit));
+
+    let ty = ascription("p : (u8, x: f64, y: _) := q");
+    let TypeExprKind::Tuple(fields) = &ty.kind else { panic!() };
+    let names: Vec<_> = fields.iter().map(|field| field.name.as_ref().map(|name| name.name.as_str())).collect();
+    assert_eq!(names, [None, Some("x"), Some("y")]);
+    assert!(matches!(fields[2].ty.kind, TypeExprKind::Hole));
+    assert!(matches!(ascription("g : (s32) := 1").kind, TypeExprKind::Name(_)));
+}
+
+#[test]
+fn casts() {
+    let tokens: Vec<_> = TokenIterator::new("\
+x := (xs
+    .iter
+    .collect as (s32 .Array dyn .Box)
+)
+y := a + b as u8 |> f
+").collect();
+    let ast = parse(&tokens).unwrap();
+    let Stmt::Let(Let { value, .. }) = &ast.stmts[0] else { panic!() };
+    let ExprKind::Cast { expr, ty } = &value.kind else { panic!() };
+    assert!(matches!(expr.kind, ExprKind::Method { .. }));
+    assert!(matches!(&ty.kind, TypeExprKind::Apply { name, .. } if name.name == "Box"));
+
+    let Stmt::Let(Let { value, .. }) = &ast.stmts[1] else { panic!() };
+    let ExprKind::Binary { lhs, .. } = &value.kind else { panic!() };
+    assert!(matches!(&lhs.kind, ExprKind::Cast { expr, .. } if matches!(expr.kind, ExprKind::Binary { .. })));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(parse_errors("p : (x: u8, f64) := q"), [ParseErrorKind::PositionalAfterNamed]);
+    assert!(matches!(parse_errors("p : (x: u8, x: f64) := q")[..], [ParseErrorKind::DuplicateField { .. }]));
+    assert_eq!(parse_errors("x : := 1"), [ParseErrorKind::ExpectedType]);
+    assert_eq!(parse_errors("f _ :=\nf 0 : s32 ::= 1"), [ParseErrorKind::AscribedClause]);
+}
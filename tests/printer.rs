@@ -0,0 +1,124 @@
+use std::path::Path;
+use rolang::lexer::*;
+use rolang::parser::*;
+use rolang::printer::print;
+
+fn parse_all(src: &str) -> (Ast, Vec<ParseErrorKind>) {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    (ast, diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect())
+}
+
+/// Prints `src` and checks that the printed source parses to the same tree and prints the same again
+fn round_trip(src: &str) -> String {
+    let (ast, errors) = parse_all(src);
+    let printed = print(&ast, src);
+    dbg!(&printed);
+    let (reparsed, reparsed_errors) = parse_all(&printed);
+    assert_eq!(reparsed.without_spans(), ast.without_spans());
+    assert_eq!(reparsed_errors, errors);
+    assert_eq!(print(&reparsed, &printed), printed);
+    printed
+}
+
+fn sources(dir: &Path, found: &mut Vec<String>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            sources(&path, found);
+        } else if path.extension().is_some_and(|ext| ext == "rolang") || path.file_name().is_some_and(|name| name == ".rolang") {
+            found.push(std::fs::read_to_string(path).unwrap());
+        }
+    }
+}
+
+#[test]
+fn examples() {
+    let mut found = vec![];
+    sources(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/examples")), &mut found);
+    assert!(!found.is_empty());
+    for src in found {
+        round_trip(&src);
+    }
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(round_trip("x := ((a + b)) * (c * d) - (e - f)"), "x := (a + b) * (c * d) - (e - f)\n");
+    assert_eq!(round_trip("x := (a ** b) ** c ** d"), "x := (a ** b) ** c ** d\n");
+    assert_eq!(round_trip("x := not (a == b) and (not c) == d or e"), "x := not a == b and (not c) == d or e\n");
+    assert_eq!(round_trip("x := (f a) (g b) (-1) (- (-c))"), "x := (f a) (g b) (-1) (-(-c))\n");
+    assert_eq!(round_trip("x := (f a).b (c .d e) .(g h) .i"), "x := (f a .b) (c .d e) .(g h) .i\n");
+    assert_eq!(round_trip("x := (xs for x: x) + (y as s32) + (fn z: z) (yield 1)"), "x := (xs for x: x) + (y as s32) + (fn z: z) (yield 1)\n");
+    assert_eq!(round_trip("x := f (fn y: y) (fn z: z)"), "x := f (fn y: y) fn z: z\n");
+    // a dot right after a number would make it a float
+    assert_eq!(round_trip("x := 2 .f\ny := (2\n .iter)"), "x := 2 .f\ny := 2 .iter\n");
+}
+
+#[test]
+fn tuples_and_literals() {
+    assert_eq!(round_trip("x := (b := 1, 2, a := (3,),)"), "x := (b := 1, 2, a := (3,))\n");
+    assert_eq!(round_trip("f (a, (b,), ('c = Some _)) : (s32, name: (str,)) := [1, 2]"), "f (a, (b,), 'c = Some _) : (s32, name: (str,)) := [1, 2]\n");
+    assert_eq!(round_trip("x := (\"a\", \"\", \"\"\"say \"hi\\\"\"\"\", 'a', '\\n', ''', 0x1F_u8)"),
+        "x := (\"a\", \"\", \"\"\"say \"hi\\\"\"\"\", 'a', '\\n', ''', 0x1F_u8)\n");
+    assert_eq!(round_trip("x := (_\"a\", \"b\"s, r\"\"\"\"c\"\"\"\"u8, _\"\")"), "x := (_\"a\", \"b\"s, r\"c\"u8, _\"\")\n");
+}
+
+#[test]
+fn blocks() {
+    let src = "\
+main () :=
+    println while:   x < 720
+      then:
+        x .<-fact
+    else: x
+    [1, 2] for (Some 'n): print n
+    r := match x:
+        0 => ()
+        ('n > 0) if n < 10 => n
+        ?'e = 1 =>
+            e
+    f 'x: print x
+";
+    assert_eq!(round_trip(src), "\
+main () :=
+    println while: x < 720
+    then:
+        x .<- fact
+    else: x
+    [1, 2] for Some 'n: print n
+    r := match x:
+        0 => ()
+        ('n > 0) if n < 10 => n
+        ?'e = 1 =>
+            e
+    f 'x (print x)
+");
+    assert_eq!(round_trip("main () :=\n    f: a b:\n        c\n"), "main () :=\n    f: a b:\n        c\n");
+}
+
+#[test]
+fn declarations() {
+    let src = "\
+#inline
+#deprecated \"use g\"
+pub f 'x := x
+fact _ :=
+fact 0 ::= 1
+fact 'n ::= n * fact (n - 1)
+pub use std.(io.*, fmt as f)
+mod util
+type Opt 't where 't is Show + Eq s32:
+    Some 't
+    None
+class Point:
+    pub x: s32
+    y: s32 -> s32 .Array dyn
+trait Show 't:
+    show 't : str
+trait Show (s32, str) for Point:
+    show _ := \"p\"
+g := #[likely, unroll \"4\"] 1
+";
+    round_trip(src);
+}
@@ -0,0 +1,123 @@
+use rolang::syntax::*;
+
+fn check(text: &str, edit: &TextEdit) -> (GreenNode, Reparse) {
+    let old = parse_source(text);
+    let mut new_text = text.to_string();
+    edit.apply(&mut new_text);
+    let res = reparse(&old.green, edit);
+    assert_eq!(res.green.to_string(), new_text);
+    assert_eq!(res.green, parse_source(&new_text).green, "{text:?} {edit:?}");
+    for range in &res.changed {
+        assert!(range.end <= new_text.len());
+    }
+    (old.green, res)
+}
+
+/// Top-level node of `green` holding `offset`
+fn top_level(green: &GreenNode, offset: usize) -> GreenNode {
+    let root = SyntaxNode::new_root(green.clone());
+    root.children().find(|node| node.text_range().contains(&offset)).unwrap().green().clone()
+}
+
+#[test]
+fn block_reused() {
+    let text = "\
+fact := fact_impl 1
+
+main () :=
+    x := f (1 + 2)
+    xs for n:
+        print n
+    y
+
+g := 3
+";
+    let offset = text.find("print").unwrap() + 5;
+    let edit = TextEdit { delete: offset..offset, insert: " 'a".to_string() };
+    let (old, res) = check(text, &edit);
+    dbg!(&res.changed);
+    assert!(top_level(&old, 0).ptr_eq(&top_level(&res.green, 0)));
+    assert!(top_level(&old, text.len() - 2).ptr_eq(&top_level(&res.green, text.len() + 1)));
+    let [changed] = &res.changed[..] else { panic!() };
+    let start = text.find("print").unwrap();
+    assert_eq!(*changed, start..start + "print 'a n".len());
+}
+
+#[test]
+fn fallback() {
+    // the edit reaches lines after the block
+    check("f :=\n    a\ng\n", &TextEdit { delete: 9..9, insert: "(".to_string() });
+    check("f :=\n    (a\n    b)\ng\n", &TextEdit { delete: 10..10, insert: ")".to_string() });
+    check("f :=\n    a\ng\n", &TextEdit { delete: 9..9, insert: "\n  h".to_string() });
+    check("f :=\n    a \"\ng\n", &TextEdit { delete: 12..13, insert: String::new() });
+    check("while: a\nx\n", &TextEdit { delete: 9..10, insert: "then".to_string() });
+}
+
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.next(items.len())]
+    }
+}
+
+#[test]
+fn random_edits() {
+    let lines = [
+        "f 'a ('n > 0) := a + n",
+        "main () :=",
+        "    x := 1",
+        "    xs for n:",
+        "        print n # comment",
+        "      |> g",
+        "    while: x < 3",
+        "    then: x .<-f",
+        "    else:",
+        "        y",
+        "g 0 ::= (1, 2",
+        "  )",
+        "s := \"str\"",
+        "## comment",
+        "",
+        "h x; y",
+        "    y := match x:",
+        "        Some 'a if a > 0 => a",
+        "pub mod util",
+        "use std.(io, fmt.* as x)",
+        "trait Show for s32 where s32 is Eq:",
+        "type Option 't:",
+        "    show 'x : str",
+        "#inline",
+        "    #[test, deprecated \"x\"] z := 2",
+        "    r := yield x + 1",
+    ];
+    let fragments = [
+        "x", " ", "  ", "\n", "\n    ", "\n  ", "\n        ", "(", ")", "]", ":=", "::=", ":", "# ", "#", "\"", "then",
+        "else", "for", "while:", "1", "+", ",", ";", "'a", "\r\n", "\t", "mut ", "\"\"\"", "#[",
+    ];
+
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    for _ in 0..300 {
+        let mut text: String = (0..random.next(10) + 1)
+            .map(|_| random.pick(&lines).to_string() + "\n")
+            .collect();
+        for _ in 0..10 {
+            let start = random.next(text.len() + 1);
+            let end = (start + random.next(4)).min(text.len());
+            let insert = match random.next(3) {
+                0 => String::new(),
+                _ => random.pick(&fragments).to_string(),
+            };
+            let edit = TextEdit { delete: start..end, insert };
+            check(&text, &edit);
+            edit.apply(&mut text);
+        }
+    }
+}
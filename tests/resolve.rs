@@ -0,0 +1,149 @@
+use rolang::loader::parse_source;
+use rolang::parser::*;
+use rolang::prelude;
+use rolang::resolve::*;
+
+fn resolve_all(src: &str) -> Resolution {
+    let (ast, diagnostics) = parse_source(src);
+    assert_eq!(diagnostics, []);
+    let resolution = resolve(&ast, &prelude::module().ast);
+    dbg!(&resolution.defs, &resolution.diagnostics);
+    resolution
+}
+
+fn errors(resolution: &Resolution) -> Vec<ParseErrorKind> {
+    resolution.diagnostics.iter().map(|diagnostic| diagnostic.kind.clone()).collect()
+}
+
+/// Module definition `name` and the rows it's used on
+fn uses<'r>(resolution: &'r Resolution, name: &str, kind: DefKind) -> (&'r Def, Vec<usize>) {
+    let def = resolution.defs.iter().find(|def| def.name == name && def.kind == kind).unwrap();
+    (def, def.uses.iter().map(|span| span.row).collect())
+}
+
+#[test]
+fn scopes() {
+    let resolution = resolve_all("\
+main () :=
+    r := fact 3
+    [r, 2] for 'n:
+        print n
+    match Some r:
+        Some ('v > 0) => v
+        None => r
+fact_impl _ _ :=
+fact_impl 'prod 0 ::= prod
+fact_impl 'prod 'n ::= fact_impl (n * prod) (n - 1)
+fact := fact_impl 1
+");
+    assert_eq!(errors(&resolution), []);
+    assert_eq!(uses(&resolution, "fact", DefKind::Value).1, [1]);
+    assert_eq!(uses(&resolution, "fact_impl", DefKind::Function).1, [9, 10]);
+    assert_eq!(uses(&resolution, "r", DefKind::Local).1, [2, 4, 6]);
+    assert_eq!(uses(&resolution, "n", DefKind::LoopVar).1, [3]);
+    // the guard and the arm see the binder
+    assert_eq!(uses(&resolution, "v", DefKind::Binder).1, [5, 5]);
+    let (some, rows) = uses(&resolution, "Some", DefKind::Constructor);
+    assert_eq!((some.scope, rows), (DefScope::Prelude, vec![4, 5]));
+
+    // each clause binds its own parameters
+    let params: Vec<_> = resolution.defs.iter().filter(|def| def.kind == DefKind::Param).map(|def| def.uses.len()).collect();
+    assert_eq!(params, [1, 1, 2]);
+    let call = Span { row: 9, num: 24, end_row: 9, end_num: 33 };
+    assert_eq!(resolution.def_at(call).map(|def| def.kind), Some(DefKind::Function));
+}
+
+#[test]
+fn lambda_captures() {
+    let resolution = resolve_all("\
+f 'a :=
+    b := 1
+    g := fn 'x: a + b + x + f x
+    h := fn: fn 'y: b + y
+    g
+");
+    assert_eq!(errors(&resolution), []);
+    let mut captures: Vec<_> = resolution.captures.iter()
+        .map(|(span, ids)| (span.row, ids.iter().map(|&id| resolution.defs[id].name.to_string()).collect::<Vec<_>>()))
+        .collect();
+    captures.sort();
+    // the inner lambda of `h` captures `b` through the outer one
+    assert_eq!(captures, [(2, vec!["a".into(), "b".into()]), (3, vec!["b".into()]), (3, vec!["b".into()])]);
+}
+
+#[test]
+fn unresolved() {
+    let resolution = resolve_all("\
+fact 'n := n
+main () :=
+    total := 1
+    fatc 3
+    prnt totl
+    q
+    x.anything
+");
+    assert_eq!(errors(&resolution), [
+        ParseErrorKind::UnresolvedName { name: "fatc".into(), suggestion: Some("fact".into()) },
+        ParseErrorKind::UnresolvedName { name: "prnt".into(), suggestion: Some("print".into()) },
+        ParseErrorKind::UnresolvedName { name: "totl".into(), suggestion: Some("total".into()) },
+        ParseErrorKind::UnresolvedName { name: "q".into(), suggestion: None },
+        ParseErrorKind::UnresolvedName { name: "x".into(), suggestion: None },
+    ]);
+    assert_eq!(resolution.diagnostics[0].to_string(), "4:5: error: cannot find `fatc` in this scope, did you mean `fact`?");
+
+    // names a glob import may bring in are not reported
+    let resolution = resolve_all("use math.*\nuse util.(sum, max as largest)\nx := sum (largest 1 2) (pow 2 3)");
+    assert_eq!(errors(&resolution), []);
+}
+
+#[test]
+fn shadowing() {
+    let resolution = resolve_all("\
+f 'x :=
+    x := x + 1
+    x := 2
+    [1, 2] for 'x: x
+    g := fn 'x: x
+    match x:
+        'y => y
+    _x := 1
+    y := fn '_x: _x
+    fn 'f: f
+");
+    let shadowed = |row, num| ParseErrorKind::ShadowedBinding { name: "x".into(), shadowed: Span { row, num, end_row: row, end_num: num + 1 } };
+    // `x := x + 1` uses the parameter it shadows and `x := 2` rebinds in the same block
+    assert_eq!(errors(&resolution), [shadowed(2, 5), shadowed(2, 5)]);
+    assert!(resolution.diagnostics.iter().all(|diagnostic| diagnostic.severity == Severity::Warning));
+    assert_eq!(resolution.diagnostics[0].to_string(), "4:16: warning: `x` shadows the binding at 3:5");
+}
+
+#[test]
+fn duplicates() {
+    let resolution = resolve_all("\
+x := 1
+x := 2
+trait Show 't:
+    show 't : String
+trait Debug 't:
+    show 't : String
+show 'x := x
+None := 3
+");
+    let first = Span { row: 0, num: 1, end_row: 0, end_num: 2 };
+    // only the prelude has a `None` before
+    assert_eq!(errors(&resolution), [ParseErrorKind::DuplicateDefinition { first }]);
+    let show: Vec<_> = resolution.defs.iter().filter(|def| def.name == "show").map(|def| def.kind).collect();
+    assert_eq!(show, [DefKind::Method, DefKind::Function]);
+}
+
+#[test]
+fn example_and_prelude() {
+    let resolution = resolve_all(include_str!("../examples/factorial/.rolang"));
+    assert_eq!(errors(&resolution), []);
+    let prelude = prelude::module().ast;
+    assert_eq!(resolve(&prelude, &Ast::default()).diagnostics, []);
+
+    assert_eq!(edit_distance("fact", "fatc"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+}
@@ -0,0 +1,149 @@
+use rolang::lexer::*;
+use rolang::syntax::*;
+
+fn parse_tree(src: &str) -> Parse {
+    let res = parse_source(src);
+    dbg!(res.syntax().descendants().collect::<Vec<_>>());
+    assert_eq!(res.syntax().text(), src);
+    res
+}
+
+#[test]
+fn lossless() {
+    for src in [
+        "",
+        "\n\n",
+        "x := 1",
+        "f 'a ('n > 0) := a + n  # comment\n    |> g\n\n\n",
+        "main () :=\r\n\tx := \"a\r\n  b\" \r\n\tf (x\r\n",
+        "a := )\nb := f (x + ) y ]\n  ## only a comment\nc := \"unclosed\n\n",
+        "x := 0x + '\\q' + \u{0663}",
+        "f _ :=\nf 0 ::= 1\n   \nf 'n ::=\n    n *\n      f (n - 1)",
+    ] {
+        parse_tree(src);
+    }
+    parse_tree(include_str!("../examples/factorial/.rolang"));
+}
+
+#[test]
+fn trivia_outside_nodes() {
+    let parse = parse_tree("x := 1 # one\n\ny := 2\n");
+    let root = parse.syntax();
+    let kinds: Vec<_> = root.children_with_tokens().map(|child| child.kind()).collect();
+    assert_eq!(kinds, [
+        SyntaxKind::Definition, SyntaxKind::Whitespace, SyntaxKind::Comment, SyntaxKind::Newline, SyntaxKind::Newline,
+        SyntaxKind::Definition, SyntaxKind::Newline,
+    ]);
+    let first = root.children().next().unwrap();
+    assert_eq!(first.text(), "x := 1");
+}
+
+#[test]
+fn typed_views() {
+    let parse = parse_tree("\
+fact_impl 'prod ('n > 0) ::= fact_impl (n * prod) (n - 1)
+main () :=
+    xs for x: print x
+");
+    let stmts: Vec<_> = parse.root().stmts().collect();
+    let [ast::Stmt::Clause(clause), ast::Stmt::Definition(main)] = &stmts[..] else { panic!() };
+    assert_eq!(clause.name().unwrap().text(), "fact_impl");
+    let params: Vec<_> = clause.params().collect();
+    assert!(matches!(&params[..], [ast::Pattern::BindPat(_), ast::Pattern::GuardPat(_)]));
+    let Some(ast::Expr::Apply(apply)) = clause.body() else { panic!() };
+    assert_eq!(apply.args().count(), 2);
+
+    let Some(ast::Expr::Block(block)) = main.body() else { panic!() };
+    let [ast::Stmt::Expr(ast::Expr::For(for_))] = &block.stmts().collect::<Vec<_>>()[..] else { panic!() };
+    let Some(ast::Pattern::BindPat(binder)) = for_.pattern() else { panic!() };
+    assert_eq!(binder.name().unwrap().text(), "x");
+    assert!(matches!(for_.body(), Some(ast::Expr::Apply(_))));
+}
+
+#[test]
+fn tokens_and_nodes() {
+    let src = "a := f (b + 1)\n\"\"\"\nmulti\n\"\"\" g";
+    let parse = parse_tree(src);
+    for token in TokenIterator::new(src) {
+        if let TokenType::Indent = token.ty {
+            continue;
+        }
+        let syntax = parse.token(&token).unwrap();
+        assert_eq!(parse.line_index.span(syntax.text_range()), rolang::parser::Span::of(&token));
+    }
+
+    let plus = parse.syntax().tokens().find(|token| token.text() == "+").unwrap();
+    let binary = plus.parent();
+    assert_eq!(binary.kind(), SyntaxKind::Binary);
+    assert_eq!(parse.covering_node(parse.span(&binary)), binary);
+    let kinds: Vec<_> = binary.ancestors().map(|node| node.kind()).collect();
+    assert_eq!(kinds, [SyntaxKind::Binary, SyntaxKind::Paren, SyntaxKind::Apply, SyntaxKind::Definition, SyntaxKind::Root]);
+    assert_eq!(plus.next_token().unwrap().kind(), SyntaxKind::Whitespace);
+}
+
+#[test]
+fn errors_keep_tokens() {
+    let parse = parse_tree("x := g (1 :=) (b)\ny := )");
+    assert_eq!(parse.diagnostics.len(), 3);
+    let errors: Vec<_> = parse.syntax().descendants()
+        .filter(|node| node.kind() == SyntaxKind::Error)
+        .map(|node| node.text())
+        .collect();
+    assert_eq!(errors, [":=", ")"]);
+    assert!(parse.syntax().descendants().any(|node| node.kind() == SyntaxKind::Missing));
+}
+
+#[test]
+fn tuple_views() {
+    let parse = parse_tree("x := (b := 1, [2, 3], a := (4))\n");
+    let Some(ast::Stmt::Definition(def)) = parse.root().stmts().next() else { panic!() };
+    let Some(ast::Expr::Tuple(tuple)) = def.body() else { panic!() };
+    let fields: Vec<_> = tuple.fields().collect();
+    let [ast::TupleField::Named(b), ast::TupleField::Positional(ast::Expr::List(list)), ast::TupleField::Named(a)] = &fields[..] else { panic!() };
+    assert_eq!(b.name().unwrap().text(), "b");
+    assert_eq!(list.elements().count(), 2);
+    assert!(matches!(a.value(), Some(ast::Expr::Paren(_))));
+}
+
+#[test]
+fn type_views() {
+    let parse = parse_tree("x : (n: s32 .Array) -> u8 := y as dyn Show\n");
+    let Some(ast::Stmt::Definition(def)) = parse.root().stmts().next() else { panic!() };
+    let Some(ast::TypeExpr::FunctionType(function)) = def.ty() else { panic!() };
+    let Some(ast::TypeExpr::TupleType(tuple)) = function.param() else { panic!() };
+    let [ast::TupleTypeField::Named(field)] = &tuple.fields().collect::<Vec<_>>()[..] else { panic!() };
+    let Some(ast::TypeExpr::ApplyType(array)) = field.ty() else { panic!() };
+    assert_eq!(array.name().unwrap().text(), "Array");
+    assert!(matches!(function.result(), Some(ast::TypeExpr::NameType(_))));
+    let Some(ast::Expr::Cast(cast)) = def.body() else { panic!() };
+    assert!(matches!(cast.ty(), Some(ast::TypeExpr::DynType(_))));
+}
+
+#[test]
+fn declaration_views() {
+    let parse = parse_tree("\
+pub class Pair 'a 'b where 'a is Eq:
+    pub first: 'a
+    second: 'b
+trait Show 't:
+    show 't : str
+trait Into u64 for s32:
+    into 'x := x as u64
+");
+    let [ast::Stmt::TypeDecl(pair), ast::Stmt::TraitDecl(show), ast::Stmt::Impl(into)] = &parse.root().stmts().collect::<Vec<_>>()[..] else { panic!() };
+    assert!(pair.is_class() && pair.visibility().is_some());
+    assert_eq!(pair.params().map(|param| param.text().to_string()).collect::<Vec<_>>(), ["'a", "'b"]);
+    let predicate = pair.where_clause().unwrap().predicates().next().unwrap();
+    assert_eq!(predicate.bounds().next().unwrap().name().unwrap().text(), "Eq");
+    let fields: Vec<_> = pair.fields().collect();
+    assert_eq!(fields.iter().map(|field| field.visibility().is_some()).collect::<Vec<_>>(), [true, false]);
+    assert!(matches!(fields[1].ty(), Some(ast::TypeExpr::VarType(_))));
+
+    let [ast::Stmt::Signature(signature)] = &show.items().collect::<Vec<_>>()[..] else { panic!() };
+    assert_eq!(signature.params().count(), 1);
+    assert!(matches!(signature.ty(), Some(ast::TypeExpr::NameType(_))));
+
+    assert_eq!(into.trait_ref().unwrap().args().count(), 1);
+    assert!(matches!(into.ty(), Some(ast::TypeExpr::NameType(_))));
+    assert!(matches!(into.body().unwrap().stmts().next(), Some(ast::Stmt::Definition(_))));
+}
@@ -0,0 +1,193 @@
+use rolang::infer::*;
+use rolang::loader::parse_source;
+use rolang::parser::*;
+use rolang::prelude;
+use rolang::resolve::{resolve, Resolution};
+
+fn infer_source(src: &str) -> (Typing, Resolution) {
+    let (ast, diagnostics) = parse_source(src);
+    assert_eq!(diagnostics, []);
+    let prelude = prelude::module().ast;
+    let resolution = resolve(&ast, &prelude);
+    assert_eq!(resolution.diagnostics, []);
+    let typing = infer(&ast, &prelude, &resolution);
+    dbg!(&typing.diagnostics);
+    (typing, resolution)
+}
+
+fn messages(src: &str) -> Vec<String> {
+    infer_source(src).0.diagnostics.iter().map(|diagnostic| diagnostic.to_string()).collect()
+}
+
+fn scheme(typing: &Typing, resolution: &Resolution, name: &str) -> String {
+    let id = resolution.defs.iter().position(|def| def.name == name).unwrap();
+    typing.schemes[&id].to_string()
+}
+
+/// Span of the `len` characters at `row` and `num`
+fn span(row: usize, num: usize, len: usize) -> Span {
+    Span { row, num, end_row: row, end_num: num + len }
+}
+
+/// Type the impl a dispatch picked is for, followed by the ones picked for its `where` clause
+fn impl_type(typing: &Typing, dispatch: &Dispatch) -> String {
+    match dispatch {
+        Dispatch::Static { impl_index, bounds } if bounds.is_empty() => typing.impls[*impl_index].scheme.ty.to_string(),
+        Dispatch::Static { impl_index, bounds } => {
+            let bounds: Vec<_> = bounds.iter().map(|bound| impl_type(typing, bound)).collect();
+            format!("{} [{}]", typing.impls[*impl_index].scheme.ty, bounds.join(", "))
+        }
+        dispatch => format!("{dispatch:?}"),
+    }
+}
+
+const SHOW: &str = "\
+trait Show 't:
+    show 't : String
+    twice 't : String := t.show
+trait Show for s32:
+    show 'n := \"number\"
+trait Show for 't .Option where 't is Show:
+    show 'option := match option:
+        Some 'x => x.show
+        None => \"none\"
+";
+
+#[test]
+fn static_dispatch() {
+    let src = format!("{SHOW}\
+describe 'x := x.show
+main () :=
+    1s32 .show
+    (Some 2s32).show
+    describe (Some (Some 3s32))
+    show 4s32
+    (Some 5s32) as (Show dyn)
+");
+    let (typing, resolution) = infer_source(&src);
+    assert_eq!(typing.diagnostics, []);
+    assert_eq!(scheme(&typing, &resolution, "describe"), "'a -> String where 'a is Show");
+    assert_eq!(scheme(&typing, &resolution, "show"), "'a -> String where 'a is Show");
+    let dispatch = |row, num, len| {
+        let dispatches = &typing.dispatch[&span(row, num, len)];
+        dispatches.iter().map(|dispatch| impl_type(&typing, dispatch)).collect::<Vec<_>>()
+    };
+    assert_eq!(dispatch(9, 18, 4), ["Generic"]);
+    assert_eq!(dispatch(11, 11, 4), ["(s32,)"]);
+    assert_eq!(dispatch(12, 17, 4), ["('a .Option,) [(s32,)]"]);
+    assert_eq!(dispatch(13, 5, 8), ["('a .Option,) [('a .Option,) [(s32,)]]"]);
+    assert_eq!(dispatch(14, 5, 4), ["(s32,)"]);
+    let vtable = &typing.vtables[0];
+    assert_eq!(typing.impls[vtable.impl_index].scheme.ty.to_string(), "('a .Option,)");
+    let bounds: Vec<_> = vtable.bounds.iter().map(|bound| impl_type(&typing, bound)).collect();
+    assert_eq!(bounds, ["(s32,)"]);
+}
+
+#[test]
+fn unmet_bounds() {
+    assert_eq!(messages(&format!("{SHOW}\
+describe 'x := x.show
+main () :=
+    describe 'c'
+    (Some 1.5).show
+    1 .show
+    \"text\".show
+")), [
+        "12:5: error: no impl meets `char is Show`",
+        "13:16: error: no impl meets `f64 is Show`",
+        "15:12: error: no function or trait method `show` takes a `String` first",
+    ]);
+}
+
+#[test]
+fn impls() {
+    assert_eq!(messages(&format!("{SHOW}\
+trait Show for String:
+    twice 's := s
+trait Show for char:
+    show 'c := 1
+    hide 'c := \"\"
+trait Shown for bool:
+    show 'b := \"\"
+trait Show for 't .Array:
+    show 'array := match array:
+        [] => \"\"
+        ['x, _] => x.show
+trait Show for s32:
+    show 'n := \"\"
+main () :=
+    1s32 .show
+")), [
+        "10:7: error: `show` of `Show` isn't defined",
+        "13:5: error: expected `char -> String`, found `char -> {integer}`",
+        "14:5: error: `hide` isn't a method of `Show`",
+        "15:7: error: no trait named `Shown`",
+        "20:22: error: `'a is Show` is needed, add it to the `where` clause",
+        "24:11: error: several impls meet `s32 is Show`",
+    ]);
+}
+
+#[test]
+fn dot_calls() {
+    let src = "\
+type Meters:
+    Meters s32
+size (Meters 'n) := n
+trait Size 't:
+    size 't : s32
+trait Size for String:
+    size 's := 0
+trait Length 't:
+    size 't : s32
+trait Length for String:
+    size 's := 1
+main () :=
+    (Meters 1).size
+    'c'.size
+    fn 'x: x.size
+    \"text\".size
+    1 .nonexistent
+    \"{}\".printn
+    g 'y := y
+    1 .g
+";
+    assert_eq!(messages(src), [
+        "14:9: error: no function or trait method `size` takes a `char` first",
+        "15:14: error: `.size` could call `size`, `Size.size` or `Length.size`",
+        "16:12: error: `.size` could call `Size.size` or `Length.size`",
+        "17:8: error: no function or trait method `nonexistent` takes a `{integer}` first",
+        "18:10: error: no function or trait method `printn` takes a `String` first",
+    ]);
+    let (typing, _) = infer_source(src);
+    assert_eq!(typing.types[&span(12, 16, 4)].to_string(), "s32");
+    assert!(!typing.dispatch.contains_key(&span(12, 16, 4)));
+    assert_eq!(typing.types[&span(19, 8, 1)].to_string(), "int");
+}
+
+#[test]
+fn dynamic_dispatch() {
+    let src = format!("{SHOW}\
+main () :=
+    shown := 1s32 as (Show dyn)
+    shown.show
+    shown.twice
+    'c' as (Show dyn)
+    wrap 'c'
+    1 as (Show dyn)
+wrap 'x := x as (Show dyn)
+");
+    let (typing, resolution) = infer_source(&src);
+    let messages: Vec<_> = typing.diagnostics.iter().map(|diagnostic| diagnostic.to_string()).collect();
+    assert_eq!(messages, ["14:5: error: no impl meets `char is Show`", "15:5: error: no impl meets `char is Show`"]);
+    assert_eq!(scheme(&typing, &resolution, "wrap"), "'a -> Show dyn where 'a is Show");
+    assert_eq!(typing.vtables.len(), 2);
+    assert_eq!(typing.vtables[1].span, span(15, 5, 15));
+    assert_eq!(typing.impls[typing.vtables[1].impl_index].scheme.ty.to_string(), "(s32,)");
+    let vtable = &typing.vtables[0];
+    assert_eq!((vtable.trait_name.as_str(), vtable.slots.len()), ("Show", 2));
+    assert_eq!(typing.impls[vtable.impl_index].scheme.ty.to_string(), "(s32,)");
+    assert_eq!(vtable.span, span(10, 14, 18));
+    let dynamic = |slot| vec![Dispatch::Dynamic { trait_name: "Show".into(), slot: Some(slot) }];
+    assert_eq!(typing.dispatch[&span(11, 11, 4)], dynamic(0));
+    assert_eq!(typing.dispatch[&span(12, 11, 5)], dynamic(1));
+}
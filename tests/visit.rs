@@ -0,0 +1,123 @@
+use rolang::lexer::*;
+use rolang::parser::*;
+use rolang::printer::print;
+use rolang::visit::*;
+
+/// Starts on the second line, so that no node has a zero span
+const SRC: &str = "
+#inline
+pub f 'x : s32 := x
+fact _ :=
+fact 0 ::= 1
+fact ('n > 0) ::= n * fact (n - 1)
+pub use std.(io.*, fmt as f)
+mod util
+type Opt 't where 't is Show + Eq s32:
+    Some 't
+    None
+class Point:
+    pub x: s32
+    y: s32 -> s32 .Array dyn
+trait Show 't:
+    show 't : str
+trait Show (s32, str) for Point:
+    show (?'p = \"p\") := p
+main () :=
+    mut r := match (a := 1, [2, 3]):
+        (1, ['y, _]) if y > 0 => -y
+        _ => ()
+    [1, 2] for Some 'n: print n .abs
+    while: r < 10
+    then: r .<- f
+    else: #[likely] r
+    g := fn 'a: yield (a as s64) + r
+    x .into (1,)
+";
+
+fn parse(src: &str) -> Ast {
+    let tokens: Vec<_> = TokenIterator::new(src).collect();
+    let (ast, diagnostics) = parse_with_diagnostics(&tokens);
+    dbg!(&ast);
+    assert_eq!(diagnostics, []);
+    ast
+}
+
+#[derive(Default)]
+struct Spans(Vec<Span>);
+
+impl Visit for Spans {
+    fn visit_span(&mut self, span: Span) {
+        self.0.push(span);
+    }
+}
+
+struct Erase;
+
+impl VisitMut for Erase {
+    fn visit_span_mut(&mut self, span: &mut Span) {
+        *span = Span::default();
+    }
+}
+
+#[test]
+fn every_span_is_reached() {
+    let ast = parse(SRC);
+    let mut spans = Spans::default();
+    spans.visit_ast(&ast);
+    assert_eq!(spans.0.len(), format!("{ast:?}").matches(" row:").count());
+    assert!(spans.0.iter().all(|span| span.row > 0));
+
+    let mut erased = ast.clone();
+    Erase.visit_ast_mut(&mut erased);
+    let mut spans = Spans::default();
+    spans.visit_ast(&erased);
+    assert!(spans.0.iter().all(|span| *span == Span::default()));
+    assert_eq!(erased, ast.without_spans());
+}
+
+#[derive(Default)]
+struct Names(Vec<String>);
+
+impl Visit for Names {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Name(name) = &expr.kind {
+            self.0.push(name.to_string());
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[test]
+fn override_one_node() {
+    let mut names = Names::default();
+    names.visit_ast(&parse("
+f ('n > lo) (?'m = hi) := #[cold] (fn 'x: [x, y .z] for 'e: e)
+"));
+    assert_eq!(names.0, ["lo", "hi", "x", "y", "z", "e"]);
+}
+
+struct Rename;
+
+impl Fold for Rename {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        let expr = fold_expr(self, expr);
+        match expr.kind {
+            ExprKind::Name(name) if &*name == "r" => Expr { kind: ExprKind::Name("total".into()), ..expr },
+            kind => Expr { kind, ..expr },
+        }
+    }
+}
+
+struct Identity;
+
+impl Fold for Identity {}
+
+#[test]
+fn fold() {
+    let ast = parse(SRC);
+    assert_eq!(Identity.fold_ast(ast.clone()), ast);
+
+    let src = "main () :=\n    mut r := 0\n    r .<- f (fn: r + 1)\n";
+    let renamed = Rename.fold_ast(parse(src));
+    assert_eq!(print(&renamed, src), "main () :=\n    mut r := 0\n    total .<- f fn: total + 1\n");
+}